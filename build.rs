@@ -58,23 +58,33 @@ fn dst_dirs() -> Vec<PathBuf> {
     }.iter().filter(|p| p.exists()).cloned().collect()
 }
 
-fn copy_sprite_sheet() {
-    let in_path = &res_src_path(files::SPRITE_SHEET);
+fn copy_res_file(name: &str) {
+    let in_path = &res_src_path(name);
 
     for dest in dst_dirs().iter() {
         let out_dir = dest.join(files::RES_DIR);
         ensure_dir(&out_dir);
 
-        let out_path = out_dir.join(files::SPRITE_SHEET);
+        let out_path = out_dir.join(name);
 
         if source_changed_rel(in_path, &out_path) {
             fs::copy(in_path, &out_path)
-                .expect("Failed to copy sprite sheet");
+                .expect(&format!("Failed to copy {}", name));
         }
     }
 }
 
+// for resources the game can do without
+fn copy_optional_res_file(name: &str) {
+    if res_src_path(name).exists() {
+        copy_res_file(name);
+    }
+}
+
 fn main() {
     generate_entity_store!("spec.toml", "entity_store.rs");
-    copy_sprite_sheet();
+    copy_res_file(files::SPRITE_SHEET);
+    copy_res_file(files::SPRITE_MANIFEST);
+    copy_optional_res_file(files::SPRITE_NORMAL_MAP);
+    copy_res_file(files::ENCOUNTER_TABLES);
 }
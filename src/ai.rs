@@ -5,10 +5,12 @@ use spatial_hash::SpatialHashTable;
 use content::ActionType;
 use ai_info::GlobalAiInfo;
 use append::Append;
-use direction::CardinalDirections;
 use static_grid::StaticGrid;
 use search::PathNode;
 use vec_pool::VecPool;
+use turn::TurnInfo;
+use entity_id_allocator::{EntityIdAllocator, EntityRef};
+use movement;
 
 const OCCUPIED_MOVE_COST: u32 = 1000;
 
@@ -23,7 +25,7 @@ pub struct AiEnv {
     npcs: Vec<NpcInfo>,
     movement_grid: StaticGrid<u64>,
     seq: u64,
-    paths: FnvHashMap<EntityId, Vec<PathNode>>,
+    paths: FnvHashMap<EntityRef, Vec<PathNode>>,
     path_pool: VecPool<PathNode>,
 }
 
@@ -42,7 +44,9 @@ impl AiEnv {
                                                  actions: &mut A,
                                                  entity_store: &EntityStore,
                                                  spatial_hash: &SpatialHashTable,
-                                                 global_info: &mut GlobalAiInfo)
+                                                 global_info: &mut GlobalAiInfo,
+                                                 allocator: &EntityIdAllocator,
+                                                 turn: TurnInfo)
     {
         self.seq += 1;
         self.npcs.clear();
@@ -53,6 +57,14 @@ impl AiEnv {
                 continue;
             };
 
+            if let Some(status_effects) = entity_store.status_effects.get(id) {
+                if !status_effects.can_act(turn) {
+                    // stay put, and make sure nothing plans to move into this cell
+                    *self.movement_grid.get_checked_mut(coord.cast()) = self.seq;
+                    continue;
+                }
+            }
+
             let distance = if let Some(distance) = global_info.get_distance(coord) {
                 distance
             } else {
@@ -69,7 +81,8 @@ impl AiEnv {
             a.distance.cmp(&b.distance)
         });
         for npc in self.npcs.iter() {
-            let remove_path = if let Some(path) = self.paths.get_mut(&npc.id) {
+            let entity = allocator.entity_ref(npc.id);
+            let remove_path = if let Some(path) = self.paths.get_mut(&entity) {
                 if let Some(node) = path.pop() {
                     if node.origin == npc.coord {
                         actions.append(ActionType::Walk(npc.id, node.direction));
@@ -81,17 +94,20 @@ impl AiEnv {
                 false
             };
             if remove_path {
-                self.path_pool.free(self.paths.remove(&npc.id).unwrap());
+                self.path_pool.free(self.paths.remove(&entity).unwrap());
             }
 
             let mut best_destination = None;
             let mut min_distance = ::std::u32::MAX;
-            for direction in CardinalDirections {
+            for direction in global_info.movement_mode().directions() {
                 let destination = npc.coord + direction.vector();
+                if movement::cuts_corner(spatial_hash, npc.coord, destination) {
+                    continue;
+                }
                 if let Some(distance) = global_info.get_distance(destination) {
                     if distance <= min_distance {
                         min_distance = distance;
-                        best_destination = Some((direction, destination));
+                        best_destination = Some((*direction, destination));
                     }
                 }
             }
@@ -130,7 +146,7 @@ impl AiEnv {
                                 None
                             } else {
                                 *self.movement_grid.get_checked_mut(first_coord) = self.seq;
-                                self.paths.insert(npc.id, path);
+                                self.paths.insert(entity, path);
                                 Some(first.direction)
                             }
                         } else {
@@ -156,6 +172,13 @@ impl AiEnv {
         }
     }
 
+    // forgets the path of an entity that's being deleted
+    pub fn remove_entity(&mut self, entity: EntityRef) {
+        if let Some(path) = self.paths.remove(&entity) {
+            self.path_pool.free(path);
+        }
+    }
+
     pub fn update(&mut self, change: &EntityChange, entity_store: &EntityStore) {
         use self::EntityChange::*;
         match change {
@@ -3,6 +3,7 @@ use dijkstra_map::DijkstraMap;
 use search::{self, SearchEnv, PathNode};
 use entity_store::{EntityChange, ComponentValue, EntityStore};
 use spatial_hash::{SpatialHashTable, SpatialHashCell};
+use movement::MovementMode;
 
 const DISTANCE_TO_PLAYER_THRESHOLD: u32 = 20;
 
@@ -10,6 +11,7 @@ pub struct GlobalAiInfo {
     distance_to_player: DijkstraMap,
     search_env: SearchEnv,
     player_coord: Vector2<i32>,
+    movement_mode: MovementMode,
 }
 
 fn general_can_enter(cell: &SpatialHashCell) -> bool {
@@ -17,11 +19,12 @@ fn general_can_enter(cell: &SpatialHashCell) -> bool {
 }
 
 impl GlobalAiInfo {
-    pub fn new(width: u32, height: u32) -> Self {
+    pub fn new(width: u32, height: u32, movement_mode: MovementMode) -> Self {
         Self {
             distance_to_player: DijkstraMap::new(width, height),
             search_env: SearchEnv::new(width, height),
             player_coord: Vector2::new(0, 0),
+            movement_mode,
         }
     }
 
@@ -33,6 +36,7 @@ impl GlobalAiInfo {
         self.distance_to_player.compute_distance_to_coord(spatial_hash,
                                                           self.player_coord,
                                                           DISTANCE_TO_PLAYER_THRESHOLD,
+                                                          self.movement_mode.directions(),
                                                           general_can_enter);
     }
 
@@ -49,6 +53,10 @@ impl GlobalAiInfo {
         }
     }
 
+    pub fn movement_mode(&self) -> MovementMode {
+        self.movement_mode
+    }
+
     pub fn get_distance(&self, coord: Vector2<i32>) -> Option<u32> {
         self.distance_to_player.get_distance_signed(coord)
     }
@@ -60,6 +68,6 @@ impl GlobalAiInfo {
                                path: &mut Vec<PathNode>) -> search::Result<()>
         where C: Fn(&SpatialHashCell, Vector2<u32>) -> Option<u32>,
     {
-        self.search_env.search(spatial_hash, start, self.player_coord, cost_fn, &self.distance_to_player, path)
+        self.search_env.search(spatial_hash, start, self.player_coord, self.movement_mode.directions(), cost_fn, &self.distance_to_player, path)
     }
 }
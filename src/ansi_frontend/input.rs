@@ -0,0 +1,29 @@
+use termion::event::Key;
+use input::{Input, Bindable, Modifiers, Unbindable, System};
+
+fn char_bindable(ch: char) -> Bindable {
+    match ch {
+        ' ' => Bindable::Space,
+        '\n' | '\r' => Bindable::Return,
+        _ => Bindable::Char(ch),
+    }
+}
+
+pub fn convert_key(key: Key) -> Option<Input> {
+    use self::Bindable::*;
+    let (bindable, modifiers) = match key {
+        Key::Esc => return Some(Input::Unbindable(Unbindable::Escape)),
+        // raw mode swallows the interrupt signal, so ctrl+c quits instead
+        Key::Ctrl('c') => return Some(Input::System(System::Quit)),
+        Key::Up => (Up, Modifiers::none()),
+        Key::Down => (Down, Modifiers::none()),
+        Key::Left => (Left, Modifiers::none()),
+        Key::Right => (Right, Modifiers::none()),
+        Key::Char(ch) => (char_bindable(ch), Modifiers::none()),
+        Key::Ctrl(ch) => (char_bindable(ch), Modifiers { ctrl: true, ..Modifiers::none() }),
+        Key::Alt(ch) => (char_bindable(ch), Modifiers { alt: true, ..Modifiers::none() }),
+        _ => return None,
+    };
+
+    Some(Input::Bindable(bindable, modifiers))
+}
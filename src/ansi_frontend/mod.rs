@@ -0,0 +1,417 @@
+use std::io::{self, Write, Stdout};
+use std::time::{Duration, Instant};
+use std::thread;
+use cgmath::Vector2;
+use fnv::FnvHashMap;
+use termion::{self, color, cursor, clear, style};
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::input::{TermRead, Keys};
+use termion::AsyncReader;
+
+use frontend::{self, FrontendOutput, FrontendInput, OutputWorldState, LightUpdate};
+use entity_store::{EntityStore, EntityChange, EntityId, ComponentValue, ComponentType};
+use spatial_hash::SpatialHashTable;
+use static_grid::StaticGrid;
+use vision::VisionCell;
+use content::{TileSprite, AmbientLightInfo};
+use input::Input;
+use camera::{Camera, CameraFocus};
+use util::time::duration_millis;
+use renderer::MAX_NUM_LIGHTS;
+
+mod input;
+use self::input::convert_key;
+
+const REMEMBERED_COLOUR: [u8; 3] = [40, 40, 56];
+
+// there's no vsync to pace the game loop, and redrawing too often floods slow connections
+const MIN_FRAME_DURATION_MS: u64 = 33;
+
+#[derive(Debug, Clone, Copy)]
+struct Glyph {
+    ch: char,
+    colour: [u8; 3],
+    priority: u8,
+    creature: bool,
+}
+
+fn glyph(sprite: TileSprite) -> Option<Glyph> {
+    use self::TileSprite::*;
+    let (ch, colour, priority) = match sprite {
+        Blank | _Num => return None,
+        Angler => ('@', [255, 255, 255], 6),
+        Crab => ('c', [255, 128, 64], 6),
+        Snail => ('s', [160, 224, 96], 6),
+        CrabCorpse | SnailCorpse => ('%', [160, 96, 96], 2),
+        OuterFloor => ('·', [200, 180, 120], 0),
+        InnerFloor => ('·', [160, 120, 80], 0),
+        InnerWater => ('≈', [64, 128, 255], 1),
+        OuterWall => ('█', [160, 160, 160], 3),
+        InnerWall => ('▓', [192, 176, 160], 3),
+        InnerDoor | OuterDoor => ('+', [176, 112, 48], 3),
+        InnerDoorOpening1 | InnerDoorOpening2 | InnerDoorOpening3 |
+            InnerDoorOpening4 | InnerDoorOpening5 | InnerDoorOpening6 |
+            OuterDoorOpening1 | OuterDoorOpening2 | OuterDoorOpening3 |
+            OuterDoorOpening4 | OuterDoorOpening5 | OuterDoorOpening6 => ('┼', [176, 112, 48], 3),
+        InnerDoorOpen | OuterDoorOpen => ('\'', [176, 112, 48], 3),
+        // windows share a cell with a wall
+        Window => ('▒', [128, 224, 255], 4),
+        Light => ('☼', [255, 240, 160], 5),
+        Smoke => ('░', [200, 200, 200], 4),
+    };
+
+    let creature = match sprite {
+        Angler | Crab | Snail => true,
+        _ => false,
+    };
+
+    Some(Glyph {
+        ch,
+        colour,
+        priority,
+        creature,
+    })
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnsiLight {
+    position: Vector2<f32>,
+    height: f32,
+    colour: [f32; 3],
+    intensity: f32,
+}
+
+impl LightUpdate for AnsiLight {
+    fn set_position(&mut self, position: Vector2<f32>) {
+        self.position = position;
+    }
+    fn set_height(&mut self, height: f32) {
+        self.height = height;
+    }
+    fn set_colour(&mut self, colour: [f32; 3]) {
+        self.colour = colour;
+    }
+    fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+}
+
+pub struct AnsiWorldState {
+    sprites: FnvHashMap<EntityId, TileSprite>,
+    vision: StaticGrid<VisionCell>,
+    lights: Vec<(StaticGrid<VisionCell>, AnsiLight)>,
+    next_light_index: usize,
+    num_lights: usize,
+    sky: StaticGrid<bool>,
+    ambient_light: [f32; 3],
+    sky_light: [f32; 3],
+    camera: Camera,
+    frame_count: u64,
+    total_time_ms: u64,
+}
+
+impl AnsiWorldState {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            sprites: FnvHashMap::default(),
+            vision: StaticGrid::new_default(width, height),
+            lights: Vec::new(),
+            next_light_index: 0,
+            num_lights: 0,
+            sky: StaticGrid::new_copy(width, height, false),
+            ambient_light: [0.0; 3],
+            sky_light: [0.0; 3],
+            camera: Camera::new(),
+            frame_count: 0,
+            total_time_ms: 0,
+        }
+    }
+
+    // mirrors the lighting in the tile renderer's fragment shader, sampled at the cell's centre
+    fn lit_colour(&self, coord: Vector2<u32>, vision_bitmap: u8, colour: [u8; 3]) -> [u8; 3] {
+        let mut total = self.ambient_light;
+        if *self.sky.get_checked(coord) {
+            for i in 0..3 {
+                total[i] += self.sky_light[i];
+            }
+        }
+        for &(ref grid, ref light) in self.lights[..self.num_lights].iter() {
+            let cell = grid.get_checked(coord);
+            if cell.time != self.frame_count || cell.bitmap & vision_bitmap == 0 {
+                continue;
+            }
+            let dx = light.position.x - (coord.x as f32 + 0.5);
+            let dy = light.position.y - (coord.y as f32 + 0.5);
+            let vertical = light.height / (dx * dx + dy * dy + light.height * light.height).sqrt();
+            for i in 0..3 {
+                total[i] += light.colour[i] * cell.filter[i] * light.intensity * vertical;
+            }
+        }
+
+        let mut lit = [0; 3];
+        for i in 0..3 {
+            lit[i] = (colour[i] as f32 * total[i]).min(255.0) as u8;
+        }
+        lit
+    }
+}
+
+impl<'a, 'b> OutputWorldState<'a, 'b> for AnsiWorldState {
+
+    type VisionCellGrid = &'b mut StaticGrid<VisionCell>;
+    type LightCellGrid = &'b mut StaticGrid<VisionCell>;
+    type LightUpdate = AnsiLight;
+
+    fn update(&mut self, change: &EntityChange, entity_store: &EntityStore, spatial_hash: &SpatialHashTable) {
+        self.camera.update_focus(change);
+        let sky = &mut self.sky;
+        frontend::sky_change(change, entity_store, spatial_hash, |coord, open| {
+            if let Some(cell) = sky.get_signed_mut(coord) {
+                *cell = open;
+            }
+        });
+        use self::EntityChange::*;
+        match change {
+            &Insert(id, ComponentValue::Sprite(sprite)) => {
+                self.sprites.insert(id, sprite);
+            }
+            &Remove(id, ComponentType::Sprite) => {
+                self.sprites.remove(&id);
+            }
+            _ => {}
+        }
+    }
+
+    fn set_player_position(&mut self, player_position: Vector2<f32>) {
+        self.camera.set_target(player_position);
+    }
+
+    fn set_frame_info(&mut self, frame_count: u64, total_time: Duration) {
+        self.frame_count = frame_count;
+        self.total_time_ms = duration_millis(total_time);
+    }
+
+    fn set_ambient_light(&mut self, ambient: AmbientLightInfo, sky: AmbientLightInfo) {
+        self.ambient_light = ambient.total();
+        self.sky_light = sky.total();
+    }
+
+    fn focus_camera(&mut self, focus: CameraFocus, entity_store: &EntityStore, duration: Duration) {
+        self.camera.focus(focus, entity_store, duration);
+    }
+
+    fn vision_grid(&'b mut self) -> Self::VisionCellGrid {
+        &mut self.vision
+    }
+
+    fn next_light(&'b mut self) -> Option<(Self::LightCellGrid, &'b mut Self::LightUpdate)> {
+        if self.next_light_index < MAX_NUM_LIGHTS {
+            let index = self.next_light_index;
+            self.next_light_index += 1;
+
+            if index == self.lights.len() {
+                let grid = StaticGrid::new_default(self.vision.width(), self.vision.height());
+                self.lights.push((grid, Default::default()));
+            }
+
+            let &mut (ref mut grid, ref mut light) = &mut self.lights[index];
+            Some((grid, light))
+        } else {
+            None
+        }
+    }
+}
+
+pub struct AnsiFrontendOutput {
+    stdout: RawTerminal<Stdout>,
+    world_state: AnsiWorldState,
+    glyphs: StaticGrid<Option<Glyph>>,
+    top_left: Vector2<i32>,
+    terminal_size: (u16, u16),
+    cursor: Option<Vector2<i32>>,
+    description: Option<String>,
+    last_draw: Instant,
+}
+
+pub struct AnsiFrontendInput {
+    keys: Keys<AsyncReader>,
+}
+
+pub fn create() -> (AnsiFrontendInput, AnsiFrontendOutput) {
+    let mut stdout = io::stdout().into_raw_mode().expect("Failed to enter raw mode");
+    write!(stdout, "{}{}", clear::All, cursor::Hide).expect("Failed to write to terminal");
+
+    let input = AnsiFrontendInput {
+        keys: termion::async_stdin().keys(),
+    };
+
+    let output = AnsiFrontendOutput {
+        stdout,
+        world_state: AnsiWorldState::new(0, 0),
+        glyphs: StaticGrid::new_copy(0, 0, None),
+        top_left: Vector2::new(0, 0),
+        terminal_size: (0, 0),
+        cursor: None,
+        description: None,
+        last_draw: Instant::now(),
+    };
+
+    (input, output)
+}
+
+impl FrontendInput for AnsiFrontendInput {
+    fn with_input<F: FnMut(Input)>(&mut self, mut f: F) {
+        // the async reader runs out of keys rather than blocking
+        while let Some(key) = self.keys.next() {
+            if let Ok(key) = key {
+                if let Some(input) = convert_key(key) {
+                    f(input);
+                }
+            }
+        }
+    }
+}
+
+impl AnsiFrontendOutput {
+    fn populate_glyphs(&mut self, entity_store: &EntityStore) {
+        for glyph in self.glyphs.iter_mut() {
+            *glyph = None;
+        }
+
+        let frame_count = self.world_state.frame_count;
+        for (id, sprite) in self.world_state.sprites.iter() {
+            let coord = if let Some(coord) = entity_store.coord.get(id) {
+                *coord
+            } else {
+                continue;
+            };
+            let glyph = if let Some(glyph) = glyph(*sprite) {
+                glyph
+            } else {
+                continue;
+            };
+            if glyph.creature {
+                let visible = self.world_state.vision.get_signed(coord)
+                    .map_or(false, |cell| cell.time == frame_count);
+                if !visible {
+                    continue;
+                }
+            }
+            if let Some(cell) = self.glyphs.get_signed_mut(coord) {
+                let replace = cell.map_or(true, |current| glyph.priority >= current.priority);
+                if replace {
+                    *cell = Some(glyph);
+                }
+            }
+        }
+    }
+
+    fn render_cell(&self, coord: Vector2<i32>, output: &mut String) {
+        let (ch, colour) = match (self.world_state.vision.get_signed(coord), self.glyphs.get_signed(coord)) {
+            (Some(vision_cell), Some(&Some(glyph))) if vision_cell.time != 0 => {
+                let colour = if vision_cell.time == self.world_state.frame_count {
+                    self.world_state.lit_colour(coord.cast(), vision_cell.bitmap, glyph.colour)
+                } else {
+                    REMEMBERED_COLOUR
+                };
+                (glyph.ch, colour)
+            }
+            _ => (' ', [0, 0, 0]),
+        };
+
+        let is_cursor = self.cursor == Some(coord);
+        if is_cursor {
+            output.push_str(&format!("{}", style::Invert));
+        }
+        output.push_str(&format!("{}{}", color::Fg(color::Rgb(colour[0], colour[1], colour[2])), ch));
+        if is_cursor {
+            output.push_str(&format!("{}", style::NoInvert));
+        }
+    }
+}
+
+impl<'a> FrontendOutput<'a> for AnsiFrontendOutput {
+    type WorldState = AnsiWorldState;
+    fn with_world_state<F: FnMut(&mut Self::WorldState)>(&'a mut self, mut f: F) {
+        self.world_state.next_light_index = 0;
+        f(&mut self.world_state);
+        self.world_state.num_lights = self.world_state.next_light_index;
+    }
+    fn draw(&mut self, entity_store: &EntityStore) {
+        let elapsed = self.last_draw.elapsed();
+        let min_frame_duration = Duration::from_millis(MIN_FRAME_DURATION_MS);
+        if elapsed < min_frame_duration {
+            thread::sleep(min_frame_duration - elapsed);
+        }
+        self.last_draw = Instant::now();
+
+        let terminal_size = termion::terminal_size().unwrap_or((80, 24));
+        let mut output = String::new();
+        if terminal_size != self.terminal_size {
+            self.terminal_size = terminal_size;
+            output.push_str(&format!("{}", clear::All));
+        }
+
+        // the bottom row is for the description
+        let (width, height) = terminal_size;
+        let view_height = height.saturating_sub(1);
+
+        let view_size = Vector2::new(width, view_height).cast();
+        let world_size = Vector2::new(self.world_state.vision.width(), self.world_state.vision.height()).cast();
+        let time_ms = self.world_state.total_time_ms;
+        let mid_position = self.world_state.camera.update(time_ms, view_size, world_size);
+        let mid = Vector2::new((mid_position.x + 0.5).floor() as i32,
+                               (mid_position.y + 0.5).floor() as i32);
+        self.top_left = mid - Vector2::new(width as i32 / 2, view_height as i32 / 2);
+
+        self.populate_glyphs(entity_store);
+
+        for y in 0..view_height {
+            output.push_str(&format!("{}", cursor::Goto(1, y + 1)));
+            for x in 0..width {
+                let coord = self.top_left + Vector2::new(x as i32, y as i32);
+                self.render_cell(coord, &mut output);
+            }
+        }
+
+        output.push_str(&format!("{}{}{}", cursor::Goto(1, height), color::Fg(color::Reset), clear::CurrentLine));
+        if let Some(ref description) = self.description {
+            output.push_str(&description.chars().take(width as usize).collect::<String>());
+        }
+
+        self.stdout.write_all(output.as_bytes()).expect("Failed to write to terminal");
+        self.stdout.flush().expect("Failed to flush terminal");
+    }
+    fn handle_resize(&mut self, _width: u16, _height: u16) {
+        // the terminal size is checked on every draw
+    }
+    fn update_world_size(&mut self, width: u32, height: u32) {
+        self.world_state.vision = StaticGrid::new_default(width, height);
+        self.world_state.lights.clear();
+        self.world_state.sky = StaticGrid::new_copy(width, height, false);
+        self.glyphs = StaticGrid::new_copy(width, height, None);
+    }
+    fn world_coord(&self, x: i32, y: i32) -> Vector2<i32> {
+        // positions are terminal cells rather than pixels
+        self.top_left + Vector2::new(x, y)
+    }
+    fn set_cursor(&mut self, cursor: Option<Vector2<i32>>) {
+        self.cursor = cursor;
+    }
+    fn set_description(&mut self, description: Option<&str>) {
+        self.description = description.map(|d| d.to_string());
+    }
+    fn request_screenshot(&mut self) {
+        // there are no pixels to capture
+    }
+    // each cell is always a single character
+    fn zoom_in(&mut self) {}
+    fn zoom_out(&mut self) {}
+}
+
+impl Drop for AnsiFrontendOutput {
+    fn drop(&mut self) {
+        let _ = write!(self.stdout, "{}{}{}{}", color::Fg(color::Reset), clear::All, cursor::Goto(1, 1), cursor::Show);
+        let _ = self.stdout.flush();
+    }
+}
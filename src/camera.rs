@@ -0,0 +1,179 @@
+use std::time::Duration;
+use cgmath::Vector2;
+use entity_store::{EntityId, EntityStore, EntityChange, ComponentValue};
+use util::time::duration_millis;
+
+// how far (in cells) the target can stray from the middle of the view before the camera follows
+const DEAD_ZONE: Vector2<f32> = Vector2 { x: 2.0, y: 1.0 };
+
+// proportion of the remaining distance covered per second is 1 - e^-rate
+const EASING_RATE: f32 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraFocus {
+    Position(Vector2<f32>),
+    Entity(EntityId),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActiveFocus {
+    focus: CameraFocus,
+    position: Vector2<f32>,
+    remaining_ms: u64,
+}
+
+fn dead_zone_axis(position: f32, goal: f32, half_width: f32) -> f32 {
+    let delta = goal - position;
+    if delta > half_width {
+        goal - half_width
+    } else if delta < -half_width {
+        goal + half_width
+    } else {
+        position
+    }
+}
+
+// keeps space outside the world out of view, centring worlds smaller than the view
+fn clamp_axis(position: f32, view_size: f32, world_size: f32) -> f32 {
+    // positions refer to the top-left corner of the cell in the middle of the view
+    let min = view_size / 2.0 - 0.5;
+    let max = world_size - view_size / 2.0 - 0.5;
+    if min > max {
+        (world_size - 1.0) / 2.0
+    } else {
+        position.max(min).min(max)
+    }
+}
+
+pub struct Camera {
+    position: Vector2<f32>,
+    initialised: bool,
+    target: Option<Vector2<f32>>,
+    focus: Option<ActiveFocus>,
+    last_time_ms: Option<u64>,
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Self {
+            position: Vector2::new(0.0, 0.0),
+            initialised: false,
+            target: None,
+            focus: None,
+            last_time_ms: None,
+        }
+    }
+
+    pub fn position(&self) -> Vector2<f32> {
+        self.position
+    }
+
+    pub fn set_target(&mut self, target: Vector2<f32>) {
+        self.target = Some(target);
+    }
+
+    pub fn focus(&mut self, focus: CameraFocus, entity_store: &EntityStore, duration: Duration) {
+        let position = match focus {
+            CameraFocus::Position(position) => Some(position),
+            CameraFocus::Entity(id) => entity_store.position.get(&id).cloned(),
+        };
+
+        if let Some(position) = position {
+            self.focus = Some(ActiveFocus {
+                focus,
+                position,
+                remaining_ms: duration_millis(duration),
+            });
+        }
+    }
+
+    // follows the focused entity if it moves
+    pub fn update_focus(&mut self, change: &EntityChange) {
+        if let &EntityChange::Insert(id, ComponentValue::Position(position)) = change {
+            if let Some(ref mut focus) = self.focus {
+                if focus.focus == CameraFocus::Entity(id) {
+                    focus.position = position;
+                }
+            }
+        }
+    }
+
+    // view and world sizes are in cells; returns the new position
+    pub fn update(&mut self, time_ms: u64, view_size: Vector2<f32>, world_size: Vector2<f32>) -> Vector2<f32> {
+        let elapsed_ms = self.last_time_ms.map_or(0, |last_time_ms| time_ms.saturating_sub(last_time_ms));
+        self.last_time_ms = Some(time_ms);
+
+        let focus_position = self.focus.map(|focus| focus.position);
+        let expired = if let Some(ref mut focus) = self.focus {
+            focus.remaining_ms = focus.remaining_ms.saturating_sub(elapsed_ms);
+            focus.remaining_ms == 0
+        } else {
+            false
+        };
+        if expired {
+            self.focus = None;
+        }
+
+        let (goal, dead_zone) = if let Some(position) = focus_position {
+            (position, Vector2::new(0.0, 0.0))
+        } else if let Some(target) = self.target {
+            (target, DEAD_ZONE)
+        } else {
+            return self.position;
+        };
+
+        let position = if self.initialised {
+            let desired = Vector2::new(dead_zone_axis(self.position.x, goal.x, dead_zone.x),
+                                       dead_zone_axis(self.position.y, goal.y, dead_zone.y));
+            let progress = 1.0 - (-EASING_RATE * elapsed_ms as f32 / 1000.0).exp();
+            self.position + (desired - self.position) * progress
+        } else {
+            // start out centred rather than sliding in from the corner
+            self.initialised = true;
+            goal
+        };
+
+        self.position = Vector2::new(clamp_axis(position.x, view_size.x, world_size.x),
+                                     clamp_axis(position.y, view_size.y, world_size.y));
+
+        self.position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dead_zone_holds_still_inside() {
+        assert_eq!(dead_zone_axis(5.0, 5.0, 2.0), 5.0);
+        assert_eq!(dead_zone_axis(5.0, 6.5, 2.0), 5.0);
+        assert_eq!(dead_zone_axis(5.0, 3.0, 2.0), 5.0);
+    }
+
+    #[test]
+    fn dead_zone_follows_outside() {
+        assert_eq!(dead_zone_axis(5.0, 9.0, 2.0), 7.0);
+        assert_eq!(dead_zone_axis(5.0, 1.0, 2.0), 3.0);
+    }
+
+    #[test]
+    fn clamp_keeps_view_inside_world() {
+        // a 10 cell view over a 40 cell world can centre between cells 4.5 and 34.5
+        assert_eq!(clamp_axis(0.0, 10.0, 40.0), 4.5);
+        assert_eq!(clamp_axis(20.0, 10.0, 40.0), 20.0);
+        assert_eq!(clamp_axis(39.0, 10.0, 40.0), 34.5);
+    }
+
+    #[test]
+    fn clamp_centres_small_worlds() {
+        assert_eq!(clamp_axis(0.0, 10.0, 6.0), 2.5);
+        assert_eq!(clamp_axis(5.0, 10.0, 6.0), 2.5);
+    }
+
+    #[test]
+    fn clamp_allows_exact_fit() {
+        assert_eq!(clamp_axis(0.0, 10.0, 10.0), 4.5);
+        assert_eq!(clamp_axis(9.0, 10.0, 10.0), 4.5);
+    }
+}
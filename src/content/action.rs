@@ -1,11 +1,11 @@
 use entity_store::{EntityId, EntityStore, insert};
-use direction::CardinalDirection;
+use direction::Direction;
 use append::Append;
 use content::{ChangeDesc, DoorState};
 
 #[derive(Debug, Clone, Copy)]
 pub enum ActionType {
-    Walk(EntityId, CardinalDirection),
+    Walk(EntityId, Direction),
     CloseDoor(EntityId),
 }
 
@@ -19,7 +19,7 @@ impl ActionType {
     }
 }
 
-pub fn walk<A: Append<ChangeDesc>>(id: EntityId, dir: CardinalDirection, entity_store: &EntityStore, changes: &mut A) {
+pub fn walk<A: Append<ChangeDesc>>(id: EntityId, dir: Direction, entity_store: &EntityStore, changes: &mut A) {
     let current_coord = entity_store.coord.get(&id).cloned().expect("Expected coord");
     let new_coord = current_coord + dir.vector();
 
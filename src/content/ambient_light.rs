@@ -0,0 +1,127 @@
+// light that reaches cells without coming from anywhere in particular
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AmbientLightInfo {
+    pub intensity: f32,
+    pub colour: [f32; 3],
+}
+
+impl AmbientLightInfo {
+    // the colour scaled by the intensity
+    pub fn total(self) -> [f32; 3] {
+        [self.colour[0] * self.intensity, self.colour[1] * self.intensity, self.colour[2] * self.intensity]
+    }
+
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let mut colour = [0.0; 3];
+        for i in 0..3 {
+            colour[i] = self.colour[i] + (other.colour[i] - self.colour[i]) * t;
+        }
+        Self {
+            intensity: self.intensity + (other.intensity - self.intensity) * t,
+            colour,
+        }
+    }
+}
+
+// lights every cell, under a roof or not, so interiors are never completely dark.
+// the settings file can override it.
+pub const DEFAULT_AMBIENT_LIGHT: AmbientLightInfo = AmbientLightInfo {
+    intensity: 0.1,
+    colour: [1.0, 1.0, 1.0],
+};
+
+// the day moves on with each turn, rather than in real time, so it doesn't pass while the player
+// is thinking. this counts the npcs' turns as well as the player's.
+const DAY_NUM_TURNS: u64 = 2000;
+
+// the game starts in the morning
+const START_TIME_OF_DAY: f32 = 0.35;
+
+const NIGHT: AmbientLightInfo = AmbientLightInfo { intensity: 0.03, colour: [0.4, 0.5, 1.0] };
+const SUNRISE: AmbientLightInfo = AmbientLightInfo { intensity: 0.25, colour: [1.0, 0.65, 0.45] };
+const DAY: AmbientLightInfo = AmbientLightInfo { intensity: 0.5, colour: [1.0, 0.97, 0.9] };
+const SUNSET: AmbientLightInfo = AmbientLightInfo { intensity: 0.25, colour: [1.0, 0.5, 0.3] };
+
+// the sky at each time of day, from midnight to midnight
+const SKY_KEYFRAMES: [(f32, AmbientLightInfo); 8] = [
+    (0.0, NIGHT),
+    (0.22, NIGHT),
+    (0.28, SUNRISE),
+    (0.35, DAY),
+    (0.65, DAY),
+    (0.72, SUNSET),
+    (0.78, NIGHT),
+    (1.0, NIGHT),
+];
+
+// from 0 at midnight to 1 at the following midnight
+pub fn time_of_day(turn_count: u64) -> f32 {
+    let day_turns = turn_count % DAY_NUM_TURNS;
+    (START_TIME_OF_DAY + day_turns as f32 / DAY_NUM_TURNS as f32) % 1.0
+}
+
+// the light from the sky, which only reaches cells that aren't under a roof
+pub fn sky_light(time_of_day: f32) -> AmbientLightInfo {
+    for window in SKY_KEYFRAMES.windows(2) {
+        let (start, from) = window[0];
+        let (end, to) = window[1];
+        if time_of_day < end {
+            return from.lerp(to, (time_of_day - start) / (end - start));
+        }
+    }
+    NIGHT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: AmbientLightInfo, b: AmbientLightInfo) {
+        assert!((a.intensity - b.intensity).abs() < 0.0001, "{:?} != {:?}", a, b);
+        for i in 0..3 {
+            assert!((a.colour[i] - b.colour[i]).abs() < 0.0001, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn keyframes_are_reached_exactly() {
+        for &(time, light) in SKY_KEYFRAMES.iter() {
+            assert_close(sky_light(time), light);
+        }
+    }
+
+    #[test]
+    fn halfway_between_keyframes_is_blended() {
+        assert_close(sky_light(0.25), NIGHT.lerp(SUNRISE, 0.5));
+        assert_close(sky_light(0.75), SUNSET.lerp(NIGHT, 0.5));
+    }
+
+    #[test]
+    fn sky_is_continuous_across_midnight() {
+        assert_close(sky_light(0.9999), sky_light(0.0));
+    }
+
+    #[test]
+    fn day_starts_in_the_morning() {
+        assert!((time_of_day(0) - START_TIME_OF_DAY).abs() < 0.0001);
+        assert_close(sky_light(time_of_day(0)), DAY);
+    }
+
+    #[test]
+    fn time_of_day_wraps_at_midnight() {
+        let turns_to_midnight = ((1.0 - START_TIME_OF_DAY) * DAY_NUM_TURNS as f32).round() as u64;
+        assert!(time_of_day(turns_to_midnight - 1) > 0.99);
+        // rounding may leave midnight itself just either side of 0
+        let midnight = time_of_day(turns_to_midnight);
+        assert!(midnight < 0.0001 || midnight > 0.9999);
+        assert!(time_of_day(turns_to_midnight + 1) < 0.01);
+    }
+
+    #[test]
+    fn days_repeat() {
+        for &turn in [0, 1, 500, 1999].iter() {
+            assert!((time_of_day(turn) - time_of_day(turn + DAY_NUM_TURNS)).abs() < 0.0001);
+            assert!((time_of_day(turn) - time_of_day(turn + 3 * DAY_NUM_TURNS)).abs() < 0.0001);
+        }
+    }
+}
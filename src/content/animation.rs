@@ -2,8 +2,8 @@ use std::time::Duration;
 use cgmath::Vector2;
 
 use append::Append;
-use entity_store::{EntityId, EntityChange, insert};
-use content::SpriteAnimation;
+use entity_store::{EntityId, EntityChange, insert, remove};
+use content::{SpriteAnimation, SpriteEffectInfo, FlashInfo};
 
 pub enum Animation {
     Slide {
@@ -20,7 +20,8 @@ pub enum Animation {
         progress: f32,
         duration: Duration,
         turnaround_progress: f32,
-        mid_change: Option<EntityChange>,
+        // applied when the bump lands
+        mid_changes: Vec<EntityChange>,
     },
     Sprites {
         id: EntityId,
@@ -29,6 +30,22 @@ pub enum Animation {
         index: usize,
         remaining: Duration,
     },
+    SpriteEffectTransition {
+        id: EntityId,
+        from: SpriteEffectInfo,
+        to: SpriteEffectInfo,
+        then: EntityChange,
+        progress: f32,
+        duration: Duration,
+    },
+    // the flash is removed when it finishes, leaving the sprite's effect as it was
+    Flash {
+        id: EntityId,
+        from: FlashInfo,
+        to: FlashInfo,
+        progress: f32,
+        duration: Duration,
+    },
 }
 
 pub enum AnimationStatus {
@@ -68,20 +85,20 @@ impl Animation {
                     AnimationStatus::Finished
                 }
             }
-            BumpSlide { id, base, path, mut progress, duration, turnaround_progress, mid_change } => {
+            BumpSlide { id, base, path, mut progress, duration, turnaround_progress, mut mid_changes } => {
                 let progress_delta = duration_ratio(time_delta, duration) * 2.0;
                 progress += progress_delta;
                 if progress > 2.0 {
                     progress = 2.0;
                 }
 
-                let (mut mult, mid_change) = if progress < 1.0 {
-                    (progress, mid_change)
+                let mut mult = if progress < 1.0 {
+                    progress
                 } else {
-                    if let Some(change) = mid_change {
+                    for change in mid_changes.drain(..) {
                         changes.append(Checked(change));
                     }
-                    (2.0 - progress, None)
+                    2.0 - progress
                 };
 
                 mult *= turnaround_progress;
@@ -91,7 +108,7 @@ impl Animation {
 
                 if progress < 2.0 {
                     AnimationStatus::Running(Animation::BumpSlide {
-                        id, base, path, progress, duration, turnaround_progress, mid_change
+                        id, base, path, progress, duration, turnaround_progress, mid_changes
                     })
                 } else {
                     AnimationStatus::Finished
@@ -127,6 +144,28 @@ impl Animation {
 
                 AnimationStatus::Running(Animation::Sprites { id, animation, then, index, remaining })
             }
+            SpriteEffectTransition { id, from, to, then, mut progress, duration } => {
+                progress += duration_ratio(time_delta, duration);
+                if progress >= 1.0 {
+                    changes.append(Checked(then));
+                    return AnimationStatus::Finished;
+                }
+
+                changes.append(Unchecked(insert::sprite_effect(id, from.lerp(to, progress))));
+
+                AnimationStatus::Running(Animation::SpriteEffectTransition { id, from, to, then, progress, duration })
+            }
+            Flash { id, from, to, mut progress, duration } => {
+                progress += duration_ratio(time_delta, duration);
+                if progress >= 1.0 {
+                    changes.append(Checked(remove::flash(id)));
+                    return AnimationStatus::Finished;
+                }
+
+                changes.append(Unchecked(insert::flash(id, from.lerp(to, progress))));
+
+                AnimationStatus::Running(Animation::Flash { id, from, to, progress, duration })
+            }
         }
     }
 }
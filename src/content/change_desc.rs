@@ -2,7 +2,7 @@ use std::time::Duration;
 use cgmath::Vector2;
 
 use entity_store::{EntityId, EntityChange};
-use content::{Animation, SpriteAnimation};
+use content::{Animation, SpriteAnimation, SpriteEffectInfo, FlashInfo};
 
 pub enum ChangeDesc {
     Immediate(EntityChange),
@@ -28,7 +28,7 @@ impl ChangeDesc {
                       target: Vector2<f32>,
                       duration: Duration,
                       turnaround_progress: f32,
-                      mid_change: Option<EntityChange>) -> Self {
+                      mid_changes: Vec<EntityChange>) -> Self {
         let animation = Animation::BumpSlide {
             id,
             base: from,
@@ -36,7 +36,7 @@ impl ChangeDesc {
             progress: 0.0,
             duration,
             turnaround_progress,
-            mid_change,
+            mid_changes,
         };
         ChangeDesc::Animation(animation)
     }
@@ -50,4 +50,29 @@ impl ChangeDesc {
         };
         ChangeDesc::Animation(animation)
     }
+    pub fn sprite_effect_transition(id: EntityId,
+                                    from: SpriteEffectInfo,
+                                    to: SpriteEffectInfo,
+                                    duration: Duration,
+                                    then: EntityChange) -> Self {
+        let animation = Animation::SpriteEffectTransition {
+            id,
+            from,
+            to,
+            then,
+            progress: 0.0,
+            duration,
+        };
+        ChangeDesc::Animation(animation)
+    }
+    pub fn flash(id: EntityId, from: FlashInfo, to: FlashInfo, duration: Duration) -> Self {
+        let animation = Animation::Flash {
+            id,
+            from,
+            to,
+            progress: 0.0,
+            duration,
+        };
+        ChangeDesc::Animation(animation)
+    }
 }
@@ -0,0 +1,25 @@
+use content::{TileSprite, SpriteAnimation, sprite_animation};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeathType {
+    Crab,
+    Snail,
+}
+
+use self::DeathType::*;
+
+impl DeathType {
+    pub fn animation(self) -> SpriteAnimation {
+        match self {
+            Crab => sprite_animation::CRAB_DEATH,
+            Snail => sprite_animation::SNAIL_DEATH,
+        }
+    }
+
+    pub fn corpse_sprite(self) -> TileSprite {
+        match self {
+            Crab => TileSprite::CrabCorpse,
+            Snail => TileSprite::SnailCorpse,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+use rand::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Creature {
+    Crab,
+    Snail,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Encounter {
+    pub creature: Creature,
+    pub weight: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncounterTable {
+    pub encounters: Vec<Encounter>,
+}
+
+impl EncounterTable {
+    pub fn choose<R: Rng>(&self, rng: &mut R) -> Option<Creature> {
+        let total = self.encounters.iter().map(|e| e.weight).sum::<u32>();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.gen_range(0, total);
+        for encounter in self.encounters.iter() {
+            if roll < encounter.weight {
+                return Some(encounter.creature);
+            }
+            roll -= encounter.weight;
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncounterTables {
+    pub tables: BTreeMap<String, EncounterTable>,
+}
+
+impl EncounterTables {
+    pub fn get(&self, name: &str) -> Option<&EncounterTable> {
+        self.tables.get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::XorShiftRng;
+
+    fn table(weights: &[(Creature, u32)]) -> EncounterTable {
+        EncounterTable {
+            encounters: weights.iter().map(|&(creature, weight)| Encounter { creature, weight }).collect(),
+        }
+    }
+
+    #[test]
+    fn empty_table_chooses_nothing() {
+        let mut rng = XorShiftRng::new_unseeded();
+        assert_eq!(table(&[]).choose(&mut rng), None);
+        assert_eq!(table(&[(Creature::Crab, 0), (Creature::Snail, 0)]).choose(&mut rng), None);
+    }
+
+    #[test]
+    fn zero_weight_is_never_chosen() {
+        let mut rng = XorShiftRng::new_unseeded();
+        let table = table(&[(Creature::Crab, 0), (Creature::Snail, 3), (Creature::Crab, 0)]);
+        for _ in 0..100 {
+            assert_eq!(table.choose(&mut rng), Some(Creature::Snail));
+        }
+    }
+
+    #[test]
+    fn choices_follow_weights() {
+        let mut rng = XorShiftRng::new_unseeded();
+        let table = table(&[(Creature::Crab, 3), (Creature::Snail, 1)]);
+        let num_crabs = (0..4000).filter(|_| table.choose(&mut rng) == Some(Creature::Crab)).count();
+        assert!(num_crabs > 2800 && num_crabs < 3200);
+    }
+}
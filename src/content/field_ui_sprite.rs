@@ -1,8 +1,14 @@
+enum_from_primitive! {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FieldUiSprite {
     HealthFull,
     HealthEmpty,
+    Poison,
+    Stun,
+    Slow,
+    Cursor,
     _Num,
 }
+}
 
 pub const NUM_FIELD_UI_SPRITES: usize = FieldUiSprite::_Num as usize;
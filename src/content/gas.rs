@@ -0,0 +1,16 @@
+// fully dense gas blocks this much of the vision and light passing through it
+pub const MAX_OPACITY: f32 = 0.35;
+
+// how much gas fills a cell, from 0 to 1
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GasInfo {
+    pub density: f32,
+}
+
+impl GasInfo {
+    pub fn new(density: f32) -> Self {
+        Self {
+            density,
+        }
+    }
+}
@@ -7,6 +7,9 @@ pub use self::tile_sprite::TileSprite;
 pub mod door_type;
 pub use self::door_type::DoorType;
 
+pub mod death_type;
+pub use self::death_type::DeathType;
+
 pub mod depth;
 pub use self::depth::{DepthType, DepthInfo};
 
@@ -23,11 +26,23 @@ pub mod sprite_animation;
 pub use self::sprite_animation::SpriteAnimation;
 
 pub mod sprite_effect;
-pub use self::sprite_effect::{SpriteEffect, SpriteEffectInfo};
+pub use self::sprite_effect::{SpriteEffect, SpriteEffectInfo, OutlineInfo, FlashInfo};
 
 pub mod light;
 pub use self::light::LightInfo;
 
+pub mod ambient_light;
+pub use self::ambient_light::AmbientLightInfo;
+
+pub mod tint;
+pub use self::tint::TintInfo;
+
+pub mod gas;
+pub use self::gas::GasInfo;
+
+pub mod particle;
+pub use self::particle::{ParticleType, ParticleBurst, ParticleEmitterInfo};
+
 pub mod field_ui_sprite;
 pub use self::field_ui_sprite::FieldUiSprite;
 
@@ -36,3 +51,12 @@ pub use self::health::HealthInfo;
 
 pub mod field_ui_offsets;
 pub use self::field_ui_offsets::FieldUiOffsets;
+
+pub mod status_effect;
+pub use self::status_effect::{StatusEffect, StatusEffectType, StatusEffectsInfo};
+
+pub mod encounter;
+pub use self::encounter::{Creature, Encounter, EncounterTable, EncounterTables};
+
+pub mod spawner;
+pub use self::spawner::SpawnerInfo;
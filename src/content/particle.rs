@@ -0,0 +1,96 @@
+use cgmath::Vector2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParticleType {
+    Sparks,
+    Splash,
+    Dust,
+}
+
+// how the particles in a burst are spread out, with ranges picked from at random for each particle
+#[derive(Clone, Copy, Debug)]
+pub struct ParticleBurst {
+    pub count: u32,
+    pub colour: [f32; 3],
+    // radians either side of the emitter's direction
+    pub spread: f32,
+    // cells per second
+    pub speed: (f32, f32),
+    pub lifetime_ms: (f32, f32),
+    pub size_px: (f32, f32),
+    // cells per second per second, towards positive y
+    pub gravity: f32,
+}
+
+use self::ParticleType::*;
+
+impl ParticleType {
+    pub fn burst(self) -> ParticleBurst {
+        match self {
+            Sparks => ParticleBurst {
+                count: 12,
+                colour: [1.0, 0.8, 0.3],
+                spread: 1.0,
+                speed: (2.0, 5.0),
+                lifetime_ms: (150.0, 350.0),
+                size_px: (1.0, 2.0),
+                gravity: 6.0,
+            },
+            Splash => ParticleBurst {
+                count: 10,
+                colour: [0.5, 0.7, 1.0],
+                spread: ::std::f32::consts::PI,
+                speed: (0.5, 1.5),
+                lifetime_ms: (200.0, 400.0),
+                size_px: (1.0, 2.0),
+                gravity: 8.0,
+            },
+            Dust => ParticleBurst {
+                count: 16,
+                colour: [0.6, 0.55, 0.45],
+                spread: ::std::f32::consts::PI,
+                speed: (0.1, 0.6),
+                lifetime_ms: (400.0, 900.0),
+                size_px: (1.0, 3.0),
+                gravity: -0.3,
+            },
+        }
+    }
+}
+
+// inserting this on an entity emits a burst of particles, so re-inserting it emits another
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ParticleEmitterInfo {
+    pub typ: ParticleType,
+    // in cells, from the top-left of the world
+    pub position: Vector2<f32>,
+    // particles fly out in all directions when this is zero
+    pub direction: Vector2<f32>,
+}
+
+impl ParticleEmitterInfo {
+    // thrown away from whatever struck the thing being hit
+    pub fn sparks(position: Vector2<f32>, direction: Vector2<f32>) -> Self {
+        Self {
+            typ: Sparks,
+            position,
+            direction,
+        }
+    }
+
+    pub fn splash(position: Vector2<f32>) -> Self {
+        Self {
+            typ: Splash,
+            position,
+            direction: Vector2::new(0.0, 0.0),
+        }
+    }
+
+    pub fn dust(position: Vector2<f32>) -> Self {
+        Self {
+            typ: Dust,
+            position,
+            direction: Vector2::new(0.0, 0.0),
+        }
+    }
+}
@@ -0,0 +1,18 @@
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpawnerInfo {
+    pub table: String,
+    pub period: u64,
+    pub population_cap: usize,
+    pub radius: i32,
+}
+
+impl SpawnerInfo {
+    pub fn new<S: Into<String>>(table: S, period: u64, population_cap: usize, radius: i32) -> Self {
+        Self {
+            table: table.into(),
+            period,
+            population_cap,
+            radius,
+        }
+    }
+}
@@ -3,6 +3,7 @@ use content::TileSprite;
 pub type SpriteAnimation = &'static [SpriteAnimationFrame];
 
 const DOOR_MILLIS: u32 = 16;
+const DEATH_MILLIS: u32 = 48;
 
 use self::TileSprite::*;
 
@@ -42,6 +43,24 @@ pub const OUTER_DOOR_CLOSE: SpriteAnimation = &[
     SpriteAnimationFrame { sprite: OuterDoorOpening1, millis: DOOR_MILLIS },
 ];
 
+pub const CRAB_DEATH: SpriteAnimation = &[
+    SpriteAnimationFrame { sprite: Crab, millis: DEATH_MILLIS },
+    SpriteAnimationFrame { sprite: Blank, millis: DEATH_MILLIS },
+    SpriteAnimationFrame { sprite: Crab, millis: DEATH_MILLIS },
+    SpriteAnimationFrame { sprite: Blank, millis: DEATH_MILLIS },
+    SpriteAnimationFrame { sprite: Crab, millis: DEATH_MILLIS },
+    SpriteAnimationFrame { sprite: CrabCorpse, millis: DEATH_MILLIS },
+];
+
+pub const SNAIL_DEATH: SpriteAnimation = &[
+    SpriteAnimationFrame { sprite: Snail, millis: DEATH_MILLIS },
+    SpriteAnimationFrame { sprite: Blank, millis: DEATH_MILLIS },
+    SpriteAnimationFrame { sprite: Snail, millis: DEATH_MILLIS },
+    SpriteAnimationFrame { sprite: Blank, millis: DEATH_MILLIS },
+    SpriteAnimationFrame { sprite: Snail, millis: DEATH_MILLIS },
+    SpriteAnimationFrame { sprite: SnailCorpse, millis: DEATH_MILLIS },
+];
+
 pub struct SpriteAnimationFrame {
     pub sprite: TileSprite,
     pub millis: u32,
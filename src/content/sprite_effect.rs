@@ -1,6 +1,23 @@
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SpriteEffect {
     Water,
+    Smoke,
+    HeatShimmer,
+    Glow,
+    Dissolve,
+}
+
+impl SpriteEffect {
+    // translucent sprites are drawn after everything else, and don't hide what's behind them
+    pub fn is_translucent(self) -> bool {
+        match self {
+            SpriteEffect::Smoke => true,
+            SpriteEffect::Water |
+                SpriteEffect::HeatShimmer |
+                SpriteEffect::Glow |
+                SpriteEffect::Dissolve => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -16,4 +33,86 @@ impl SpriteEffectInfo {
             args: [steps as f32, min, max, 0.0],
         }
     }
+
+    // the sprite's image is replaced by drifting smoke, as opaque as the gas is dense
+    pub fn smoke(density: f32) -> Self {
+        Self {
+            effect: SpriteEffect::Smoke,
+            args: [density, 0.0, 0.0, 0.0],
+        }
+    }
+
+    // rows of the sprite sway from side to side by up to amplitude_px
+    pub fn heat_shimmer(amplitude_px: f32, speed: f32) -> Self {
+        Self {
+            effect: SpriteEffect::HeatShimmer,
+            args: [amplitude_px, speed, 0.0, 0.0],
+        }
+    }
+
+    // the sprite lights itself by intensity, in place of the emissive value from the sprite manifest
+    pub fn glow(intensity: f32) -> Self {
+        Self {
+            effect: SpriteEffect::Glow,
+            args: [intensity, 0.0, 0.0, 0.0],
+        }
+    }
+
+    // from 0, where the sprite is whole, to 1, where it's gone entirely
+    pub fn dissolve(progress: f32) -> Self {
+        Self {
+            effect: SpriteEffect::Dissolve,
+            args: [progress, 0.0, 0.0, 0.0],
+        }
+    }
+
+    // blends the args of two infos with the same effect, keeping the effect of self
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let mut args = self.args;
+        for i in 0..4 {
+            args[i] += (other.args[i] - self.args[i]) * t;
+        }
+        Self {
+            effect: self.effect,
+            args,
+        }
+    }
+}
+
+// transparent pixels bordering the sprite are drawn in this colour, whatever its sprite effect
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct OutlineInfo {
+    pub colour: [f32; 3],
+}
+
+impl OutlineInfo {
+    pub fn new(r: f32, g: f32, b: f32) -> Self {
+        Self {
+            colour: [r, g, b],
+        }
+    }
+}
+
+// mixes the lit sprite with a colour, by amount from 0 to 1, on top of its sprite effect
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FlashInfo {
+    pub colour: [f32; 3],
+    pub amount: f32,
+}
+
+impl FlashInfo {
+    pub fn new(r: f32, g: f32, b: f32, amount: f32) -> Self {
+        Self {
+            colour: [r, g, b],
+            amount,
+        }
+    }
+
+    // fades between two flashes, keeping the colour of self
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            colour: self.colour,
+            amount: self.amount + (other.amount - self.amount) * t,
+        }
+    }
 }
@@ -0,0 +1,207 @@
+use entity_store::{EntityStore, insert, remove};
+use append::Append;
+use content::ChangeDesc;
+use turn::{TurnInfo, TurnState, NUM_TURN_STATES};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatusEffectType {
+    Poison,
+    Stun,
+    Slow,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub typ: StatusEffectType,
+    pub remaining: u32,
+    pub strength: i32,
+}
+
+impl StatusEffect {
+    pub fn poison(turns: u32, damage: i32) -> Self {
+        Self {
+            typ: StatusEffectType::Poison,
+            remaining: turns,
+            strength: damage,
+        }
+    }
+    pub fn stun(turns: u32) -> Self {
+        Self {
+            typ: StatusEffectType::Stun,
+            remaining: turns,
+            strength: 0,
+        }
+    }
+    pub fn slow(turns: u32) -> Self {
+        Self {
+            typ: StatusEffectType::Slow,
+            remaining: turns,
+            strength: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusEffectsInfo {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffectsInfo {
+    pub fn new() -> Self {
+        Self {
+            effects: Vec::new(),
+        }
+    }
+
+    pub fn with_effect(mut self, effect: StatusEffect) -> Self {
+        self.add(effect);
+        self
+    }
+
+    pub fn add(&mut self, effect: StatusEffect) {
+        for existing in self.effects.iter_mut() {
+            if existing.typ == effect.typ {
+                // reapplying an effect refreshes it rather than stacking it
+                existing.remaining = existing.remaining.max(effect.remaining);
+                existing.strength = existing.strength.max(effect.strength);
+                return;
+            }
+        }
+        self.effects.push(effect);
+    }
+
+    pub fn has(&self, typ: StatusEffectType) -> bool {
+        self.effects.iter().any(|effect| effect.typ == typ)
+    }
+
+    pub fn poison_damage(&self) -> i32 {
+        self.effects.iter()
+            .filter(|effect| effect.typ == StatusEffectType::Poison)
+            .map(|effect| effect.strength)
+            .sum()
+    }
+
+    pub fn can_act(&self, turn: TurnInfo) -> bool {
+        if self.has(StatusEffectType::Stun) {
+            return false;
+        }
+        if self.has(StatusEffectType::Slow) {
+            // slowed entities only act every other round
+            return (turn.count / NUM_TURN_STATES as u64) % 2 == 0;
+        }
+        true
+    }
+
+    pub fn tick(&self) -> Option<Self> {
+        let effects = self.effects.iter().filter_map(|effect| {
+            if effect.remaining > 1 {
+                Some(StatusEffect {
+                    remaining: effect.remaining - 1,
+                    ..*effect
+                })
+            } else {
+                None
+            }
+        }).collect::<Vec<_>>();
+
+        if effects.is_empty() {
+            None
+        } else {
+            Some(Self { effects })
+        }
+    }
+}
+
+pub fn tick<A: Append<ChangeDesc>>(turn_state: TurnState, entity_store: &EntityStore, changes: &mut A) {
+    for (id, info) in entity_store.status_effects.iter() {
+        // effects wear off at the end of their owner's turn
+        let owns_turn = match turn_state {
+            TurnState::Player => entity_store.player.contains(id),
+            TurnState::Npc => entity_store.npc.contains(id),
+        };
+        if !owns_turn {
+            continue;
+        }
+
+        let damage = info.poison_damage();
+        if damage > 0 {
+            if let Some(health) = entity_store.health.get(id) {
+                changes.append(ChangeDesc::immediate(insert::health(*id, health.reduce(damage))));
+            }
+        }
+
+        if let Some(next) = info.tick() {
+            changes.append(ChangeDesc::immediate(insert::status_effects(*id, next)));
+        } else {
+            changes.append(ChangeDesc::immediate(remove::status_effects(*id)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(count: u64) -> TurnInfo {
+        TurnInfo {
+            state: TurnState::Player,
+            count,
+        }
+    }
+
+    #[test]
+    fn add_refreshes_existing_effect() {
+        let mut info = StatusEffectsInfo::new();
+        info.add(StatusEffect::poison(2, 1));
+        info.add(StatusEffect::poison(5, 3));
+        info.add(StatusEffect::poison(1, 2));
+
+        assert_eq!(info.effects.len(), 1);
+        assert_eq!(info.effects[0].remaining, 5);
+        assert_eq!(info.effects[0].strength, 3);
+        assert_eq!(info.poison_damage(), 3);
+    }
+
+    #[test]
+    fn add_keeps_different_effects() {
+        let info = StatusEffectsInfo::new()
+            .with_effect(StatusEffect::poison(2, 1))
+            .with_effect(StatusEffect::slow(3));
+
+        assert_eq!(info.effects.len(), 2);
+        assert!(info.has(StatusEffectType::Poison));
+        assert!(info.has(StatusEffectType::Slow));
+        assert!(!info.has(StatusEffectType::Stun));
+    }
+
+    #[test]
+    fn tick_counts_down_and_expires() {
+        let info = StatusEffectsInfo::new()
+            .with_effect(StatusEffect::poison(2, 1))
+            .with_effect(StatusEffect::stun(1));
+
+        let info = info.tick().expect("Poison should still be active");
+        assert_eq!(info.effects.len(), 1);
+        assert_eq!(info.effects[0].typ, StatusEffectType::Poison);
+        assert_eq!(info.effects[0].remaining, 1);
+
+        assert!(info.tick().is_none());
+    }
+
+    #[test]
+    fn can_act() {
+        assert!(StatusEffectsInfo::new().can_act(turn(0)));
+
+        let stunned = StatusEffectsInfo::new().with_effect(StatusEffect::stun(1));
+        assert!(!stunned.can_act(turn(0)));
+        assert!(!stunned.can_act(turn(2)));
+
+        // a round is a player turn and an npc turn
+        let slowed = StatusEffectsInfo::new().with_effect(StatusEffect::slow(4));
+        assert!(slowed.can_act(turn(0)));
+        assert!(slowed.can_act(turn(1)));
+        assert!(!slowed.can_act(turn(2)));
+        assert!(!slowed.can_act(turn(3)));
+        assert!(slowed.can_act(turn(4)));
+    }
+}
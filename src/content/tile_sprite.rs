@@ -1,3 +1,4 @@
+enum_from_primitive! {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TileSprite {
     Blank = 0,
@@ -5,6 +6,8 @@ pub enum TileSprite {
     Angler,
     Crab,
     Snail,
+    CrabCorpse,
+    SnailCorpse,
 
     OuterFloor,
     InnerFloor,
@@ -34,7 +37,10 @@ pub enum TileSprite {
 
     Light,
 
+    Smoke,
+
     _Num,
 }
+}
 
 pub const NUM_TILE_SPRITES: usize = TileSprite::_Num as usize;
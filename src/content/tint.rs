@@ -0,0 +1,13 @@
+// light passing through a cell is multiplied by the filter of each tint in the cell
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TintInfo {
+    pub filter: [f32; 3],
+}
+
+impl TintInfo {
+    pub fn new(r: f32, g: f32, b: f32) -> Self {
+        Self {
+            filter: [r, g, b],
+        }
+    }
+}
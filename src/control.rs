@@ -1,7 +1,12 @@
-use direction::CardinalDirection;
+use direction::Direction;
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum Control {
-    Move(CardinalDirection),
+    Move(Direction),
     Wait,
+    Explore,
+    Look,
+    Screenshot,
+    ZoomIn,
+    ZoomOut,
 }
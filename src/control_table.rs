@@ -1,20 +1,20 @@
 use std::collections::HashMap;
 
 use control::Control;
-use input::Bindable;
+use input::{Bindable, Modifiers};
 
 pub struct GameControlTable {
-    controls: HashMap<Bindable, Control>,
+    controls: HashMap<(Bindable, Modifiers), Control>,
 }
 
 impl GameControlTable {
-    pub fn new(controls: HashMap<Bindable, Control>) -> Self {
+    pub fn new(controls: HashMap<(Bindable, Modifiers), Control>) -> Self {
         Self {
             controls,
         }
     }
 
-    pub fn get(&self, input: Bindable) -> Option<Control> {
-        self.controls.get(&input).cloned()
+    pub fn get(&self, input: Bindable, modifiers: Modifiers) -> Option<Control> {
+        self.controls.get(&(input, modifiers)).cloned()
     }
 }
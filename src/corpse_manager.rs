@@ -0,0 +1,35 @@
+use std::collections::VecDeque;
+use entity_store::{EntityId, EntityChange, ComponentValue};
+use entity_id_allocator::{EntityIdAllocator, EntityRef};
+use append::Append;
+
+// the oldest corpses are cleared away beyond this many, so they don't build up over a long game
+const MAX_NUM_CORPSES: usize = 32;
+
+pub struct CorpseManager {
+    corpses: VecDeque<EntityRef>,
+}
+
+impl CorpseManager {
+    pub fn new() -> Self {
+        Self {
+            corpses: VecDeque::new(),
+        }
+    }
+
+    pub fn update(&mut self, change: &EntityChange, allocator: &EntityIdAllocator) {
+        if let &EntityChange::Insert(id, ComponentValue::Corpse) = change {
+            self.corpses.push_back(allocator.entity_ref(id));
+        }
+    }
+
+    pub fn remove_excess<D: Append<EntityId>>(&mut self, to_delete: &mut D, allocator: &EntityIdAllocator) {
+        // corpses deleted some other way no longer count
+        self.corpses.retain(|corpse| allocator.is_current(*corpse));
+        while self.corpses.len() > MAX_NUM_CORPSES {
+            if let Some(corpse) = self.corpses.pop_front() {
+                to_delete.append(corpse.id);
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use cgmath::Vector2;
+use entity_store::{EntityId, EntityStore};
+use spatial_hash::SpatialHashTable;
+use content::{TileSprite, DoorState, StatusEffectType};
+
+fn creature_name(sprite: TileSprite) -> &'static str {
+    match sprite {
+        TileSprite::Angler => "you",
+        TileSprite::Crab => "crab",
+        TileSprite::Snail => "snail",
+        _ => "creature",
+    }
+}
+
+fn describe_creature(id: EntityId, entity_store: &EntityStore) -> String {
+    let name = entity_store.sprite.get(&id).cloned().map_or("creature", creature_name);
+    let mut description = name.to_string();
+
+    if let Some(health) = entity_store.health.get(&id) {
+        description.push_str(&format!(" ({}/{})", health.current.max(0), health.max));
+    }
+
+    if let Some(status_effects) = entity_store.status_effects.get(&id) {
+        for effect in status_effects.effects.iter() {
+            description.push_str(match effect.typ {
+                StatusEffectType::Poison => ", poisoned",
+                StatusEffectType::Stun => ", stunned",
+                StatusEffectType::Slow => ", slowed",
+            });
+        }
+    }
+
+    description
+}
+
+// describes the contents of a cell, leaving out creatures unless the cell is in view
+pub fn describe_cell(coord: Vector2<i32>,
+                     entity_store: &EntityStore,
+                     spatial_hash: &SpatialHashTable,
+                     in_view: bool) -> Option<String> {
+    let sh_cell = if let Some(sh_cell) = spatial_hash.get_signed(coord) {
+        sh_cell
+    } else {
+        return None;
+    };
+
+    let mut parts = Vec::new();
+
+    if in_view {
+        for id in sh_cell.attackable_set.iter() {
+            parts.push(describe_creature(*id, entity_store));
+        }
+    }
+
+    for id in sh_cell.door_set.iter() {
+        if let Some(door) = entity_store.door.get(id) {
+            parts.push(match door.state {
+                DoorState::Open => "open door",
+                DoorState::Closed => "closed door",
+            }.to_string());
+        }
+    }
+
+    if !sh_cell.wall_set.is_empty() {
+        parts.push("wall".to_string());
+    }
+
+    for (id, light) in entity_store.light.iter() {
+        if entity_store.player.contains(id) {
+            continue;
+        }
+        if entity_store.coord.get(id) == Some(&coord) {
+            parts.push(format!("light (colour {:.1}, {:.1}, {:.1})",
+                               light.colour[0], light.colour[1], light.colour[2]));
+        }
+    }
+
+    if parts.is_empty() {
+        parts.push("floor".to_string());
+    }
+
+    Some(parts.join(", "))
+}
@@ -1,8 +1,9 @@
 use std::collections::VecDeque;
 use cgmath::Vector2;
 use static_grid::StaticGrid;
-use direction::CardinalDirections;
+use direction::Direction;
 use spatial_hash::{SpatialHashTable, SpatialHashCell};
+use movement;
 
 struct Cell {
     seq: u64,
@@ -57,23 +58,38 @@ impl DijkstraMap {
                                         spatial_hash: &SpatialHashTable,
                                         coord: Vector2<i32>,
                                         threshold: u32,
+                                        directions: &[Direction],
                                         can_enter: P)
         where P: Fn(&SpatialHashCell) -> bool,
     {
-        let coord = if let Some(coord) = self.grid.convert_signed(coord) {
-            coord
-        } else {
-            return;
-        };
+        self.compute_distance_to_coords(spatial_hash, Some(coord), threshold, directions, can_enter);
+    }
 
+    pub fn compute_distance_to_coords<I, P>(&mut self,
+                                            spatial_hash: &SpatialHashTable,
+                                            coords: I,
+                                            threshold: u32,
+                                            directions: &[Direction],
+                                            can_enter: P)
+        where I: IntoIterator<Item=Vector2<i32>>,
+              P: Fn(&SpatialHashCell) -> bool,
+    {
         self.seq += 1;
 
-        {
-            let cell = self.grid.get_checked_mut(coord);
-            cell.seq = self.seq;
-            cell.value = 0;
+        // every source starts at distance 0
+        for coord in coords {
+            let coord = if let Some(coord) = self.grid.convert_signed(coord) {
+                coord
+            } else {
+                continue;
+            };
+            {
+                let cell = self.grid.get_checked_mut(coord);
+                cell.seq = self.seq;
+                cell.value = 0;
+            }
+            self.coord_queue.push_back(coord);
         }
-        self.coord_queue.push_back(coord);
 
         while let Some(coord) = self.coord_queue.pop_front() {
             let value = self.grid.get_checked(coord).value;
@@ -83,11 +99,15 @@ impl DijkstraMap {
                 break;
             }
 
-            for direction in CardinalDirections {
+            for direction in directions.iter() {
                 let next_signed_coord = signed_coord + direction.vector();
                 if let Some(cell) = self.grid.get_signed_mut(next_signed_coord) {
                     let next_coord = next_signed_coord.cast();
                     if cell.seq != self.seq {
+                        if movement::cuts_corner(spatial_hash, signed_coord, next_signed_coord) {
+                            continue;
+                        }
+
                         let sh_cell = spatial_hash.get(next_coord).expect("Spatial hash of different size to dijkstra map");
 
                         if can_enter(sh_cell) {
@@ -1,12 +1,13 @@
 use std::mem;
-use entity_store::{EntityId, EntityChange, EntityStore, ComponentValue};
+use entity_store::{EntityChange, EntityStore, ComponentValue};
 use content::ActionType;
 use append::Append;
 use turn::{TurnInfo, NUM_TURN_STATES};
 use content::DoorState;
+use entity_id_allocator::{EntityIdAllocator, EntityRef};
 
 struct OpenDoor {
-    id: EntityId,
+    entity: EntityRef,
     close_time: u64,
 }
 
@@ -23,13 +24,13 @@ impl DoorManager {
         }
     }
 
-    pub fn update(&mut self, change: &EntityChange, turn: TurnInfo) {
+    pub fn update(&mut self, change: &EntityChange, allocator: &EntityIdAllocator, turn: TurnInfo) {
         use self::EntityChange::*;
         match change {
             &Insert(id, ComponentValue::Door(door_info)) => {
                 if door_info.state == DoorState::Open {
                     self.open_doors.push(OpenDoor {
-                        id,
+                        entity: allocator.entity_ref(id),
                         close_time: turn.count + NUM_TURN_STATES as u64 + 1,
                     });
                 }
@@ -41,13 +42,18 @@ impl DoorManager {
     pub fn close_doors<A: Append<ActionType>>(&mut self,
                                               actions: &mut A,
                                               entity_store: &EntityStore,
+                                              allocator: &EntityIdAllocator,
                                               turn: TurnInfo)
     {
         for door in self.open_doors.drain(..) {
-            if let Some(door_info) = entity_store.door.get(&door.id) {
+            if !allocator.is_current(door.entity) {
+                // the door was deleted and its id may now belong to something else
+                continue;
+            }
+            if let Some(door_info) = entity_store.door.get(&door.entity.id) {
                 if door_info.state == DoorState::Open {
                     if turn.count >= door.close_time {
-                        actions.append(ActionType::CloseDoor(door.id));
+                        actions.append(ActionType::CloseDoor(door.entity.id));
                     }
                     self.open_doors_swap.push(door);
                 }
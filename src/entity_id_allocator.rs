@@ -1,4 +1,123 @@
 use entity_store::EntityId;
 use id_allocator::IdAllocator;
 
-pub type EntityIdAllocator = IdAllocator<EntityId>;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EntityRef {
+    pub id: EntityId,
+    pub generation: u32,
+}
+
+pub struct EntityIdAllocator {
+    allocator: IdAllocator<EntityId>,
+    generations: Vec<u32>,
+    // false from when an entity is deleted until its id is allocated again
+    live: Vec<bool>,
+}
+
+impl EntityIdAllocator {
+    pub fn new() -> Self {
+        Self {
+            allocator: IdAllocator::new(),
+            generations: Vec::new(),
+            live: Vec::new(),
+        }
+    }
+
+    pub fn allocate(&mut self) -> EntityId {
+        let id = self.allocator.allocate();
+        let index = id as usize;
+        while self.generations.len() <= index {
+            self.generations.push(0);
+            self.live.push(false);
+        }
+        self.live[index] = true;
+        id
+    }
+
+    pub fn peek(&self) -> EntityId {
+        self.allocator.peek()
+    }
+
+    // references to the entity stop matching straight away, but the id isn't reused until it's freed
+    pub fn delete(&mut self, id: EntityId) {
+        let index = id as usize;
+        if let Some(generation) = self.generations.get_mut(index) {
+            *generation += 1;
+        }
+        if let Some(live) = self.live.get_mut(index) {
+            *live = false;
+        }
+    }
+
+    // the id must have been deleted, and nothing may still refer to it without an EntityRef
+    pub fn free(&mut self, id: EntityId) {
+        debug_assert!(!self.is_live(id), "Freeing an entity that hasn't been deleted");
+        self.allocator.free(id);
+    }
+
+    pub fn is_live(&self, id: EntityId) -> bool {
+        self.live.get(id as usize).cloned().unwrap_or(false)
+    }
+
+    pub fn generation(&self, id: EntityId) -> u32 {
+        self.generations.get(id as usize).cloned().unwrap_or(0)
+    }
+
+    pub fn entity_ref(&self, id: EntityId) -> EntityRef {
+        EntityRef {
+            id,
+            generation: self.generation(id),
+        }
+    }
+
+    pub fn is_current(&self, entity_ref: EntityRef) -> bool {
+        self.is_live(entity_ref.id) && self.generation(entity_ref.id) == entity_ref.generation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delete_makes_refs_stale() {
+        let mut allocator = EntityIdAllocator::new();
+        let id = allocator.allocate();
+        let entity = allocator.entity_ref(id);
+        assert!(allocator.is_live(id));
+        assert!(allocator.is_current(entity));
+
+        allocator.delete(id);
+        assert!(!allocator.is_live(id));
+        assert!(!allocator.is_current(entity));
+    }
+
+    #[test]
+    fn ids_are_only_reused_once_freed() {
+        let mut allocator = EntityIdAllocator::new();
+        let id = allocator.allocate();
+        allocator.delete(id);
+        assert!(allocator.allocate() != id);
+
+        allocator.free(id);
+        assert_eq!(allocator.allocate(), id);
+    }
+
+    #[test]
+    fn refs_to_reused_ids_stay_stale() {
+        let mut allocator = EntityIdAllocator::new();
+        let id = allocator.allocate();
+        let old = allocator.entity_ref(id);
+
+        allocator.delete(id);
+        allocator.free(id);
+        let reused = allocator.allocate();
+        assert_eq!(reused, id);
+
+        let new = allocator.entity_ref(reused);
+        assert!(allocator.is_live(reused));
+        assert!(allocator.is_current(new));
+        assert!(!allocator.is_current(old));
+        assert!(old != new);
+    }
+}
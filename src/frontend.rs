@@ -1,9 +1,11 @@
 use std::time::Duration;
 use cgmath::Vector2;
 
-use entity_store::{EntityStore, EntityChange};
+use entity_store::{EntityStore, EntityChange, ComponentValue, ComponentType};
 use spatial_hash::SpatialHashTable;
+use content::AmbientLightInfo;
 use vision::VisionGrid;
+use camera::CameraFocus;
 
 use input::Input;
 
@@ -21,16 +23,62 @@ pub trait OutputWorldState<'a, 'b> {
     fn update(&mut self, change: &EntityChange, entity_store: &EntityStore, spatial_hash: &SpatialHashTable);
     fn set_player_position(&mut self, player_position: Vector2<f32>);
     fn set_frame_info(&mut self, frame_count: u64, total_time: Duration);
+    // ambient light reaches every cell, and sky light only the cells open to the sky
+    fn set_ambient_light(&mut self, ambient: AmbientLightInfo, sky: AmbientLightInfo);
+    fn focus_camera(&mut self, focus: CameraFocus, entity_store: &EntityStore, duration: Duration);
     fn vision_grid(&'b mut self) -> Self::VisionCellGrid;
     fn next_light(&'b mut self) -> Option<(Self::LightCellGrid, &'b mut Self::LightUpdate)>;
 }
 
+// calls f with each cell a change opens to the sky or closes off from it, for frontends that keep
+// track of which cells are outdoors. this runs before the change is committed.
+pub fn sky_change<F>(change: &EntityChange, entity_store: &EntityStore, spatial_hash: &SpatialHashTable, mut f: F)
+    where F: FnMut(Vector2<i32>, bool),
+{
+    // the cell stays open if anything else in it is outdoors
+    let still_open = |coord: Vector2<i32>| {
+        spatial_hash.get_signed(coord).map_or(false, |sh_cell| sh_cell.outdoor_count > 1)
+    };
+
+    use self::EntityChange::*;
+    match change {
+        &Insert(id, ComponentValue::Coord(coord)) => {
+            if entity_store.outdoor.contains(&id) {
+                // an outdoor entity that moves leaves its old cell before opening the new one
+                if let Some(old_coord) = entity_store.coord.get(&id) {
+                    f(*old_coord, still_open(*old_coord));
+                }
+                f(coord, true);
+            }
+        }
+        &Insert(id, ComponentValue::Outdoor) => {
+            if let Some(coord) = entity_store.coord.get(&id) {
+                f(*coord, true);
+            }
+        }
+        &Remove(id, ComponentType::Outdoor) | &Remove(id, ComponentType::Coord) => {
+            if entity_store.outdoor.contains(&id) {
+                if let Some(coord) = entity_store.coord.get(&id) {
+                    f(*coord, still_open(*coord));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
 pub trait FrontendOutput<'a> {
     type WorldState: for<'b> OutputWorldState<'a, 'b>;
     fn with_world_state<F: FnMut(&mut Self::WorldState)>(&'a mut self, f: F);
     fn draw(&mut self, entity_store: &EntityStore);
     fn handle_resize(&mut self, width: u16, height: u16);
     fn update_world_size(&mut self, width: u32, height: u32);
+    fn world_coord(&self, x: i32, y: i32) -> Vector2<i32>;
+    fn set_description(&mut self, description: Option<&str>);
+    fn set_cursor(&mut self, cursor: Option<Vector2<i32>>);
+    fn request_screenshot(&mut self);
+    fn zoom_in(&mut self);
+    fn zoom_out(&mut self);
 }
 
 pub trait FrontendInput {
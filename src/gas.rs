@@ -0,0 +1,131 @@
+use cgmath::Vector2;
+use direction::CardinalDirections;
+use entity_store::{EntityChange, EntityStore, EntityId, insert};
+use entity_id_allocator::EntityIdAllocator;
+use spatial_hash::SpatialHashTable;
+use static_grid::StaticGrid;
+use content::{gas, GasInfo, SpriteEffectInfo};
+use append::Append;
+use prototype;
+
+// the fraction of a cell's gas that drifts into each open neighbour every turn
+const SPREAD_PER_NEIGHBOUR: f32 = 0.15;
+
+// the fraction of gas that remains after each turn
+const RETAIN: f32 = 0.9;
+
+// thinner gas disappears entirely
+const MIN_DENSITY: f32 = 0.05;
+
+fn is_open(spatial_hash: &SpatialHashTable, coord: Vector2<u32>) -> bool {
+    spatial_hash.get(coord).map_or(false, |sh_cell| sh_cell.solid_count == 0)
+}
+
+fn is_open_signed(spatial_hash: &SpatialHashTable, coord: Vector2<i32>) -> bool {
+    spatial_hash.get_signed(coord).map_or(false, |sh_cell| sh_cell.solid_count == 0)
+}
+
+pub struct GasEnv {
+    density: StaticGrid<f32>,
+    next_density: StaticGrid<f32>,
+    entities: StaticGrid<Option<EntityId>>,
+}
+
+impl GasEnv {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            density: StaticGrid::new_copy(width, height, 0.0),
+            next_density: StaticGrid::new_copy(width, height, 0.0),
+            entities: StaticGrid::new_copy(width, height, None),
+        }
+    }
+
+    // spreads gas between open cells and thins it out, then creates, updates and deletes gas entities to match
+    pub fn update<A: Append<EntityChange>>(&mut self,
+                                           changes: &mut A,
+                                           to_delete: &mut Vec<EntityId>,
+                                           entity_store: &EntityStore,
+                                           spatial_hash: &SpatialHashTable,
+                                           allocator: &mut EntityIdAllocator)
+    {
+        for density in self.density.iter_mut() {
+            *density = 0.0;
+        }
+        for density in self.next_density.iter_mut() {
+            *density = 0.0;
+        }
+        for entity in self.entities.iter_mut() {
+            *entity = None;
+        }
+
+        for (id, info) in entity_store.gas.iter() {
+            let coord = if let Some(coord) = entity_store.coord.get(id) {
+                *coord
+            } else {
+                continue;
+            };
+            if let Some(entity) = self.entities.get_signed_mut(coord) {
+                if entity.is_some() {
+                    // a cell only needs one gas entity, so any others are merged into it
+                    to_delete.push(*id);
+                } else {
+                    *entity = Some(*id);
+                }
+            }
+            if !is_open_signed(spatial_hash, coord) {
+                // something solid, like a closing door, has filled the cell, so its gas is deleted below
+                continue;
+            }
+            if let Some(density) = self.density.get_signed_mut(coord) {
+                *density += info.density;
+            }
+        }
+
+        for (id, vent_density) in entity_store.gas_vent.iter() {
+            if let Some(coord) = entity_store.coord.get(id) {
+                if !is_open_signed(spatial_hash, *coord) {
+                    continue;
+                }
+                if let Some(density) = self.density.get_signed_mut(*coord) {
+                    *density = density.max(*vent_density);
+                }
+            }
+        }
+
+        for coord in self.density.coord_iter() {
+            let density = *self.density.get_checked(coord);
+            if density == 0.0 {
+                continue;
+            }
+            let mut remaining = density;
+            for neighbour in spatial_hash.neighbour_coord_iter(coord, CardinalDirections) {
+                if is_open(spatial_hash, neighbour) {
+                    let share = density * SPREAD_PER_NEIGHBOUR;
+                    *self.next_density.get_checked_mut(neighbour) += share;
+                    remaining -= share;
+                }
+            }
+            *self.next_density.get_checked_mut(coord) += remaining;
+        }
+
+        for coord in self.next_density.coord_iter() {
+            let density = (*self.next_density.get_checked(coord) * RETAIN).min(1.0);
+            let entity = *self.entities.get_checked(coord);
+
+            if density < MIN_DENSITY {
+                if let Some(id) = entity {
+                    to_delete.push(id);
+                }
+                continue;
+            }
+
+            if let Some(id) = entity {
+                changes.append(insert::gas(id, GasInfo::new(density)));
+                changes.append(insert::opacity(id, density * gas::MAX_OPACITY));
+                changes.append(insert::sprite_effect(id, SpriteEffectInfo::smoke(density)));
+            } else {
+                prototype::gas(changes, allocator.allocate(), coord.cast(), density);
+            }
+        }
+    }
+}
@@ -1,5 +1,5 @@
-use glutin::{Event, WindowEvent, ModifiersState, ElementState, VirtualKeyCode};
-use input::{Input, Bindable, Unbindable, System};
+use glutin::{self, Event, WindowEvent, ModifiersState, ElementState, VirtualKeyCode};
+use input::{Input, Bindable, Modifiers, Unbindable, System, Mouse, MouseButton};
 
 fn to_char_event(ch: char, keymod: ModifiersState) -> Option<Bindable> {
     if ch.is_alphabetic() {
@@ -27,6 +27,8 @@ fn to_char_event(ch: char, keymod: ModifiersState) -> Option<Bindable> {
             '.' => '>',
             ',' => '<',
             '/' => '?',
+            '=' => '+',
+            '-' => '_',
             _ => return None,
         }
     } else {
@@ -89,17 +91,39 @@ fn convert_key(keycode: VirtualKeyCode, keymod: ModifiersState) -> Option<Input>
         VirtualKeyCode::Period => to_char_event('.', keymod),
         VirtualKeyCode::Comma => to_char_event(',', keymod),
         VirtualKeyCode::Slash => to_char_event('/', keymod),
+        VirtualKeyCode::Equals => to_char_event('=', keymod),
+        VirtualKeyCode::Minus => to_char_event('-', keymod),
+        VirtualKeyCode::Subtract => to_char_event('-', keymod),
         _ => None,
     };
 
     if let Some(event) = maybe_event {
-        return Some(Input::Bindable(event));
+        let shift = match event {
+            Char(_) => false,
+            _ => keymod.shift,
+        };
+        let modifiers = Modifiers {
+            ctrl: keymod.ctrl,
+            alt: keymod.alt,
+            shift,
+        };
+        return Some(Input::Bindable(event, modifiers));
     }
 
     None
 }
 
-pub fn convert_event(event: Event) -> Option<Input> {
+fn convert_mouse_button(button: glutin::MouseButton) -> Option<MouseButton> {
+    match button {
+        glutin::MouseButton::Left => Some(MouseButton::Left),
+        glutin::MouseButton::Right => Some(MouseButton::Right),
+        glutin::MouseButton::Middle => Some(MouseButton::Middle),
+        glutin::MouseButton::Other(_) => None,
+    }
+}
+
+// glutin doesn't report the cursor position with button presses, so it's tracked here
+pub fn convert_event(event: Event, cursor: &mut (i32, i32)) -> Option<Input> {
     let event = if let Event::WindowEvent { event, .. } = event {
         event
     } else {
@@ -117,6 +141,15 @@ pub fn convert_event(event: Event) -> Option<Input> {
                 }
             }
         }
+        WindowEvent::MouseMoved { position: (x, y), .. } => {
+            *cursor = (x as i32, y as i32);
+            return Some(Input::Mouse(Mouse::Move { x: cursor.0, y: cursor.1 }));
+        }
+        WindowEvent::MouseInput { state: ElementState::Pressed, button, .. } => {
+            if let Some(button) = convert_mouse_button(button) {
+                return Some(Input::Mouse(Mouse::Press { x: cursor.0, y: cursor.1, button }));
+            }
+        }
         _ => {}
     }
 
@@ -1,5 +1,7 @@
+use std::fs;
 use gfx;
 use gfx::Device;
+use image::RgbaImage;
 use glutin;
 use glutin::GlContext;
 use gfx_window_glutin;
@@ -9,14 +11,19 @@ use frontend::{FrontendOutput, FrontendInput};
 
 use renderer::{Renderer, ColourFormat, DepthFormat, RendererWorldState};
 
+use cgmath::Vector2;
+
 use input::Input;
 use entity_store::EntityStore;
+use res::paths;
 
 mod input;
 use self::input::convert_event;
 
 type Resources = gfx_device_gl::Resources;
 
+const WINDOW_TITLE: &'static str = "Lighting Experiment";
+
 pub struct GlutinFrontendOutput {
     window: glutin::GlWindow,
     device: gfx_device_gl::Device,
@@ -25,15 +32,29 @@ pub struct GlutinFrontendOutput {
     factory: gfx_device_gl::Factory,
     rtv: gfx::handle::RenderTargetView<Resources, ColourFormat>,
     dsv: gfx::handle::DepthStencilView<Resources, DepthFormat>,
+    screenshot_requested: bool,
+    capture_period: Option<u64>,
+    frame_index: u64,
 }
 
 pub struct GlutinFrontendInput {
     events_loop: glutin::EventsLoop,
+    cursor: (i32, i32),
 }
 
-pub fn create() -> (GlutinFrontendInput, GlutinFrontendOutput) {
+fn save_image(image: &RgbaImage, name: &str) {
+    let dir = paths::screenshot_dir();
+    let path = dir.join(name);
+    if let Err(e) = fs::create_dir_all(&dir).and_then(|_| image.save(&path)) {
+        eprintln!("Failed to save {}: {:?}", path.display(), e);
+    }
+}
+
+// frames are dumped every capture_period frames if it's set
+pub fn create(capture_period: Option<u64>, hot_reload: bool) -> (GlutinFrontendInput, GlutinFrontendOutput) {
     let builder = glutin::WindowBuilder::new()
-        .with_dimensions(960, 720);
+        .with_dimensions(960, 720)
+        .with_title(WINDOW_TITLE);
 
     let events_loop = glutin::EventsLoop::new();
     let context = glutin::ContextBuilder::new()
@@ -44,10 +65,11 @@ pub fn create() -> (GlutinFrontendInput, GlutinFrontendOutput) {
 
     let mut encoder = factory.create_command_buffer().into();
 
-    let renderer = Renderer::new(&rtv, &mut factory, &mut encoder, &mut device);
+    let renderer = Renderer::new(&rtv, hot_reload, &mut factory, &mut encoder, &mut device);
 
     let input = GlutinFrontendInput {
         events_loop,
+        cursor: (0, 0),
     };
 
     let output = GlutinFrontendOutput {
@@ -58,6 +80,9 @@ pub fn create() -> (GlutinFrontendInput, GlutinFrontendOutput) {
         factory,
         rtv,
         dsv,
+        screenshot_requested: false,
+        capture_period,
+        frame_index: 0,
     };
 
     (input, output)
@@ -65,8 +90,9 @@ pub fn create() -> (GlutinFrontendInput, GlutinFrontendOutput) {
 
 impl FrontendInput for GlutinFrontendInput {
     fn with_input<F: FnMut(Input)>(&mut self, mut f: F) {
+        let cursor = &mut self.cursor;
         self.events_loop.poll_events(|event| {
-            if let Some(input_event) = convert_event(event) {
+            if let Some(input_event) = convert_event(event, cursor) {
                 f(input_event);
             }
         });
@@ -81,9 +107,23 @@ impl<'a> FrontendOutput<'a> for GlutinFrontendOutput {
         state.finalise(&mut self.encoder);
     }
     fn draw(&mut self, entity_store: &EntityStore) {
+        self.renderer.poll_hot_reload(&mut self.factory, &mut self.encoder, &mut self.device);
         self.renderer.clear(&mut self.encoder);
         self.renderer.render(entity_store, &mut self.encoder, &mut self.factory);
 
+        let dump_frame = self.capture_period.map_or(false, |period| period > 0 && self.frame_index % period == 0);
+        if self.screenshot_requested || dump_frame {
+            let image = self.renderer.capture(&mut self.encoder, &mut self.factory, &mut self.device);
+            if self.screenshot_requested {
+                save_image(&image, &format!("screenshot_{:06}.png", self.frame_index));
+                self.screenshot_requested = false;
+            }
+            if dump_frame {
+                save_image(&image, &format!("frame_{:06}.png", self.frame_index));
+            }
+        }
+        self.frame_index += 1;
+
         self.encoder.flush(&mut self.device);
         self.window.swap_buffers().expect("Failed to swap buffers");
         self.device.cleanup();
@@ -97,4 +137,22 @@ impl<'a> FrontendOutput<'a> for GlutinFrontendOutput {
     fn update_world_size(&mut self, width: u32, height: u32) {
         self.renderer.update_world_size(width, height, &mut self.encoder);
     }
+    fn world_coord(&self, x: i32, y: i32) -> Vector2<i32> {
+        self.renderer.screen_to_world(Vector2::new(x, y))
+    }
+    fn set_cursor(&mut self, cursor: Option<Vector2<i32>>) {
+        self.renderer.set_cursor(cursor);
+    }
+    fn request_screenshot(&mut self) {
+        self.screenshot_requested = true;
+    }
+    fn zoom_in(&mut self) {
+        self.renderer.zoom_in(&self.rtv, &mut self.encoder, &mut self.factory);
+    }
+    fn zoom_out(&mut self) {
+        self.renderer.zoom_out(&self.rtv, &mut self.encoder, &mut self.factory);
+    }
+    fn set_description(&mut self, description: Option<&str>) {
+        self.renderer.set_description(description);
+    }
 }
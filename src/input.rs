@@ -9,6 +9,20 @@ pub enum Bindable {
     Space,
 }
 
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    // shift is already applied to characters, so this is only set for other keys
+    pub shift: bool,
+}
+
+impl Modifiers {
+    pub fn none() -> Self {
+        Default::default()
+    }
+}
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum Unbindable {
     Escape,
@@ -20,9 +34,24 @@ pub enum System {
     Resize(u16, u16),
 }
 
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+// positions are in window pixels
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Mouse {
+    Move { x: i32, y: i32 },
+    Press { x: i32, y: i32, button: MouseButton },
+}
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub enum Input {
-    Bindable(Bindable),
+    Bindable(Bindable, Modifiers),
     Unbindable(Unbindable),
     System(System),
+    Mouse(Mouse),
 }
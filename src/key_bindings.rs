@@ -0,0 +1,288 @@
+use std::collections::{BTreeMap, HashMap};
+use direction::Direction;
+use control::Control;
+use control_table::GameControlTable;
+use input::{Bindable, Modifiers};
+use movement::MovementMode;
+use simple_file;
+use res::{paths, files};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyBindings {
+    pub bindings: BTreeMap<String, String>,
+}
+
+const DIRECTION_NAMES: [(Direction, &'static str); 8] = [
+    (Direction::North, "north"),
+    (Direction::East, "east"),
+    (Direction::South, "south"),
+    (Direction::West, "west"),
+    (Direction::NorthEast, "north-east"),
+    (Direction::SouthEast, "south-east"),
+    (Direction::SouthWest, "south-west"),
+    (Direction::NorthWest, "north-west"),
+];
+
+const KEY_NAMES: [(Bindable, &'static str); 6] = [
+    (Bindable::Up, "up"),
+    (Bindable::Down, "down"),
+    (Bindable::Left, "left"),
+    (Bindable::Right, "right"),
+    (Bindable::Return, "return"),
+    (Bindable::Space, "space"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyError {
+    UnknownModifier,
+    UnknownKey,
+    // shift is already applied to characters, so bind the shifted character instead
+    ShiftedChar,
+}
+
+fn parse_key(string: &str) -> Result<(Bindable, Modifiers), KeyError> {
+    let mut modifiers = Modifiers::none();
+    let mut parts = string.split('+').collect::<Vec<_>>();
+    let key = if let Some(key) = parts.pop() {
+        key
+    } else {
+        return Err(KeyError::UnknownKey);
+    };
+
+    for part in parts {
+        match part.to_lowercase().as_ref() {
+            "ctrl" => modifiers.ctrl = true,
+            "alt" => modifiers.alt = true,
+            "shift" => modifiers.shift = true,
+            _ => return Err(KeyError::UnknownModifier),
+        }
+    }
+
+    for &(bindable, name) in KEY_NAMES.iter() {
+        if key.to_lowercase() == name {
+            return Ok((bindable, modifiers));
+        }
+    }
+
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(_), None) if modifiers.shift => Err(KeyError::ShiftedChar),
+        (Some(ch), None) => Ok((Bindable::Char(ch), modifiers)),
+        _ => Err(KeyError::UnknownKey),
+    }
+}
+
+fn format_key(bindable: Bindable, modifiers: Modifiers) -> String {
+    let mut string = String::new();
+    if modifiers.ctrl {
+        string.push_str("ctrl+");
+    }
+    if modifiers.alt {
+        string.push_str("alt+");
+    }
+    if modifiers.shift {
+        string.push_str("shift+");
+    }
+    match bindable {
+        Bindable::Char(ch) => string.push(ch),
+        _ => {
+            for &(other, name) in KEY_NAMES.iter() {
+                if other == bindable {
+                    string.push_str(name);
+                }
+            }
+        }
+    }
+    string
+}
+
+fn parse_control(string: &str) -> Option<Control> {
+    let string = string.to_lowercase();
+    if string == "wait" {
+        return Some(Control::Wait);
+    }
+    if string == "explore" {
+        return Some(Control::Explore);
+    }
+    if string == "look" {
+        return Some(Control::Look);
+    }
+    if string == "screenshot" {
+        return Some(Control::Screenshot);
+    }
+    if string == "zoom-in" {
+        return Some(Control::ZoomIn);
+    }
+    if string == "zoom-out" {
+        return Some(Control::ZoomOut);
+    }
+    for &(direction, name) in DIRECTION_NAMES.iter() {
+        if string == name {
+            return Some(Control::Move(direction));
+        }
+    }
+    None
+}
+
+fn format_control(control: Control) -> String {
+    match control {
+        Control::Wait => "wait".to_string(),
+        Control::Explore => "explore".to_string(),
+        Control::Look => "look".to_string(),
+        Control::Screenshot => "screenshot".to_string(),
+        Control::ZoomIn => "zoom-in".to_string(),
+        Control::ZoomOut => "zoom-out".to_string(),
+        Control::Move(direction) => {
+            DIRECTION_NAMES.iter().find(|&&(other, _)| other == direction)
+                .map(|&(_, name)| name.to_string())
+                .expect("Missing direction name")
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn defaults(movement_mode: MovementMode) -> Self {
+        use self::Bindable::*;
+        use self::Direction::*;
+
+        let mut controls = vec![
+            (Up, Control::Move(North)),
+            (Right, Control::Move(East)),
+            (Down, Control::Move(South)),
+            (Left, Control::Move(West)),
+            (Space, Control::Wait),
+            (Char('.'), Control::Wait),
+            (Char('x'), Control::Explore),
+            (Char('v'), Control::Look),
+            (Char('p'), Control::Screenshot),
+            (Char('='), Control::ZoomIn),
+            (Char('-'), Control::ZoomOut),
+            // vi-keys
+            (Char('k'), Control::Move(North)),
+            (Char('l'), Control::Move(East)),
+            (Char('j'), Control::Move(South)),
+            (Char('h'), Control::Move(West)),
+        ];
+
+        if movement_mode == MovementMode::EightWay {
+            controls.extend(vec![
+                (Char('u'), Control::Move(NorthEast)),
+                (Char('n'), Control::Move(SouthEast)),
+                (Char('b'), Control::Move(SouthWest)),
+                (Char('y'), Control::Move(NorthWest)),
+                // numpad layout
+                (Char('8'), Control::Move(North)),
+                (Char('6'), Control::Move(East)),
+                (Char('2'), Control::Move(South)),
+                (Char('4'), Control::Move(West)),
+                (Char('9'), Control::Move(NorthEast)),
+                (Char('3'), Control::Move(SouthEast)),
+                (Char('1'), Control::Move(SouthWest)),
+                (Char('7'), Control::Move(NorthWest)),
+                (Char('5'), Control::Wait),
+            ]);
+        }
+
+        let bindings = controls.into_iter().map(|(bindable, control)| {
+            (format_key(bindable, Modifiers::none()), format_control(control))
+        }).collect();
+
+        Self {
+            bindings,
+        }
+    }
+
+    // reads the bindings file, writing out the defaults if there isn't one yet
+    pub fn load(movement_mode: MovementMode) -> Self {
+        let path = paths::res_path(files::KEY_BINDINGS);
+        match simple_file::read_toml(&path) {
+            Ok(bindings) => bindings,
+            Err(simple_file::FileError::MissingFile) => {
+                let bindings = Self::defaults(movement_mode);
+                if let Err(e) = simple_file::write_toml(&path, &bindings) {
+                    eprintln!("Failed to write default key bindings: {:?}", e);
+                }
+                bindings
+            }
+            Err(e) => {
+                eprintln!("Failed to read key bindings ({:?}), using defaults", e);
+                Self::defaults(movement_mode)
+            }
+        }
+    }
+
+    pub fn control_table(&self) -> GameControlTable {
+        let mut controls = HashMap::new();
+        for (key, control) in self.bindings.iter() {
+            match (parse_key(key), parse_control(control)) {
+                (Ok(input), Some(control)) => {
+                    controls.insert(input, control);
+                }
+                (Err(e), _) => eprintln!("Ignoring invalid key binding ({:?}): {} = {}", e, key, control),
+                (Ok(_), None) => eprintln!("Ignoring invalid key binding: {} = {}", key, control),
+            }
+        }
+        GameControlTable::new(controls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_modifiers() -> Vec<Modifiers> {
+        let mut all = Vec::new();
+        for &ctrl in [false, true].iter() {
+            for &alt in [false, true].iter() {
+                for &shift in [false, true].iter() {
+                    all.push(Modifiers { ctrl, alt, shift });
+                }
+            }
+        }
+        all
+    }
+
+    #[test]
+    fn named_keys_round_trip() {
+        for &(bindable, _) in KEY_NAMES.iter() {
+            for modifiers in all_modifiers() {
+                let string = format_key(bindable, modifiers);
+                assert_eq!(parse_key(&string), Ok((bindable, modifiers)), "{}", string);
+            }
+        }
+    }
+
+    #[test]
+    fn chars_round_trip() {
+        for ch in "aZ.=-5".chars() {
+            for modifiers in all_modifiers().into_iter().filter(|m| !m.shift) {
+                let string = format_key(Bindable::Char(ch), modifiers);
+                assert_eq!(parse_key(&string), Ok((Bindable::Char(ch), modifiers)), "{}", string);
+            }
+        }
+    }
+
+    #[test]
+    fn shifted_chars_are_rejected() {
+        assert_eq!(parse_key("shift+a"), Err(KeyError::ShiftedChar));
+        assert_eq!(parse_key("ctrl+shift+a"), Err(KeyError::ShiftedChar));
+        assert_eq!(parse_key("shift+up").map(|(_, modifiers)| modifiers.shift), Ok(true));
+    }
+
+    #[test]
+    fn invalid_keys_are_rejected() {
+        assert_eq!(parse_key("meta+a"), Err(KeyError::UnknownModifier));
+        assert_eq!(parse_key("ab"), Err(KeyError::UnknownKey));
+        assert_eq!(parse_key(""), Err(KeyError::UnknownKey));
+    }
+
+    #[test]
+    fn default_bindings_are_valid() {
+        for &movement_mode in [MovementMode::FourWay, MovementMode::EightWay].iter() {
+            for (key, control) in KeyBindings::defaults(movement_mode).bindings.iter() {
+                assert!(parse_key(key).is_ok(), "{}", key);
+                assert!(parse_control(control).is_some(), "{}", control);
+            }
+        }
+    }
+}
@@ -1,40 +1,92 @@
 use std::collections::VecDeque;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::mem;
 use cgmath::Vector2;
+use rand::{SeedableRng, XorShiftRng};
 use frontend::{FrontendOutput, FrontendInput, OutputWorldState, LightUpdate};
 use terrain;
-use entity_store::{EntityStore, ComponentValue, EntityChange, EntityId};
+use entity_store::{EntityStore, ComponentValue, EntityChange, EntityId, insert, remove};
 use spatial_hash::SpatialHashTable;
-use entity_id_allocator::EntityIdAllocator;
+use entity_id_allocator::{EntityIdAllocator, EntityRef};
 use content::ActionType;
-use control_table::GameControlTable;
+use key_bindings::KeyBindings;
 use control::Control;
-use input::{Input, Bindable, Unbindable, System};
-use direction::CardinalDirection;
+use input::{Input, Unbindable, System, Mouse, MouseButton};
+use settings::Settings;
 use content::{ChangeDesc, Animation, AnimationStatus, AnimatedChange};
-use vision::shadowcast;
+use content::status_effect;
+use vision::{shadowcast, DiscoveryGrid};
 use ai_info::GlobalAiInfo;
 use turn::{TurnInfo, TurnState};
 use ai::AiEnv;
 use door_manager::DoorManager;
+use corpse_manager::CorpseManager;
+use spawner::SpawnerEnv;
+use gas::GasEnv;
+use travel::TravelEnv;
+use describe;
+use static_grid::StaticGrid;
+use simple_file;
+use res::{paths, files};
 use entity_store::EntityComponentTable;
 use policy;
+use camera::CameraFocus;
+use content::DoorState;
+use content::OutlineInfo;
+use content::ambient_light;
+
+const DOOR_FOCUS_DURATION_MS: u64 = 600;
+const HIGHLIGHT_COLOUR: [f32; 3] = [1.0, 0.9, 0.4];
+
+// the same seed plays out the same game
+const RNG_SEED: [u32; 4] = [0x193a6754, 0xa8a7d469, 0x97830e05, 0x113ba7bb];
+
+fn npc_in_view(entity_store: &EntityStore, player_vision: &StaticGrid<u64>, vision_time: u64) -> bool {
+    entity_store.npc.iter().any(|id| {
+        entity_store.coord.get(id).and_then(|coord| player_vision.get_signed(*coord))
+            .map_or(false, |&time| time != 0 && time == vision_time)
+    })
+}
+
+// the visible creature in a cell, for highlighting
+fn creature_at(coord: Vector2<i32>, entity_store: &EntityStore, spatial_hash: &SpatialHashTable,
+               player_vision: &StaticGrid<u64>, vision_time: u64) -> Option<EntityId> {
+    // cells that have never been seen have time 0, which is also the vision time on the first frame
+    if player_vision.get_signed(coord).map_or(true, |&time| time == 0 || time != vision_time) {
+        return None;
+    }
+    spatial_hash.get_signed(coord).and_then(|sh_cell| {
+        sh_cell.attackable_set.iter().find(|id| entity_store.npc.contains(id)).cloned()
+    })
+}
 
 fn commit<'a, 'b, S: OutputWorldState<'a, 'b>>(change: EntityChange,
                                                state: &mut S,
                                                entity_store: &mut EntityStore,
                                                spatial_hash: &mut SpatialHashTable,
                                                door_manager: &mut DoorManager,
+                                               corpse_manager: &mut CorpseManager,
+                                               travel_env: &mut TravelEnv,
+                                               allocator: &EntityIdAllocator,
                                                entity_component_table: &mut EntityComponentTable,
                                                time: u64,
                                                turn: TurnInfo,
-                                               player_id: EntityId)
+                                               player_id: EntityId,
+                                               player_vision: &StaticGrid<u64>)
 {
+    if let EntityChange::Insert(id, _) = change {
+        // changes can't bring back a deleted entity, even before its id is recycled
+        if !allocator.is_live(id) {
+            return;
+        }
+    }
+
     state.update(&change, entity_store, spatial_hash);
 
     spatial_hash.update(entity_store, &change, time);
-    door_manager.update(&change, turn);
+    door_manager.update(&change, allocator, turn);
+    corpse_manager.update(&change, allocator);
+    travel_env.update(&change, entity_store);
     entity_component_table.update(&change);
 
     if let EntityChange::Insert(id, ComponentValue::Position(new_position)) = change {
@@ -43,23 +95,26 @@ fn commit<'a, 'b, S: OutputWorldState<'a, 'b>>(change: EntityChange,
         }
     }
 
+    if let EntityChange::Insert(id, ComponentValue::Door(ref door_info)) = change {
+        // briefly show doors that something else opens within the player's view
+        let was_closed = entity_store.door.get(&id).map_or(false, |door| door.state == DoorState::Closed);
+        let in_view = entity_store.coord.get(&id).and_then(|coord| player_vision.get_signed(*coord))
+            .map_or(false, |&vision_time| vision_time != 0 && vision_time + 1 == time);
+        if door_info.state == DoorState::Open && was_closed && in_view && turn.state == TurnState::Npc {
+            state.focus_camera(CameraFocus::Entity(id), entity_store, Duration::from_millis(DOOR_FOCUS_DURATION_MS));
+        }
+    }
+
     entity_store.commit(change);
 }
 
 pub fn launch<I: FrontendInput, O: for<'a> FrontendOutput<'a>>(mut frontend_input: I, mut frontend_output: O) {
-    let control_table = {
-        use self::Bindable::*;
-        use self::Control::*;
-        use self::CardinalDirection::*;
-        GameControlTable::new(hashmap!{
-            Up => Move(North),
-            Right => Move(East),
-            Down => Move(South),
-            Left => Move(West),
-            Space => Wait,
-        })
-    };
+    let settings = Settings::load();
+    let movement_mode = settings.movement_mode;
+    let ambient = settings.ambient_light;
+    let control_table = KeyBindings::load(movement_mode).control_table();
 
+    let mut rng = XorShiftRng::from_seed(RNG_SEED);
     let mut allocator = EntityIdAllocator::new();
     let mut changes = Vec::new();
     let mut entity_store = EntityStore::new();
@@ -69,9 +124,20 @@ pub fn launch<I: FrontendInput, O: for<'a> FrontendOutput<'a>>(mut frontend_inpu
 
     let mut spatial_hash = SpatialHashTable::new(metadata.width, metadata.height);
     let mut shadowcast_env = shadowcast::ShadowcastEnv::new();
-    let mut ai_info = GlobalAiInfo::new(metadata.width, metadata.height);
+    let mut ai_info = GlobalAiInfo::new(metadata.width, metadata.height, movement_mode);
     let mut ai_env = AiEnv::new(metadata.width, metadata.height);
     let mut door_manager = DoorManager::new();
+    let mut corpse_manager = CorpseManager::new();
+    let encounter_tables = simple_file::read_toml(paths::res_path(files::ENCOUNTER_TABLES))
+        .expect("Failed to load encounter tables");
+    let mut spawner_env = SpawnerEnv::new(encounter_tables, metadata.width, metadata.height);
+    let mut gas_env = GasEnv::new(metadata.width, metadata.height);
+    let mut player_vision = StaticGrid::new_copy(metadata.width, metadata.height, 0);
+    let mut travel_env = TravelEnv::new(metadata.width, metadata.height, movement_mode);
+    let mut hover_coord = None;
+    let mut look_cursor: Option<Vector2<i32>> = None;
+    let mut highlight: Option<EntityRef> = None;
+    let mut description = None;
     let mut entity_component_table = EntityComponentTable::new();
 
     frontend_output.update_world_size(metadata.width, metadata.height);
@@ -107,6 +173,7 @@ pub fn launch<I: FrontendInput, O: for<'a> FrontendOutput<'a>>(mut frontend_inpu
     let mut animations_swap = VecDeque::new();
     let mut animated_changes = VecDeque::new();
     let mut to_delete = Vec::new();
+    let mut to_free = Vec::new();
 
     let mut running = true;
     let mut count = 1;
@@ -122,29 +189,104 @@ pub fn launch<I: FrontendInput, O: for<'a> FrontendOutput<'a>>(mut frontend_inpu
         let total_duration = now - start_instant;
         frame_instant = now;
 
+        let player_can_act = entity_store.status_effects.get(&player_id)
+            .map_or(true, |status_effects| status_effects.can_act(turn));
+
+        // the player's vision was last updated on the previous frame
+        let vision_time = count - 1;
+        let mut click_coord = None;
+        let mut inspect_changed = false;
+
         frontend_input.with_input(|input| {
             use self::Input::*;
             match input {
-                Bindable(b) => {
-                    if turn.state != TurnState::Player || !animations.is_empty() {
-                        return;
+                Bindable(b, modifiers) => {
+                    let control = control_table.get(b, modifiers);
+                    use self::Control::*;
+
+                    // these don't affect the game, so they leave travel alone
+                    match control {
+                        Some(Screenshot) => {
+                            frontend_output.request_screenshot();
+                            return;
+                        }
+                        Some(ZoomIn) => {
+                            frontend_output.zoom_in();
+                            return;
+                        }
+                        Some(ZoomOut) => {
+                            frontend_output.zoom_out();
+                            return;
+                        }
+                        _ => {}
                     }
-                    if let Some(control) = control_table.get(b) {
-                        use self::Control::*;
+
+                    travel_env.cancel();
+                    let control = if let Some(control) = control {
+                        control
+                    } else {
+                        return;
+                    };
+
+                    if let Some(cursor) = look_cursor {
+                        // in look mode, movement controls move the cursor instead of the player
                         match control {
-                            Move(direction) => {
-                                proposed_actions.push_back(ActionType::Walk(player_id, direction));
-                            }
-                            Wait => {}
+                            Move(direction) => look_cursor = Some(cursor + direction.vector()),
+                            Look | Wait => look_cursor = None,
+                            Explore | Screenshot | ZoomIn | ZoomOut => {}
                         }
+                        inspect_changed = true;
+                        return;
+                    }
 
-                        next_turn = turn.next();
+                    if control == Look {
+                        look_cursor = entity_store.coord.get(&player_id).cloned();
+                        inspect_changed = true;
+                        return;
+                    }
+
+                    if turn.state != TurnState::Player || !animations.is_empty() || !player_can_act {
+                        return;
+                    }
+                    match control {
+                        Move(direction) => {
+                            // the bindings file may still have diagonals from another movement mode
+                            if !movement_mode.allows(direction) {
+                                return;
+                            }
+                            proposed_actions.push_back(ActionType::Walk(player_id, direction));
+                            next_turn = turn.next();
+                        }
+                        Wait => {
+                            next_turn = turn.next();
+                        }
+                        Explore => {
+                            travel_env.start_explore();
+                        }
+                        Look | Screenshot | ZoomIn | ZoomOut => {}
                     }
                 }
                 Unbindable(u) => {
                     use self::Unbindable::*;
                     match u {
-                        Escape => {}
+                        Escape => {
+                            if look_cursor.is_some() {
+                                look_cursor = None;
+                                inspect_changed = true;
+                            }
+                        }
+                    }
+                }
+                Mouse(m) => {
+                    match m {
+                        Mouse::Move { x, y } => {
+                            hover_coord = Some(frontend_output.world_coord(x, y));
+                            inspect_changed = true;
+                        }
+                        Mouse::Press { x, y, button: MouseButton::Left } => {
+                            click_coord = Some(frontend_output.world_coord(x, y));
+                        }
+                        Mouse::Press { .. } => {}
                     }
                 }
                 System(s) => {
@@ -163,13 +305,70 @@ pub fn launch<I: FrontendInput, O: for<'a> FrontendOutput<'a>>(mut frontend_inpu
             break;
         }
 
+        if inspect_changed {
+            // outline the creature under the look cursor or the mouse
+            let next_highlight = look_cursor.or(hover_coord).and_then(|coord| {
+                creature_at(coord, &entity_store, &spatial_hash, &player_vision, vision_time)
+            });
+            if next_highlight != highlight.map(|entity| entity.id) {
+                if let Some(entity) = highlight.take() {
+                    // the creature may have died since, and its id been reused
+                    if allocator.is_current(entity) {
+                        changes.push(remove::outline(entity.id));
+                    }
+                }
+                if let Some(id) = next_highlight {
+                    let outline = OutlineInfo::new(HIGHLIGHT_COLOUR[0], HIGHLIGHT_COLOUR[1], HIGHLIGHT_COLOUR[2]);
+                    changes.push(insert::outline(id, outline));
+                    highlight = Some(allocator.entity_ref(id));
+                }
+            }
+        }
+
+        if let Some(coord) = click_coord {
+            let seen = player_vision.get_signed(coord).map_or(false, |&time| time != 0);
+            if turn.state == TurnState::Player && seen {
+                if let Some(player_coord) = entity_store.coord.get(&player_id) {
+                    travel_env.start(&spatial_hash, *player_coord, coord);
+                }
+            }
+        }
+
+        if turn.state == TurnState::Player && animations.is_empty() && player_can_act &&
+            next_turn == turn && travel_env.is_travelling()
+        {
+            if npc_in_view(&entity_store, &player_vision, vision_time) {
+                travel_env.cancel();
+            } else if let Some(player_coord) = entity_store.coord.get(&player_id).cloned() {
+                if let Some(direction) = travel_env.next_direction(player_coord, &entity_store, &spatial_hash, &player_vision) {
+                    proposed_actions.push_back(ActionType::Walk(player_id, direction));
+                    next_turn = turn.next();
+                }
+            }
+        }
+
+        if turn.state == TurnState::Player && animations.is_empty() && !player_can_act {
+            // the player loses their turn
+            next_turn = turn.next();
+        }
+
         if turn.state == TurnState::Npc && animations.is_empty() {
             ai_info.compute_distances(&spatial_hash);
-            ai_env.append_actions(&mut proposed_actions, &entity_store, &spatial_hash, &mut ai_info);
+            ai_env.append_actions(&mut proposed_actions, &entity_store, &spatial_hash, &mut ai_info, &allocator, turn);
             next_turn = turn.next();
         }
 
-        door_manager.close_doors(&mut proposed_actions, &entity_store, turn);
+        if next_turn != turn {
+            status_effect::tick(turn.state, &entity_store, &mut change_descs);
+            if turn.state == TurnState::Npc {
+                // the player's vision was last updated on the previous frame
+                spawner_env.spawn(&mut changes, &entity_store, &spatial_hash, &mut allocator,
+                                  &player_vision, vision_time, next_turn, &mut rng);
+                gas_env.update(&mut changes, &mut to_delete, &entity_store, &spatial_hash, &mut allocator);
+            }
+        }
+
+        door_manager.close_doors(&mut proposed_actions, &entity_store, &allocator, turn);
 
 
         for a in proposed_actions.drain(..) {
@@ -193,11 +392,11 @@ pub fn launch<I: FrontendInput, O: for<'a> FrontendOutput<'a>>(mut frontend_inpu
                 match animated_change {
                     AnimatedChange::Checked(change) => {
                         if policy::check(&change, &entity_store, &spatial_hash, &mut change_descs, &mut to_delete) {
-                            commit(change, state, &mut entity_store, &mut spatial_hash, &mut door_manager, &mut entity_component_table, count, turn, player_id);
+                            commit(change, state, &mut entity_store, &mut spatial_hash, &mut door_manager, &mut corpse_manager, &mut travel_env, &allocator, &mut entity_component_table, count, turn, player_id, &player_vision);
                         }
                     }
                     AnimatedChange::Unchecked(change) => {
-                        commit(change, state, &mut entity_store, &mut spatial_hash, &mut door_manager, &mut entity_component_table, count, turn, player_id);
+                        commit(change, state, &mut entity_store, &mut spatial_hash, &mut door_manager, &mut corpse_manager, &mut travel_env, &allocator, &mut entity_component_table, count, turn, player_id, &player_vision);
                     }
                 }
             }
@@ -210,7 +409,7 @@ pub fn launch<I: FrontendInput, O: for<'a> FrontendOutput<'a>>(mut frontend_inpu
                             if policy::check(&change, &entity_store, &spatial_hash, &mut change_descs_swap, &mut to_delete) {
                                 ai_info.update(&change, &entity_store);
                                 ai_env.update(&change, &entity_store);
-                                commit(change, state, &mut entity_store, &mut spatial_hash, &mut door_manager, &mut entity_component_table, count, turn, player_id);
+                                commit(change, state, &mut entity_store, &mut spatial_hash, &mut door_manager, &mut corpse_manager, &mut travel_env, &allocator, &mut entity_component_table, count, turn, player_id, &player_vision);
                             }
                         }
                         Animation(animation) => {
@@ -225,23 +424,36 @@ pub fn launch<I: FrontendInput, O: for<'a> FrontendOutput<'a>>(mut frontend_inpu
                 }
             }
 
+            corpse_manager.remove_excess(&mut to_delete, &allocator);
+
             for id in to_delete.drain(..) {
+                if !allocator.is_live(id) {
+                    // already deleted
+                    continue;
+                }
+                let entity = allocator.entity_ref(id);
+                ai_env.remove_entity(entity);
+                spawner_env.remove_entity(entity);
+                allocator.delete(id);
                 for change in entity_component_table.remove_entity(id) {
                     changes.push(change);
                 }
+                to_free.push(id);
             }
 
             for change in changes.drain(..) {
-                commit(change, state, &mut entity_store, &mut spatial_hash, &mut door_manager, &mut entity_component_table, count, turn, player_id);
+                commit(change, state, &mut entity_store, &mut spatial_hash, &mut door_manager, &mut corpse_manager, &mut travel_env, &allocator, &mut entity_component_table, count, turn, player_id, &player_vision);
             }
 
             state.set_frame_info(count, total_duration);
+            state.set_ambient_light(ambient,
+                                    ambient_light::sky_light(ambient_light::time_of_day(turn.count)));
 
             for (id, light_info) in entity_store.light.iter() {
                 if let Some(position) = entity_store.position.get(id) {
                     if let Some((mut light_grid, light_update)) = state.next_light() {
                         shadowcast::observe(&mut light_grid, &mut shadowcast_env, *position, &spatial_hash,
-                                            light_info.range, count);
+                                            &entity_store, light_info.range, count);
                         light_update.set_position(*position + Vector2::new(0.5, 0.5));
                         light_update.set_height(light_info.height);
                         light_update.set_intensity(light_info.intensity);
@@ -251,11 +463,44 @@ pub fn launch<I: FrontendInput, O: for<'a> FrontendOutput<'a>>(mut frontend_inpu
             }
 
             if let Some(player_position) = entity_store.position.get(&player_id) {
-                shadowcast::observe(&mut state.vision_grid(), &mut shadowcast_env, *player_position, &spatial_hash,
-                                    8, count);
+                let mut discovery = DiscoveryGrid::new(&mut player_vision);
+                shadowcast::observe(&mut (state.vision_grid(), &mut discovery), &mut shadowcast_env, *player_position, &spatial_hash,
+                                    &entity_store, 8, count);
+                if discovery.discovered() {
+                    travel_env.handle_discovery();
+                }
             }
         });
 
+        if animations.is_empty() {
+            // only recycle ids once no animation can refer to them
+            for id in to_free.drain(..) {
+                allocator.free(id);
+            }
+        }
+
+        if inspect_changed {
+            frontend_output.set_cursor(look_cursor);
+        }
+
+        if inspect_changed || next_turn != turn {
+            // the look cursor takes priority over the mouse
+            let next_description = look_cursor.or(hover_coord).and_then(|coord| {
+                player_vision.get_signed(coord).and_then(|&time| {
+                    if time == 0 {
+                        // never seen
+                        None
+                    } else {
+                        describe::describe_cell(coord, &entity_store, &spatial_hash, time == count)
+                    }
+                })
+            });
+            if next_description != description {
+                frontend_output.set_description(next_description.as_ref().map(|d| d.as_str()));
+                description = next_description;
+            }
+        }
+
         frontend_output.draw(&entity_store);
 
         count += 1;
@@ -2,6 +2,7 @@
 #![allow(unused_macros)]
 
 extern crate cgmath;
+extern crate serde;
 #[macro_use] extern crate serde_derive;
 #[macro_use] extern crate enum_primitive;
 extern crate fnv;
@@ -10,22 +11,27 @@ extern crate num;
 #[macro_use] extern crate maplit;
 extern crate handlebars;
 extern crate toml;
+extern crate rand;
 
 #[macro_use] extern crate gfx;
 extern crate glutin;
 extern crate gfx_window_glutin;
 extern crate gfx_device_gl;
 extern crate image;
+extern crate termion;
 
 extern crate direction;
 #[macro_use] extern crate entity_store_helper;
 
+use std::env;
+
 mod static_grid;
 mod limits;
 mod neighbour_count;
 mod append;
 mod vector_index;
 mod util;
+mod simple_file;
 
 mod entity_store { include_entity_store!("entity_store.rs"); }
 
@@ -43,20 +49,55 @@ mod launch;
 mod policy;
 mod frontend;
 mod glutin_frontend;
+mod ansi_frontend;
+mod software_frontend;
 mod renderer;
 mod input;
 mod control;
 mod control_table;
+mod key_bindings;
+mod settings;
 mod vision;
 mod dijkstra_map;
 mod search;
 mod ai_info;
 mod ai;
 mod door_manager;
+mod corpse_manager;
+mod spawner;
+mod gas;
 mod turn;
+mod movement;
+mod camera;
+mod travel;
+mod describe;
 mod vec_pool;
 
+const DEFAULT_NUM_PNG_FRAMES: u64 = 60;
+
+fn arg_value(args: &[String], name: &str) -> Option<String> {
+    args.iter().position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .cloned()
+}
+
 fn main() {
-    let (input, output) = glutin_frontend::create();
-    launch::launch(input, output);
+    let args = env::args().collect::<Vec<_>>();
+
+    if let Some(output_dir) = arg_value(&args, "--png") {
+        let num_frames = arg_value(&args, "--frames")
+            .map(|frames| frames.parse().expect("Invalid frame count"))
+            .unwrap_or(DEFAULT_NUM_PNG_FRAMES);
+        let (input, output) = software_frontend::create(output_dir, num_frames);
+        launch::launch(input, output);
+    } else if args.iter().any(|arg| arg == "--ansi") {
+        let (input, output) = ansi_frontend::create();
+        launch::launch(input, output);
+    } else {
+        let capture_period = arg_value(&args, "--capture-every")
+            .map(|period| period.parse().expect("Invalid capture period"));
+        let hot_reload = args.iter().any(|arg| arg == "--hot-reload");
+        let (input, output) = glutin_frontend::create(capture_period, hot_reload);
+        launch::launch(input, output);
+    }
 }
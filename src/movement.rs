@@ -0,0 +1,110 @@
+use cgmath::Vector2;
+use direction::Direction;
+use spatial_hash::SpatialHashTable;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MovementMode {
+    FourWay,
+    EightWay,
+}
+
+const FOUR_WAY_DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
+const EIGHT_WAY_DIRECTIONS: [Direction; 8] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+    Direction::NorthEast,
+    Direction::SouthEast,
+    Direction::SouthWest,
+    Direction::NorthWest,
+];
+
+impl MovementMode {
+    pub fn directions(self) -> &'static [Direction] {
+        match self {
+            MovementMode::FourWay => &FOUR_WAY_DIRECTIONS,
+            MovementMode::EightWay => &EIGHT_WAY_DIRECTIONS,
+        }
+    }
+
+    pub fn allows(self, direction: Direction) -> bool {
+        self.directions().contains(&direction)
+    }
+}
+
+// a diagonal step may not squeeze past anything solid on either side of the corner. this checks
+// solidity rather than wall_neighbours, so closed doors and other solid cells block corners like walls do
+pub fn cuts_corner(spatial_hash: &SpatialHashTable, from: Vector2<i32>, to: Vector2<i32>) -> bool {
+    let delta = to - from;
+    if delta.x == 0 || delta.y == 0 {
+        return false;
+    }
+
+    let is_solid = |coord| {
+        spatial_hash.get_signed(coord).map_or(false, |sh_cell| sh_cell.solid_count > 0)
+    };
+
+    is_solid(Vector2::new(to.x, from.y)) || is_solid(Vector2::new(from.x, to.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entity_store::{EntityStore, EntityId, insert};
+
+    fn with_solids(coords: &[Vector2<i32>]) -> SpatialHashTable {
+        let mut entity_store = EntityStore::new();
+        let mut spatial_hash = SpatialHashTable::new(3, 3);
+        for (index, coord) in coords.iter().enumerate() {
+            let id = index as EntityId;
+            for change in vec![insert::coord(id, *coord), insert::solid(id)] {
+                spatial_hash.update(&entity_store, &change, 0);
+                entity_store.commit(change);
+            }
+        }
+        spatial_hash
+    }
+
+    #[test]
+    fn diagonals_need_eight_way() {
+        assert!(MovementMode::FourWay.allows(Direction::North));
+        assert!(!MovementMode::FourWay.allows(Direction::NorthEast));
+        assert!(MovementMode::EightWay.allows(Direction::North));
+        assert!(MovementMode::EightWay.allows(Direction::NorthEast));
+        assert_eq!(MovementMode::FourWay.directions().len(), 4);
+        assert_eq!(MovementMode::EightWay.directions().len(), 8);
+    }
+
+    #[test]
+    fn open_diagonal_doesnt_cut_corner() {
+        let spatial_hash = with_solids(&[]);
+        assert!(!cuts_corner(&spatial_hash, Vector2::new(1, 1), Vector2::new(2, 0)));
+    }
+
+    #[test]
+    fn solid_beside_diagonal_cuts_corner() {
+        let spatial_hash = with_solids(&[Vector2::new(2, 1)]);
+        assert!(cuts_corner(&spatial_hash, Vector2::new(1, 1), Vector2::new(2, 0)));
+        assert!(cuts_corner(&spatial_hash, Vector2::new(1, 1), Vector2::new(2, 2)));
+        assert!(!cuts_corner(&spatial_hash, Vector2::new(1, 1), Vector2::new(0, 0)));
+
+        let spatial_hash = with_solids(&[Vector2::new(1, 0)]);
+        assert!(cuts_corner(&spatial_hash, Vector2::new(1, 1), Vector2::new(2, 0)));
+        assert!(cuts_corner(&spatial_hash, Vector2::new(1, 1), Vector2::new(0, 0)));
+        assert!(!cuts_corner(&spatial_hash, Vector2::new(1, 1), Vector2::new(2, 2)));
+    }
+
+    #[test]
+    fn orthogonal_steps_never_cut_corners() {
+        let spatial_hash = with_solids(&[Vector2::new(1, 0), Vector2::new(2, 1)]);
+        assert!(!cuts_corner(&spatial_hash, Vector2::new(1, 1), Vector2::new(1, 2)));
+        assert!(!cuts_corner(&spatial_hash, Vector2::new(1, 1), Vector2::new(0, 1)));
+    }
+}
@@ -1,8 +1,25 @@
 use std::time::Duration;
+use cgmath::Vector2;
 use entity_store::{EntityId, EntityChange, ComponentValue, EntityStore, insert, remove};
 use spatial_hash::SpatialHashTable;
 use append::Append;
-use content::{ChangeDesc, DoorState};
+use movement;
+use content::{ChangeDesc, DoorState, StatusEffectsInfo, DepthInfo, DepthType, ParticleEmitterInfo,
+              SpriteEffectInfo, FlashInfo};
+
+// draw corpses just above the floor
+const CORPSE_DEPTH: f32 = 0.02;
+
+const DAMAGE_FLASH_MS: u64 = 150;
+const DISSOLVE_MS: u64 = 500;
+
+fn damage_flash(amount: f32) -> FlashInfo {
+    FlashInfo::new(1.0, 0.2, 0.1, amount)
+}
+
+fn cell_centre(coord: Vector2<i32>) -> Vector2<f32> {
+    coord.cast::<f32>() + Vector2::new(0.5, 0.5)
+}
 
 pub fn check<R, D>(change: &EntityChange,
                    entity_store: &EntityStore,
@@ -17,6 +34,14 @@ pub fn check<R, D>(change: &EntityChange,
         &Insert(id, ComponentValue::Coord(coord)) => {
             if let Some(sh_cell) = spatial_hash.get_signed(coord) {
 
+                if entity_store.collider.contains(&id) {
+                    if let Some(current_coord) = entity_store.coord.get(&id) {
+                        if movement::cuts_corner(spatial_hash, *current_coord, coord) {
+                            return false;
+                        }
+                    }
+                }
+
                 if entity_store.door_opener.contains(&id) {
                     // open doors by bumping into them
                     if let Some(door_id) = sh_cell.door_set.iter().next() {
@@ -45,21 +70,38 @@ pub fn check<R, D>(change: &EntityChange,
 
                         if entity_store.bump_attack.contains(&id) {
                             if let Some(attackable_id) = sh_cell.attackable_set.iter().next() {
-                                let mid_change = entity_store.health.get(attackable_id).map(|health| {
-                                    insert::health(*attackable_id, health.reduce(1))
-                                });
+                                // the damage, any status effect and the sparks are applied when the bump lands
+                                let mut mid_changes = Vec::new();
+                                if let Some(health) = entity_store.health.get(attackable_id) {
+                                    mid_changes.push(insert::health(*attackable_id, health.reduce(1)));
+                                }
+                                if let Some(effect) = entity_store.bump_effect.get(&id) {
+                                    let effects = entity_store.status_effects.get(attackable_id).cloned()
+                                        .unwrap_or_else(StatusEffectsInfo::new)
+                                        .with_effect(*effect);
+                                    mid_changes.push(insert::status_effects(*attackable_id, effects));
+                                }
+                                // sparks fly from where the attacker strikes
+                                let impact = (cell_centre(*current_coord) + cell_centre(coord)) / 2.0;
+                                let sparks = ParticleEmitterInfo::sparks(impact, (coord - *current_coord).cast());
+                                mid_changes.push(insert::particle_emitter(*attackable_id, sparks));
                                 reactions.append(ChangeDesc::bump_slide(id,
                                                                         current_coord.cast(),
                                                                         coord.cast(),
                                                                         Duration::from_millis(100),
                                                                         0.49,
-                                                                        mid_change));
+                                                                        mid_changes));
                                 return false;
                             }
                         }
 
                         // Start the slide animation for the move.
                         reactions.append(ChangeDesc::slide(id, current_coord.cast(), coord.cast(), Duration::from_millis(50)));
+
+                        if sh_cell.water_count > 0 {
+                            let splash = ParticleEmitterInfo::splash(cell_centre(coord));
+                            reactions.append(ChangeDesc::immediate(insert::particle_emitter(id, splash)));
+                        }
                     }
                 }
             } else {
@@ -96,12 +138,57 @@ pub fn check<R, D>(change: &EntityChange,
             if let Some(door_info) = entity_store.door.get(&id) {
                 reactions.append(ChangeDesc::immediate(insert::sprite(id, door_info.typ.closed_sprite())));
             }
+            if let Some(coord) = entity_store.coord.get(&id) {
+                reactions.append(ChangeDesc::immediate(insert::particle_emitter(id, ParticleEmitterInfo::dust(cell_centre(*coord)))));
+            }
         }
         &Insert(id, ComponentValue::Health(info)) => {
-            if info.current <= 0 {
-                to_delete.append(id);
+            // only npcs die, as there's no game over yet to handle the player running out of health
+            if info.current <= 0 && entity_store.npc.contains(&id) {
+                // stop the entity acting or being attacked while it dies
+                reactions.append(ChangeDesc::immediate(remove::npc(id)));
+                reactions.append(ChangeDesc::immediate(remove::attackable(id)));
+                reactions.append(ChangeDesc::immediate(remove::bump_attack(id)));
+                reactions.append(ChangeDesc::immediate(remove::collider(id)));
+                reactions.append(ChangeDesc::immediate(remove::field_ui(id)));
+                reactions.append(ChangeDesc::immediate(remove::status_effects(id)));
+                if let Some(death_type) = entity_store.death.get(&id) {
+                    reactions.append(ChangeDesc::sprites(id, death_type.animation(), insert::corpse(id)));
+                } else {
+                    // without a death animation, the entity fades away before being deleted
+                    reactions.append(ChangeDesc::sprite_effect_transition(id,
+                                                                          SpriteEffectInfo::dissolve(0.0),
+                                                                          SpriteEffectInfo::dissolve(1.0),
+                                                                          Duration::from_millis(DISSOLVE_MS),
+                                                                          insert::dissolved(id)));
+                }
                 return false;
             }
+
+            let damaged = entity_store.health.get(&id).map_or(false, |health| info.current < health.current);
+            if damaged {
+                reactions.append(ChangeDesc::flash(id,
+                                                   damage_flash(0.8),
+                                                   damage_flash(0.0),
+                                                   Duration::from_millis(DAMAGE_FLASH_MS)));
+            }
+        }
+        &Insert(id, ComponentValue::ParticleEmitter(_)) => {
+            // frontends start the burst when the emitter is inserted, so it needn't stay around
+            reactions.append(ChangeDesc::immediate(remove::particle_emitter(id)));
+        }
+        &Insert(id, ComponentValue::Dissolved) => {
+            to_delete.append(id);
+            return false;
+        }
+        &Insert(id, ComponentValue::Corpse) => {
+            // the death animation has finished, so leave a corpse behind
+            if let Some(death_type) = entity_store.death.get(&id) {
+                reactions.append(ChangeDesc::immediate(insert::sprite(id, death_type.corpse_sprite())));
+                reactions.append(ChangeDesc::immediate(insert::depth(id, DepthInfo::new(DepthType::Bottom, CORPSE_DEPTH))));
+                reactions.append(ChangeDesc::immediate(remove::health(id)));
+                reactions.append(ChangeDesc::immediate(remove::death(id)));
+            }
         }
         _ => {}
     }
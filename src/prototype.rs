@@ -2,7 +2,9 @@ use cgmath::Vector2;
 use entity_store::{EntityId, EntityChange, insert};
 use content::{TileSprite, DepthType, DepthInfo, DoorState, DoorInfo,
               DoorType, SpriteEffectInfo, LightInfo, HealthInfo,
-              FieldUiOffsets};
+              FieldUiOffsets, StatusEffect, DeathType,
+              Creature, SpawnerInfo, TintInfo, GasInfo};
+use content::gas;
 use append::Append;
 
 pub fn angler<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>) {
@@ -16,6 +18,12 @@ pub fn angler<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vec
     changes.append(insert::light(id, LightInfo::new(0.2, 20, 1.0, 1.0, 1.0, 1.0)));
     changes.append(insert::bump_attack(id));
     changes.append(insert::attackable(id));
+    changes.append(insert::health(id, HealthInfo::full(10)));
+    changes.append(insert::field_ui(id, FieldUiOffsets {
+        health_vertical: 9,
+    }));
+    // the lure dazzles whatever the angler bumps into
+    changes.append(insert::bump_effect(id, StatusEffect::stun(1)));
 }
 
 pub fn crab<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>) {
@@ -32,6 +40,8 @@ pub fn crab<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vecto
         health_vertical: 7,
     }));
     changes.append(insert::hide_in_dark(id));
+    changes.append(insert::bump_effect(id, StatusEffect::poison(3, 1)));
+    changes.append(insert::death(id, DeathType::Crab));
 }
 
 pub fn snail<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>) {
@@ -48,6 +58,8 @@ pub fn snail<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vect
         health_vertical: 1,
     }));
     changes.append(insert::hide_in_dark(id));
+    changes.append(insert::bump_effect(id, StatusEffect::slow(4)));
+    changes.append(insert::death(id, DeathType::Snail));
 }
 
 pub fn inner_wall<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>) {
@@ -77,12 +89,19 @@ pub fn inner_floor<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord
     changes.append(insert::depth(id, DepthType::Bottom.into()));
 }
 
+// the hot floor around a gas vent
+pub fn vent_floor<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>) {
+    inner_floor(changes, id, coord);
+    changes.append(insert::sprite_effect(id, SpriteEffectInfo::heat_shimmer(1.0, 6.0)));
+}
+
 pub fn inner_water<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>) {
     changes.append(insert::coord(id, coord));
     changes.append(insert::position(id, coord.cast()));
     changes.append(insert::sprite(id, TileSprite::InnerWater));
     changes.append(insert::depth(id, DepthInfo::new(DepthType::Gradient, 0.01)));
     changes.append(insert::sprite_effect(id, SpriteEffectInfo::water(6, 0.3, 0.7)));
+    changes.append(insert::water(id));
 }
 
 pub fn outer_floor<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>) {
@@ -91,6 +110,7 @@ pub fn outer_floor<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord
     changes.append(insert::sprite(id, TileSprite::OuterFloor));
     changes.append(insert::depth(id, DepthType::Bottom.into()));
     changes.append(insert::sprite_effect(id, SpriteEffectInfo::water(3, 0.2, 0.8)));
+    changes.append(insert::outdoor(id));
 }
 
 pub fn inner_door<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>) {
@@ -121,6 +141,28 @@ pub fn window<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vec
     changes.append(insert::opacity(id, -1.0));
 }
 
+pub fn stained_window<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>, filter: [f32; 3]) {
+    window(changes, id, coord);
+    changes.append(insert::tint(id, TintInfo::new(filter[0], filter[1], filter[2])));
+}
+
+pub fn gas<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>, density: f32) {
+    changes.append(insert::coord(id, coord));
+    changes.append(insert::position(id, coord.cast()));
+    changes.append(insert::sprite(id, TileSprite::Smoke));
+    changes.append(insert::depth(id, DepthInfo::new(DepthType::Fixed, 0.5)));
+    changes.append(insert::sprite_effect(id, SpriteEffectInfo::smoke(density)));
+    changes.append(insert::gas(id, GasInfo::new(density)));
+    changes.append(insert::opacity(id, density * gas::MAX_OPACITY));
+    changes.append(insert::hide_in_dark(id));
+}
+
+// fills its cell with gas every turn
+pub fn gas_vent<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>, density: f32) {
+    changes.append(insert::coord(id, coord));
+    changes.append(insert::gas_vent(id, density));
+}
+
 pub fn light<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>, colour: [f32; 3]) {
     changes.append(insert::coord(id, coord));
     changes.append(insert::position(id, coord.cast()));
@@ -128,3 +170,15 @@ pub fn light<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vect
     changes.append(insert::depth(id, DepthInfo::new(DepthType::Fixed, 0.0)));
     changes.append(insert::light(id, LightInfo::new(1.0, 20, 2.0, colour[0], colour[1], colour[2])));
 }
+
+pub fn spawner<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>, info: SpawnerInfo) {
+    changes.append(insert::coord(id, coord));
+    changes.append(insert::spawner(id, info));
+}
+
+pub fn creature<A: Append<EntityChange>>(changes: &mut A, id: EntityId, coord: Vector2<i32>, creature: Creature) {
+    match creature {
+        Creature::Crab => crab(changes, id, coord),
+        Creature::Snail => snail(changes, id, coord),
+    }
+}
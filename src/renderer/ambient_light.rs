@@ -0,0 +1,31 @@
+use gfx;
+
+use content::AmbientLightInfo;
+
+// the colour of each light is in rgb, and its intensity in a
+gfx_constant_struct!( AmbientLight {
+    ambient: [f32; 4] = "u_AmbientLight",
+    sky: [f32; 4] = "u_SkyLight",
+});
+
+fn colour_intensity(info: AmbientLightInfo) -> [f32; 4] {
+    [info.colour[0], info.colour[1], info.colour[2], info.intensity]
+}
+
+impl AmbientLight {
+    pub fn new(ambient: AmbientLightInfo, sky: AmbientLightInfo) -> Self {
+        Self {
+            ambient: colour_intensity(ambient),
+            sky: colour_intensity(sky),
+        }
+    }
+}
+
+pub type AmbientLightBuffer<R> = gfx::handle::Buffer<R, AmbientLight>;
+
+pub fn create_buffer<R, F>(factory: &mut F) -> AmbientLightBuffer<R>
+    where R: gfx::Resources,
+          F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+{
+    factory.create_constant_buffer(1)
+}
@@ -0,0 +1,40 @@
+use gfx;
+use gfx::format::{Formatted, ChannelTyped};
+use image::{RgbaImage, imageops};
+
+use renderer::formats::ColourFormat;
+use renderer::render_target::RenderTarget;
+
+// reads the unscaled render target back from the gpu
+pub fn read_target<R, C, F, D>(target: &RenderTarget<R>,
+                               encoder: &mut gfx::Encoder<R, C>,
+                               factory: &mut F,
+                               device: &mut D) -> RgbaImage
+    where R: gfx::Resources,
+          C: gfx::CommandBuffer<R>,
+          F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+          D: gfx::traits::Device<Resources=R, CommandBuffer=C>,
+{
+    let width = target.width as u32;
+    let height = target.height as u32;
+
+    let download = factory.create_download_buffer::<[u8; 4]>((width * height) as usize)
+        .expect("Failed to create download buffer");
+
+    let channel_type = <<ColourFormat as Formatted>::Channel as ChannelTyped>::get_channel_type();
+    let info = target.texture.get_info().to_raw_image_info(channel_type, 0);
+    encoder.copy_texture_to_buffer_raw(target.texture.raw(), None, info, download.raw(), 0)
+        .expect("Failed to copy render target");
+    encoder.flush(device);
+
+    let reader = factory.read_mapping(&download)
+        .expect("Failed to map download buffer");
+
+    let mut image = RgbaImage::new(width, height);
+    for (pixel, data) in izip!(image.pixels_mut(), reader.iter()) {
+        pixel.data = *data;
+    }
+
+    // rows are read back from the bottom up
+    imageops::flip_vertical(&image)
+}
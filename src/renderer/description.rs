@@ -0,0 +1,189 @@
+use gfx;
+
+use renderer::formats::ColourFormat;
+use renderer::render_target::RenderTarget;
+use renderer::dimensions::{Dimensions, FixedDimensions, OutputDimensions, WorldDimensions};
+use renderer::common;
+use renderer::template::{self, ShaderSource};
+use renderer::sizes;
+use renderer::font;
+
+gfx_vertex_struct!( Vertex {
+    pos: [f32; 2] = "a_Pos",
+});
+
+// a rectangle in pixels of the render target
+gfx_vertex_struct!( Instance {
+    coord: [f32; 2] = "a_Coord",
+    size: [f32; 2] = "a_Size",
+    colour: [f32; 4] = "a_Colour",
+});
+
+gfx_pipeline!( pipe {
+    vertex: gfx::VertexBuffer<Vertex> = (),
+    instance: gfx::InstanceBuffer<Instance> = (),
+    fixed_dimensions: gfx::ConstantBuffer<FixedDimensions> = "FixedDimensions",
+    output_dimensions: gfx::ConstantBuffer<OutputDimensions> = "OutputDimensions",
+    world_dimensions: gfx::ConstantBuffer<WorldDimensions> = "WorldDimensions",
+    out_colour: gfx::BlendTarget<ColourFormat> = ("Target0", gfx::state::ColorMask::all(), gfx::preset::blend::ALPHA),
+});
+
+// in pixels of the render target
+const MARGIN_PX: u32 = 4;
+const PADDING_PX: u32 = 2;
+
+const BACKGROUND_COLOUR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const TEXT_COLOUR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+// the description of the cell under the cursor, drawn in the bottom-left corner
+pub struct Description<R: gfx::Resources> {
+    bundle: gfx::pso::bundle::Bundle<R, pipe::Data<R>>,
+    instance_upload: gfx::handle::Buffer<R, Instance>,
+    text: Option<String>,
+    num_instances: usize,
+    dirty: bool,
+    target_width: u16,
+    target_height: u16,
+}
+
+impl<R: gfx::Resources> Description<R> {
+    pub fn new<F>(target: &RenderTarget<R>,
+                  dimensions: &Dimensions<R>,
+                  shader_source: &ShaderSource,
+                  factory: &mut F) -> Self
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let pso = Self::create_pso(shader_source, factory).expect("Failed to create pipeline");
+
+        let vertex_data: Vec<Vertex> = common::QUAD_VERTICES_REFL.iter()
+            .map(|v| {
+                Vertex {
+                    pos: *v,
+                }
+            }).collect();
+
+        let (vertex_buffer, slice) =
+            factory.create_vertex_buffer_with_slice(
+                &vertex_data,
+                &common::QUAD_INDICES[..]);
+
+        let data = pipe::Data {
+            vertex: vertex_buffer,
+            instance: common::create_instance_buffer(sizes::DESCRIPTION_MAX_NUM_INSTANCES, factory)
+                .expect("Failed to create instance buffer"),
+            fixed_dimensions: dimensions.fixed_dimensions.clone(),
+            output_dimensions: dimensions.output_dimensions.clone(),
+            world_dimensions: dimensions.world_dimensions.clone(),
+            out_colour: target.rtv.clone(),
+        };
+
+        Self {
+            bundle: gfx::pso::bundle::Bundle::new(slice, pso, data),
+            instance_upload: factory.create_upload_buffer(sizes::DESCRIPTION_MAX_NUM_INSTANCES)
+                .expect("Failed to create upload buffer"),
+            text: None,
+            num_instances: 0,
+            dirty: false,
+            target_width: target.width,
+            target_height: target.height,
+        }
+    }
+
+    fn create_pso<F>(shader_source: &ShaderSource, factory: &mut F) -> Result<gfx::pso::PipelineState<R, pipe::Meta>, String>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let (handlebars, context) = template::make_shader_template_context(shader_source)?;
+        let vert = template::populate_shader(&handlebars, &context, shader!(shader_source, "description.150.hbs.vert")?.as_ref())?;
+        // the same flat colour as the minimap
+        let frag = shader!(shader_source, "minimap.150.frag")?;
+
+        factory.create_pipeline_simple(vert.as_bytes(), frag.as_bytes(), pipe::new())
+            .map_err(|e| format!("Failed to create description pipeline: {:?}", e))
+    }
+
+    pub fn reload_shaders<F>(&mut self, shader_source: &ShaderSource, factory: &mut F) -> Result<(), String>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        self.bundle.pso = Self::create_pso(shader_source, factory)?;
+        Ok(())
+    }
+
+    pub fn handle_resize(&mut self, target: &RenderTarget<R>) {
+        self.bundle.data.out_colour = target.rtv.clone();
+        self.target_width = target.width;
+        self.target_height = target.height;
+        self.dirty = true;
+    }
+
+    pub fn set_text(&mut self, text: Option<&str>) {
+        self.text = text.map(|t| t.to_string());
+        self.dirty = true;
+    }
+
+    pub fn draw<C, F>(&mut self, encoder: &mut gfx::Encoder<R, C>, factory: &mut F)
+        where C: gfx::CommandBuffer<R>,
+              F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        if self.dirty {
+            self.num_instances = {
+                let mut writer = factory.write_mapping(&self.instance_upload)
+                    .expect("Failed to map upload buffer");
+                self.write_instances(&mut writer)
+            };
+            encoder.copy_buffer(&self.instance_upload, &self.bundle.data.instance, 0, 0, self.num_instances)
+                .expect("Failed to copy instance buffer");
+            self.dirty = false;
+        }
+
+        if self.num_instances == 0 {
+            return;
+        }
+
+        self.bundle.slice.instances = Some((self.num_instances as u32, 0));
+        encoder.draw(&self.bundle.slice, &self.bundle.pso, &self.bundle.data);
+    }
+
+    fn write_instances(&self, instances: &mut [Instance]) -> usize {
+        let text = if let Some(ref text) = self.text {
+            text
+        } else {
+            return 0;
+        };
+
+        // text that doesn't fit across the target is cut short
+        let max_width = (self.target_width as u32).saturating_sub((MARGIN_PX + PADDING_PX) * 2);
+        let max_chars = ((max_width + 1) / (font::GLYPH_WIDTH + 1)) as usize;
+        let text: String = text.chars().take(max_chars).collect();
+        if text.is_empty() {
+            return 0;
+        }
+
+        let box_width = font::text_width(&text) + PADDING_PX * 2;
+        let box_height = font::GLYPH_HEIGHT + PADDING_PX * 2;
+        let box_x = MARGIN_PX;
+        let box_y = (self.target_height as u32).saturating_sub(MARGIN_PX + box_height);
+
+        // instances are drawn in order, so the background goes first
+        instances[0] = Instance {
+            coord: [box_x as f32, box_y as f32],
+            size: [box_width as f32, box_height as f32],
+            colour: BACKGROUND_COLOUR,
+        };
+
+        let mut count = 1;
+        let text_x = box_x + PADDING_PX;
+        let text_y = box_y + PADDING_PX;
+        font::for_each_pixel(&text, |x, y| {
+            if count < instances.len() {
+                instances[count] = Instance {
+                    coord: [(text_x + x) as f32, (text_y + y) as f32],
+                    size: [1.0, 1.0],
+                    colour: TEXT_COLOUR,
+                };
+                count += 1;
+            }
+        });
+
+        count
+    }
+}
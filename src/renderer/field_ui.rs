@@ -11,11 +11,11 @@ use renderer::scroll_offset::{ScrollOffset, ScrollOffsetBuffer};
 
 use renderer::dimensions::{Dimensions, FixedDimensions, OutputDimensions, WorldDimensions};
 use renderer::common;
-use renderer::template;
+use renderer::template::{self, ShaderSource};
 use renderer::sizes;
 
 use entity_store::EntityStore;
-use content::{HealthInfo, FieldUiSprite};
+use content::{HealthInfo, FieldUiSprite, StatusEffectsInfo, StatusEffectType};
 use res::input_sprite;
 
 gfx_vertex_struct!( Vertex {
@@ -28,6 +28,7 @@ gfx_vertex_struct!( Instance {
     pix_size: [f32; 2] = "a_PixSize",
     pix_offset: [f32; 2] = "a_PixOffset",
     depth: f32 = "a_Depth",
+    flags: u32 = "a_Flags",
 });
 
 gfx_pipeline!( pipe {
@@ -44,6 +45,13 @@ gfx_pipeline!( pipe {
     tex: gfx::TextureSampler<[f32; 4]> = "t_Texture",
 });
 
+pub mod field_ui_flags {
+    pub const IGNORE_VISION: u32 = 1 << 0;
+}
+
+// in front of other field ui sprites
+const CURSOR_DEPTH: f32 = -0.5;
+
 impl Instance {
     fn from_location(sprite_location: &SpriteLocation,
                      position: Vector2<f32>,
@@ -56,6 +64,7 @@ impl Instance {
             pix_size: sprite_location.size.into(),
             pix_offset: offset.cast().into(),
             depth,
+            flags: 0,
         }
     }
 }
@@ -66,6 +75,11 @@ struct SpriteCache {
     empty_health: SpriteLocation,
     health_step: Vector2<i32>,
     health_sprites_per_row: u32,
+    poison: SpriteLocation,
+    stun: SpriteLocation,
+    slow: SpriteLocation,
+    status_effect_step: Vector2<i32>,
+    cursor: SpriteLocation,
 }
 
 impl SpriteCache {
@@ -73,11 +87,26 @@ impl SpriteCache {
         let full_health = *sprite_table.get(FieldUiSprite::HealthFull).expect("Missing sprite");
         let health_step = Vector2::new(full_health.size.x as i32 + 1, full_health.size.y as i32 + 1);
         let health_sprites_per_row = input_sprite::WIDTH_PX / (health_step.x as u32);
+        let poison = *sprite_table.get(FieldUiSprite::Poison).expect("Missing sprite");
+        let status_effect_step = Vector2::new(poison.size.x as i32 + 1, poison.size.y as i32 + 1);
         Self {
             full_health,
             empty_health: *sprite_table.get(FieldUiSprite::HealthEmpty).expect("Missing sprite"),
             health_step,
             health_sprites_per_row,
+            poison,
+            stun: *sprite_table.get(FieldUiSprite::Stun).expect("Missing sprite"),
+            slow: *sprite_table.get(FieldUiSprite::Slow).expect("Missing sprite"),
+            status_effect_step,
+            cursor: *sprite_table.get(FieldUiSprite::Cursor).expect("Missing sprite"),
+        }
+    }
+
+    fn status_effect(&self, typ: StatusEffectType) -> &SpriteLocation {
+        match typ {
+            StatusEffectType::Poison => &self.poison,
+            StatusEffectType::Stun => &self.stun,
+            StatusEffectType::Slow => &self.slow,
         }
     }
 }
@@ -87,6 +116,7 @@ pub struct FieldUi<R: gfx::Resources> {
     instance_upload: gfx::handle::Buffer<R, Instance>,
     sprite_table: FieldUiSpriteTable,
     sprite_cache: SpriteCache,
+    cursor: Option<Vector2<i32>>,
 }
 
 impl<R: gfx::Resources> FieldUi<R> {
@@ -97,15 +127,11 @@ impl<R: gfx::Resources> FieldUi<R> {
                   vision_buffer: &VisionBuffer<R>,
                   frame_info_buffer: &FrameInfoBuffer<R>,
                   scroll_offset_buffer: &ScrollOffsetBuffer<R>,
+                  shader_source: &ShaderSource,
                   factory: &mut F) -> Self
         where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
     {
-        let (handlebars, context) = template::make_shader_template_context();
-
-        let pso = factory.create_pipeline_simple(
-            template::populate_shader(&handlebars, &context, include_bytes!("shaders/field_ui.150.hbs.vert")).as_bytes(),
-            template::populate_shader(&handlebars, &context, include_bytes!("shaders/field_ui.150.hbs.frag")).as_bytes(),
-            pipe::new()).expect("Failed to create pipeline");
+        let pso = Self::create_pso(shader_source, factory).expect("Failed to create pipeline");
 
         let vertex_data: Vec<Vertex> = common::QUAD_VERTICES_REFL.iter()
             .map(|v| {
@@ -148,9 +174,32 @@ impl<R: gfx::Resources> FieldUi<R> {
                 .expect("Failed to create upload buffer"),
             sprite_table,
             sprite_cache,
+            cursor: None,
         }
     }
 
+    fn create_pso<F>(shader_source: &ShaderSource, factory: &mut F) -> Result<gfx::pso::PipelineState<R, pipe::Meta>, String>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let (handlebars, context) = template::make_shader_template_context(shader_source)?;
+        let vert = template::populate_shader(&handlebars, &context, shader!(shader_source, "field_ui.150.hbs.vert")?.as_ref())?;
+        let frag = template::populate_shader(&handlebars, &context, shader!(shader_source, "field_ui.150.hbs.frag")?.as_ref())?;
+
+        factory.create_pipeline_simple(vert.as_bytes(), frag.as_bytes(), pipe::new())
+            .map_err(|e| format!("Failed to create field ui pipeline: {:?}", e))
+    }
+
+    pub fn reload_shaders<F>(&mut self, shader_source: &ShaderSource, factory: &mut F) -> Result<(), String>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        self.bundle.pso = Self::create_pso(shader_source, factory)?;
+        Ok(())
+    }
+
+    pub fn set_sprite_sheet(&mut self, sprite_sheet: &SpriteSheetTexture<R>) {
+        self.bundle.data.tex.0 = sprite_sheet.srv.clone();
+    }
+
     pub fn handle_resize<C>(&mut self, target: &RenderTarget<R>, _encoder: &mut gfx::Encoder<R, C>)
         where C: gfx::CommandBuffer<R>,
     {
@@ -158,6 +207,10 @@ impl<R: gfx::Resources> FieldUi<R> {
         self.bundle.data.out_depth = target.dsv.clone();
     }
 
+    pub fn set_cursor(&mut self, cursor: Option<Vector2<i32>>) {
+        self.cursor = cursor;
+    }
+
     pub fn clear<C>(&self, encoder: &mut gfx::Encoder<R, C>)
         where C: gfx::CommandBuffer<R>,
     {
@@ -173,7 +226,8 @@ impl<R: gfx::Resources> FieldUi<R> {
             let mut writer = factory.write_mapping(&self.instance_upload)
                 .expect("Failed to map upload buffer");
 
-            Self::draw_health(&self.sprite_cache, &mut writer, 0, entity_store)
+            let count = Self::draw_health(&self.sprite_cache, &mut writer, 0, entity_store);
+            Self::draw_cursor(&self.sprite_cache, &mut writer, count, self.cursor)
         };
 
         self.bundle.slice.instances = Some((num_instances, 0));
@@ -199,11 +253,57 @@ impl<R: gfx::Resources> FieldUi<R> {
             if let Some(health) = entity_store.health.get(id) {
                 count = Self::draw_health_entity(sprite_cache, offsets.health_vertical, instances, count, position, *health);
             }
+
+            if let Some(status_effects) = entity_store.status_effects.get(id) {
+                // status effects go in the row above the health pips
+                let health_rows = entity_store.health.get(id).map(|health| {
+                    let max = cmp::max(health.max, 0) as u32;
+                    (max + sprite_cache.health_sprites_per_row - 1) / sprite_cache.health_sprites_per_row
+                }).unwrap_or(0);
+                let vertical_offset = offsets.health_vertical + health_rows as i32 * sprite_cache.health_step.y;
+                count = Self::draw_status_effects_entity(sprite_cache, vertical_offset, instances, count, position, status_effects);
+            }
         }
 
         count
     }
 
+    fn draw_cursor(sprite_cache: &SpriteCache,
+                   instances: &mut [Instance],
+                   base: u32,
+                   cursor: Option<Vector2<i32>>) -> u32
+    {
+        if let Some(coord) = cursor {
+            let mut instance = Instance::from_location(&sprite_cache.cursor, coord.cast(), Vector2::new(0, 0), CURSOR_DEPTH);
+            // the cursor can be moved over cells that aren't visible
+            instance.flags |= field_ui_flags::IGNORE_VISION;
+            instances[base as usize] = instance;
+            base + 1
+        } else {
+            base
+        }
+    }
+
+    fn draw_status_effects_entity(sprite_cache: &SpriteCache,
+                                  vertical_offset: i32,
+                                  instances: &mut [Instance],
+                                  mut base: u32,
+                                  position: Vector2<f32>,
+                                  status_effects: &StatusEffectsInfo) -> u32
+    {
+        let base_offset = Vector2::new(0, vertical_offset);
+
+        for (i, effect) in status_effects.effects.iter().enumerate() {
+            let sprite = sprite_cache.status_effect(effect.typ);
+            let offset = base_offset + Vector2::new(-(i as i32) * sprite_cache.status_effect_step.x, 0);
+
+            instances[base as usize] = Instance::from_location(sprite, position, offset, 0.0);
+            base += 1;
+        }
+
+        base
+    }
+
     fn draw_health_entity(sprite_cache: &SpriteCache,
                           vertical_offset: i32,
                           instances: &mut [Instance],
@@ -0,0 +1,106 @@
+// a tiny pixel font for text drawn over the game, in capitals only
+
+pub const GLYPH_WIDTH: u32 = 3;
+pub const GLYPH_HEIGHT: u32 = 5;
+
+// each row is a bitmap, with the leftmost pixel in the highest bit
+pub type Glyph = [u8; GLYPH_HEIGHT as usize];
+
+// characters without a glyph are left as a gap
+pub fn glyph(ch: char) -> Option<Glyph> {
+    let glyph = match ch.to_ascii_uppercase() {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        _ => return None,
+    };
+
+    Some(glyph)
+}
+
+// calls f with the offset in pixels of each pixel of the text that's drawn
+pub fn for_each_pixel<F: FnMut(u32, u32)>(text: &str, mut f: F) {
+    for (i, ch) in text.chars().enumerate() {
+        if let Some(glyph) = glyph(ch) {
+            let x_base = i as u32 * (GLYPH_WIDTH + 1);
+            for (y, row) in glyph.iter().enumerate() {
+                for x in 0..GLYPH_WIDTH {
+                    if row & (1 << (GLYPH_WIDTH - 1 - x)) != 0 {
+                        f(x_base + x, y as u32);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn text_width(text: &str) -> u32 {
+    let len = text.chars().count() as u32;
+    if len == 0 { 0 } else { len * (GLYPH_WIDTH + 1) - 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn descriptions_have_glyphs() {
+        let text = "crab (3/5), stunned, light (colour 0.5, 1.0, 0.2)";
+        for ch in text.chars().filter(|ch| *ch != ' ') {
+            assert!(glyph(ch).is_some(), "no glyph for {:?}", ch);
+        }
+    }
+
+    #[test]
+    fn pixels_stay_within_text_width() {
+        let text = "wall";
+        let width = text_width(text);
+        let mut max_x = 0;
+        for_each_pixel(text, |x, y| {
+            assert!(y < GLYPH_HEIGHT);
+            max_x = max_x.max(x);
+        });
+        assert_eq!(width, 15);
+        assert_eq!(max_x, width - 1);
+    }
+}
@@ -2,3 +2,6 @@ use gfx;
 
 pub type ColourFormat = gfx::format::Srgba8;
 pub type DepthFormat = gfx::format::DepthStencil;
+
+// normals aren't colours, so they mustn't be gamma corrected
+pub type NormalMapFormat = gfx::format::Rgba8;
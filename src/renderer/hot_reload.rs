@@ -0,0 +1,98 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, Duration, SystemTime};
+
+use renderer::template::ShaderSource;
+use res::{paths, files};
+
+// checking modification times every frame is wasteful, and edits needn't show up instantly
+const POLL_INTERVAL_MS: u64 = 500;
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+struct FileWatcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl FileWatcher {
+    fn new(paths: Vec<PathBuf>) -> Self {
+        let files = paths.into_iter().map(|path| {
+            let time = modified(&path);
+            (path, time)
+        }).collect();
+
+        Self {
+            files,
+        }
+    }
+
+    fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for &mut (ref path, ref mut time) in self.files.iter_mut() {
+            let current = modified(path);
+            if current != *time {
+                *time = current;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Changes {
+    pub shaders: bool,
+    pub sprite_sheet: bool,
+}
+
+// watches the shaders in the source tree and the sprite sheet and its normal map in the resource directory
+pub struct HotReload {
+    shader_source: ShaderSource,
+    shaders: FileWatcher,
+    sprite_sheet: FileWatcher,
+    last_poll: Instant,
+}
+
+impl HotReload {
+    pub fn new() -> Self {
+        let shader_source = ShaderSource::source_tree();
+
+        let shader_paths = shader_source.dir()
+            .and_then(|dir| fs::read_dir(dir).ok())
+            .map(|entries| entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect())
+            .unwrap_or_else(Vec::new);
+
+        if shader_paths.is_empty() {
+            eprintln!("No shaders found to watch in the source tree");
+        }
+
+        Self {
+            shader_source,
+            shaders: FileWatcher::new(shader_paths),
+            sprite_sheet: FileWatcher::new(vec![
+                paths::res_path(files::SPRITE_SHEET),
+                paths::res_path(files::SPRITE_NORMAL_MAP),
+            ]),
+            last_poll: Instant::now(),
+        }
+    }
+
+    pub fn shader_source(&self) -> &ShaderSource {
+        &self.shader_source
+    }
+
+    pub fn poll(&mut self) -> Changes {
+        let now = Instant::now();
+        if now - self.last_poll < Duration::from_millis(POLL_INTERVAL_MS) {
+            return Changes::default();
+        }
+        self.last_poll = now;
+
+        Changes {
+            shaders: self.shaders.changed(),
+            sprite_sheet: self.sprite_sheet.changed(),
+        }
+    }
+}
@@ -8,7 +8,7 @@ use renderer::tile_renderer::{Instance, SpriteRenderInfo, WallSpriteRenderInfo,
 use renderer::sprite_sheet::TileSpriteTable;
 
 use direction::Directions;
-use content::TileSprite;
+use content::{TileSprite, SpriteEffectInfo};
 
 type InstanceIndex = u16;
 
@@ -29,6 +29,17 @@ impl InstanceManager {
         self.index_allocator.peek() as u32
     }
 
+    fn update_sprite_effect(instance: &mut Instance, sprite_effect: SpriteEffectInfo) {
+        instance.flags |= instance_flags::SPRITE_EFFECT;
+        if sprite_effect.effect.is_translucent() {
+            instance.flags |= instance_flags::TRANSLUCENT;
+        } else {
+            instance.flags &= !instance_flags::TRANSLUCENT;
+        }
+        instance.sprite_effect = sprite_effect.effect as u32;
+        instance.sprite_effect_args = sprite_effect.args;
+    }
+
     fn update_sprite(&mut self, instances: &mut [Instance],
                      entity_store: &EntityStore,spatial_hash: &SpatialHashTable, sprite_table: &TileSpriteTable,
                      index: InstanceIndex, position: Vector2<f32>, sprite: TileSprite) {
@@ -73,6 +84,8 @@ impl InstanceManager {
                 } else {
                     let index = self.index_allocator.allocate();
                     self.index_table.insert(id, index);
+                    // indices are reused, so clear anything left by the previous entity
+                    instances[index as usize] = Instance::default();
                     index
                 };
                 {
@@ -86,6 +99,15 @@ impl InstanceManager {
                     if entity_store.hide_in_dark.contains(&id) {
                         instance.hide_in_dark = 1;
                     }
+                    if let Some(sprite_effect) = entity_store.sprite_effect.get(&id) {
+                        Self::update_sprite_effect(instance, *sprite_effect);
+                    }
+                    if let Some(outline) = entity_store.outline.get(&id) {
+                        instance.update_outline(*outline);
+                    }
+                    if let Some(flash) = entity_store.flash.get(&id) {
+                        instance.update_flash(*flash);
+                    }
                 }
 
                 if let Some(sprite) = entity_store.sprite.get(&id) {
@@ -108,10 +130,17 @@ impl InstanceManager {
             }
             &Insert(id, SpriteEffect(sprite_effect)) => {
                 if let Some(index) = self.index_table.get(&id).cloned() {
-                    let instance = &mut instances[index as usize];
-                    instance.flags |= instance_flags::SPRITE_EFFECT;
-                    instance.sprite_effect = sprite_effect.effect as u32;
-                    instance.sprite_effect_args = sprite_effect.args;
+                    Self::update_sprite_effect(&mut instances[index as usize], sprite_effect);
+                }
+            }
+            &Insert(id, Outline(outline)) => {
+                if let Some(index) = self.index_table.get(&id).cloned() {
+                    instances[index as usize].update_outline(outline);
+                }
+            }
+            &Insert(id, Flash(flash)) => {
+                if let Some(index) = self.index_table.get(&id).cloned() {
+                    instances[index as usize].update_flash(flash);
                 }
             }
             &Insert(id, HideInDark) => {
@@ -128,12 +157,22 @@ impl InstanceManager {
             }
             &Remove(id, ComponentType::Sprite) => {
                 if let Some(index) = self.index_table.get(&id).cloned() {
-                    instances[index as usize].flags &= !instance_flags::SPRITE_EFFECT;
+                    instances[index as usize].update_sprite_info(SpriteRenderInfo::blank());
                 }
             }
             &Remove(id, ComponentType::SpriteEffect) => {
                 if let Some(index) = self.index_table.get(&id).cloned() {
-                    instances[index as usize].update_sprite_info(SpriteRenderInfo::blank());
+                    instances[index as usize].flags &= !(instance_flags::SPRITE_EFFECT | instance_flags::TRANSLUCENT);
+                }
+            }
+            &Remove(id, ComponentType::Outline) => {
+                if let Some(index) = self.index_table.get(&id).cloned() {
+                    instances[index as usize].outline_colour = [0.0, 0.0, 0.0, 0.0];
+                }
+            }
+            &Remove(id, ComponentType::Flash) => {
+                if let Some(index) = self.index_table.get(&id).cloned() {
+                    instances[index as usize].flash = [0.0, 0.0, 0.0, 0.0];
                 }
             }
             _ => {}
@@ -0,0 +1,335 @@
+use gfx;
+use cgmath::Vector2;
+
+use renderer::formats::ColourFormat;
+use renderer::render_target::RenderTarget;
+use renderer::vision_buffer::VisionBuffer;
+use renderer::frame_info::{FrameInfo, FrameInfoBuffer};
+use renderer::dimensions::{Dimensions, FixedDimensions, OutputDimensions, WorldDimensions};
+use renderer::common;
+use renderer::template::{self, ShaderSource};
+use renderer::sizes;
+
+use content::TileSprite;
+use entity_store::{EntityStore, EntityChange, ComponentValue};
+use static_grid::StaticGrid;
+
+gfx_vertex_struct!( Vertex {
+    pos: [f32; 2] = "a_Pos",
+});
+
+gfx_vertex_struct!( Instance {
+    coord: [f32; 2] = "a_Coord",
+    size: [f32; 2] = "a_Size",
+    colour: [f32; 4] = "a_Colour",
+    flags: u32 = "a_Flags",
+});
+
+gfx_constant_struct!( MinimapInfo {
+    origin: [f32; 2] = "u_MinimapOrigin",
+    cell_size: [f32; 2] = "u_MinimapCellSize",
+});
+
+gfx_pipeline!( pipe {
+    frame_info: gfx::ConstantBuffer<FrameInfo> = "FrameInfo",
+    vision_table: gfx::ShaderResource<u8> = "t_VisionTable",
+    vertex: gfx::VertexBuffer<Vertex> = (),
+    instance: gfx::InstanceBuffer<Instance> = (),
+    fixed_dimensions: gfx::ConstantBuffer<FixedDimensions> = "FixedDimensions",
+    output_dimensions: gfx::ConstantBuffer<OutputDimensions> = "OutputDimensions",
+    world_dimensions: gfx::ConstantBuffer<WorldDimensions> = "WorldDimensions",
+    info: gfx::ConstantBuffer<MinimapInfo> = "MinimapInfo",
+    out_colour: gfx::BlendTarget<ColourFormat> = ("Target0", gfx::state::ColorMask::all(), gfx::preset::blend::ALPHA),
+});
+
+pub mod minimap_flags {
+    // drawn once the player has seen the cell
+    pub const HIDE_UNSEEN: u32 = 1 << 0;
+    // drawn only while the player can see the cell
+    pub const HIDE_INVISIBLE: u32 = 1 << 1;
+}
+
+// in pixels of the render target
+const MARGIN_PX: f32 = 4.0;
+const MAX_CELL_SIZE_PX: u32 = 2;
+
+// the minimap takes up at most this proportion of the target's width
+const MAX_WIDTH_RATIO: f32 = 0.4;
+
+const BACKGROUND_COLOUR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const FLOOR_COLOUR: [f32; 4] = [0.3, 0.3, 0.35, 1.0];
+const WATER_COLOUR: [f32; 4] = [0.2, 0.4, 0.8, 1.0];
+const DOOR_COLOUR: [f32; 4] = [0.7, 0.45, 0.2, 1.0];
+const WALL_COLOUR: [f32; 4] = [0.75, 0.7, 0.6, 1.0];
+const PLAYER_COLOUR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const NPC_COLOUR: [f32; 4] = [0.9, 0.2, 0.2, 1.0];
+
+// when several entities in a cell have a terrain, the later variant wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Terrain {
+    Empty,
+    Floor,
+    Water,
+    Door,
+    Wall,
+}
+
+impl Terrain {
+    fn from_sprite(sprite: TileSprite) -> Option<Self> {
+        use self::TileSprite::*;
+        match sprite {
+            OuterFloor | InnerFloor => Some(Terrain::Floor),
+            InnerWater => Some(Terrain::Water),
+            InnerDoor | InnerDoorOpening1 | InnerDoorOpening2 | InnerDoorOpening3 |
+                InnerDoorOpening4 | InnerDoorOpening5 | InnerDoorOpening6 | InnerDoorOpen |
+                OuterDoor | OuterDoorOpening1 | OuterDoorOpening2 | OuterDoorOpening3 |
+                OuterDoorOpening4 | OuterDoorOpening5 | OuterDoorOpening6 | OuterDoorOpen => Some(Terrain::Door),
+            OuterWall | InnerWall | Window => Some(Terrain::Wall),
+            _ => None,
+        }
+    }
+
+    fn colour(self) -> Option<[f32; 4]> {
+        match self {
+            Terrain::Empty => None,
+            Terrain::Floor => Some(FLOOR_COLOUR),
+            Terrain::Water => Some(WATER_COLOUR),
+            Terrain::Door => Some(DOOR_COLOUR),
+            Terrain::Wall => Some(WALL_COLOUR),
+        }
+    }
+}
+
+impl Instance {
+    fn cell(coord: Vector2<f32>, colour: [f32; 4], flags: u32) -> Self {
+        Self {
+            coord: coord.into(),
+            size: [1.0, 1.0],
+            colour,
+            flags,
+        }
+    }
+}
+
+// the terrain of each cell, kept up to date by the world state
+pub struct MinimapTerrain {
+    grid: StaticGrid<Terrain>,
+    player_position: Option<Vector2<f32>>,
+    dirty: bool,
+}
+
+impl MinimapTerrain {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            grid: StaticGrid::new_copy(width, height, Terrain::Empty),
+            player_position: None,
+            dirty: true,
+        }
+    }
+
+    pub fn update(&mut self, change: &EntityChange, entity_store: &EntityStore) {
+        // the coord and sprite of an entity can be inserted in either order
+        let (coord, sprite) = match change {
+            &EntityChange::Insert(id, ComponentValue::Coord(coord)) => {
+                (Some(coord), entity_store.sprite.get(&id).cloned())
+            }
+            &EntityChange::Insert(id, ComponentValue::Sprite(sprite)) => {
+                (entity_store.coord.get(&id).cloned(), Some(sprite))
+            }
+            _ => return,
+        };
+
+        if let (Some(coord), Some(sprite)) = (coord, sprite) {
+            if let Some(terrain) = Terrain::from_sprite(sprite) {
+                if let Some(cell) = self.grid.get_signed_mut(coord) {
+                    if terrain > *cell {
+                        *cell = terrain;
+                        self.dirty = true;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn set_player_position(&mut self, player_position: Vector2<f32>) {
+        self.player_position = Some(player_position);
+    }
+}
+
+pub struct Minimap<R: gfx::Resources> {
+    bundle: gfx::pso::bundle::Bundle<R, pipe::Data<R>>,
+    instance_upload: gfx::handle::Buffer<R, Instance>,
+    terrain: MinimapTerrain,
+    // the background and terrain instances, which only change when the terrain does
+    num_terrain_instances: usize,
+    target_width: u16,
+}
+
+impl<R: gfx::Resources> Minimap<R> {
+    pub fn new<F>(target: &RenderTarget<R>,
+                  dimensions: &Dimensions<R>,
+                  vision_buffer: &VisionBuffer<R>,
+                  frame_info_buffer: &FrameInfoBuffer<R>,
+                  shader_source: &ShaderSource,
+                  factory: &mut F) -> Self
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let pso = Self::create_pso(shader_source, factory).expect("Failed to create pipeline");
+
+        let vertex_data: Vec<Vertex> = common::QUAD_VERTICES_REFL.iter()
+            .map(|v| {
+                Vertex {
+                    pos: *v,
+                }
+            }).collect();
+
+        let (vertex_buffer, slice) =
+            factory.create_vertex_buffer_with_slice(
+                &vertex_data,
+                &common::QUAD_INDICES[..]);
+
+        let data = pipe::Data {
+            frame_info: frame_info_buffer.clone(),
+            vision_table: vision_buffer.srv.clone(),
+            vertex: vertex_buffer,
+            instance: common::create_instance_buffer(sizes::MINIMAP_MAX_NUM_INSTANCES, factory)
+                .expect("Failed to create instance buffer"),
+            fixed_dimensions: dimensions.fixed_dimensions.clone(),
+            output_dimensions: dimensions.output_dimensions.clone(),
+            world_dimensions: dimensions.world_dimensions.clone(),
+            info: factory.create_constant_buffer(1),
+            out_colour: target.rtv.clone(),
+        };
+
+        Self {
+            bundle: gfx::pso::bundle::Bundle::new(slice, pso, data),
+            instance_upload: factory.create_upload_buffer(sizes::MINIMAP_MAX_NUM_INSTANCES)
+                .expect("Failed to create upload buffer"),
+            terrain: MinimapTerrain::new(0, 0),
+            num_terrain_instances: 0,
+            target_width: target.width,
+        }
+    }
+
+    fn create_pso<F>(shader_source: &ShaderSource, factory: &mut F) -> Result<gfx::pso::PipelineState<R, pipe::Meta>, String>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let (handlebars, context) = template::make_shader_template_context(shader_source)?;
+        let vert = template::populate_shader(&handlebars, &context, shader!(shader_source, "minimap.150.hbs.vert")?.as_ref())?;
+        let frag = shader!(shader_source, "minimap.150.frag")?;
+
+        factory.create_pipeline_simple(vert.as_bytes(), frag.as_bytes(), pipe::new())
+            .map_err(|e| format!("Failed to create minimap pipeline: {:?}", e))
+    }
+
+    pub fn reload_shaders<F>(&mut self, shader_source: &ShaderSource, factory: &mut F) -> Result<(), String>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        self.bundle.pso = Self::create_pso(shader_source, factory)?;
+        Ok(())
+    }
+
+    pub fn terrain(&mut self) -> &mut MinimapTerrain {
+        &mut self.terrain
+    }
+
+    pub fn handle_resize<C>(&mut self, target: &RenderTarget<R>, encoder: &mut gfx::Encoder<R, C>)
+        where C: gfx::CommandBuffer<R>,
+    {
+        self.bundle.data.out_colour = target.rtv.clone();
+        self.target_width = target.width;
+        self.update_info(encoder);
+    }
+
+    pub fn update_world_size<C>(&mut self, width: u32, height: u32, encoder: &mut gfx::Encoder<R, C>)
+        where C: gfx::CommandBuffer<R>,
+    {
+        self.terrain = MinimapTerrain::new(width, height);
+        self.update_info(encoder);
+    }
+
+    // places the minimap in the top-right corner, shrinking it on narrow targets
+    fn update_info<C>(&self, encoder: &mut gfx::Encoder<R, C>)
+        where C: gfx::CommandBuffer<R>,
+    {
+        let world_width = self.terrain.grid.width();
+        let max_width_px = (self.target_width as f32 * MAX_WIDTH_RATIO) as u32;
+        let cell_size = if world_width * MAX_CELL_SIZE_PX <= max_width_px { MAX_CELL_SIZE_PX } else { 1 };
+        let width_px = (world_width * cell_size) as f32;
+
+        encoder.update_constant_buffer(&self.bundle.data.info, &MinimapInfo {
+            origin: [self.target_width as f32 - MARGIN_PX - width_px, MARGIN_PX],
+            cell_size: [cell_size as f32, cell_size as f32],
+        });
+    }
+
+    pub fn draw<C, F>(&mut self, entity_store: &EntityStore, encoder: &mut gfx::Encoder<R, C>, factory: &mut F)
+        where C: gfx::CommandBuffer<R>,
+              F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let num_instances = {
+            let mut writer = factory.write_mapping(&self.instance_upload)
+                .expect("Failed to map upload buffer");
+
+            if self.terrain.dirty {
+                self.num_terrain_instances = Self::draw_terrain(&self.terrain, &mut writer);
+                self.terrain.dirty = false;
+            }
+
+            Self::draw_markers(&self.terrain, entity_store, &mut writer, self.num_terrain_instances)
+        };
+
+        self.bundle.slice.instances = Some((num_instances as u32, 0));
+
+        encoder.copy_buffer(&self.instance_upload, &self.bundle.data.instance, 0, 0, num_instances)
+            .expect("Failed to copy instance buffer");
+        encoder.draw(&self.bundle.slice, &self.bundle.pso, &self.bundle.data);
+    }
+
+    fn draw_terrain(terrain: &MinimapTerrain, instances: &mut [Instance]) -> usize {
+        let grid = &terrain.grid;
+
+        // instances are drawn in order, so the background goes first
+        instances[0] = Instance {
+            coord: [0.0, 0.0],
+            size: [grid.width() as f32, grid.height() as f32],
+            colour: BACKGROUND_COLOUR,
+            flags: 0,
+        };
+
+        let mut count = 1;
+        for (coord, cell) in izip!(grid.coord_iter(), grid.iter()) {
+            if let Some(colour) = cell.colour() {
+                instances[count] = Instance::cell(coord.cast(), colour, minimap_flags::HIDE_UNSEEN);
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    fn draw_markers(terrain: &MinimapTerrain,
+                    entity_store: &EntityStore,
+                    instances: &mut [Instance],
+                    base: usize) -> usize
+    {
+        let mut count = base;
+        for id in entity_store.npc.iter() {
+            if count + 1 >= instances.len() {
+                break;
+            }
+            if let Some(position) = entity_store.position.get(id) {
+                instances[count] = Instance::cell(*position, NPC_COLOUR, minimap_flags::HIDE_INVISIBLE);
+                count += 1;
+            }
+        }
+
+        // the player is drawn last so nothing covers it
+        if let Some(player_position) = terrain.player_position {
+            instances[count] = Instance::cell(player_position, PLAYER_COLOUR, 0);
+            count += 1;
+        }
+
+        count
+    }
+}
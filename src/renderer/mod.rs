@@ -1,3 +1,5 @@
+#[macro_use]
+mod template;
 mod formats;
 mod sprite_sheet;
 mod tile_renderer;
@@ -10,10 +12,21 @@ mod render_target;
 mod dimensions;
 mod vision_buffer;
 mod sizes;
+mod shading;
 mod frame_info;
-mod template;
 mod scroll_offset;
+mod ambient_light;
+mod software;
+mod capture;
+mod zoom;
+mod minimap;
+mod description;
+mod font;
+mod hot_reload;
+mod particles;
 
 pub use self::formats::{ColourFormat, DepthFormat};
 pub use self::renderer::Renderer;
 pub use self::tile_renderer::RendererWorldState;
+pub use self::software::{SoftwareRenderer, SoftwareWorldState};
+pub use self::sizes::MAX_NUM_LIGHTS;
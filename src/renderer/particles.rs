@@ -0,0 +1,289 @@
+use std::f32::consts::PI;
+use gfx;
+use cgmath::{Vector2, InnerSpace};
+use rand::{Rng, XorShiftRng};
+
+use renderer::formats::{ColourFormat, DepthFormat};
+use renderer::render_target::RenderTarget;
+use renderer::dimensions::{Dimensions, FixedDimensions, OutputDimensions, WorldDimensions};
+use renderer::vision_buffer::VisionBuffer;
+use renderer::frame_info::{FrameInfo, FrameInfoBuffer};
+use renderer::scroll_offset::{ScrollOffset, ScrollOffsetBuffer};
+use renderer::tile_renderer::Light;
+use renderer::ambient_light::{AmbientLight, AmbientLightBuffer};
+use renderer::template::{self, ShaderSource};
+use renderer::common;
+use renderer::sizes;
+
+use content::ParticleEmitterInfo;
+use entity_store::{EntityChange, ComponentValue};
+
+gfx_vertex_struct!( Vertex {
+    pos: [f32; 2] = "a_Pos",
+});
+
+// particles move and fade in the vertex shader, so they're only written once, when they're emitted
+gfx_vertex_struct!( Particle {
+    position: [f32; 2] = "a_Position",
+    velocity: [f32; 2] = "a_Velocity",
+    colour: [f32; 3] = "a_Colour",
+    size_px: f32 = "a_SizePx",
+    gravity: f32 = "a_Gravity",
+    spawn_time_ms: [u32; 2] = "a_SpawnTimeMs_u64",
+    lifetime_ms: f32 = "a_LifetimeMs",
+});
+
+gfx_pipeline!( pipe {
+    vision_table: gfx::ShaderResource<u8> = "t_VisionTable",
+    light_table: gfx::ShaderResource<u8> = "t_LightTable",
+    light_list: gfx::ConstantBuffer<Light> = "LightList",
+    sky_table: gfx::ShaderResource<u8> = "t_SkyTable",
+    ambient_light: gfx::ConstantBuffer<AmbientLight> = "AmbientLight",
+    fixed_dimensions: gfx::ConstantBuffer<FixedDimensions> = "FixedDimensions",
+    output_dimensions: gfx::ConstantBuffer<OutputDimensions> = "OutputDimensions",
+    world_dimensions: gfx::ConstantBuffer<WorldDimensions> = "WorldDimensions",
+    scroll_offset: gfx::ConstantBuffer<ScrollOffset> = "ScrollOffset",
+    frame_info: gfx::ConstantBuffer<FrameInfo> = "FrameInfo",
+    vertex: gfx::VertexBuffer<Vertex> = (),
+    instance: gfx::InstanceBuffer<Particle> = (),
+    out_colour: gfx::BlendTarget<ColourFormat> = ("Target0", gfx::state::ColorMask::all(), gfx::preset::blend::ALPHA),
+    out_depth: gfx::DepthTarget<DepthFormat> = gfx::preset::depth::LESS_EQUAL_TEST,
+});
+
+impl Default for Particle {
+    fn default() -> Self {
+        Self {
+            position: [0.0, 0.0],
+            velocity: [0.0, 0.0],
+            colour: [0.0, 0.0, 0.0],
+            size_px: 0.0,
+            gravity: 0.0,
+            spawn_time_ms: [0, 0],
+            // never drawn
+            lifetime_ms: 0.0,
+        }
+    }
+}
+
+impl Particle {
+    // where the particle is, and how visible it is from 0 to 1, or None if it has expired
+    pub fn state(&self, total_time_ms: u64) -> Option<(Vector2<f32>, f32)> {
+        let spawn_time_ms = self.spawn_time_ms[0] as u64 | (self.spawn_time_ms[1] as u64) << 32;
+        if total_time_ms < spawn_time_ms {
+            return None;
+        }
+        let age_ms = (total_time_ms - spawn_time_ms) as f32;
+        if age_ms >= self.lifetime_ms {
+            return None;
+        }
+
+        let t = age_ms / 1000.0;
+        let position = Vector2::from(self.position) + Vector2::from(self.velocity) * t +
+            Vector2::new(0.0, 0.5 * self.gravity * t * t);
+
+        Some((position, fade(age_ms / self.lifetime_ms)))
+    }
+}
+
+// mirrors the fading in the particle vertex shader
+fn fade(age: f32) -> f32 {
+    let x = ((age - 0.5) / 0.5).max(0.0).min(1.0);
+    1.0 - x * x * (3.0 - 2.0 * x)
+}
+
+fn pick<R: Rng>(rng: &mut R, (min, max): (f32, f32)) -> f32 {
+    min + rng.next_f32() * (max - min)
+}
+
+// collects bursts as particle emitters are inserted, then writes them into a ring of particles
+// once the frame's time is known, overwriting the oldest particles when it's full
+pub struct ParticleEmitter {
+    rng: XorShiftRng,
+    pending: Vec<ParticleEmitterInfo>,
+    next_index: usize,
+    num_particles: usize,
+}
+
+impl ParticleEmitter {
+    pub fn new() -> Self {
+        Self {
+            // seeded the same every run, so the software renderer's output is reproducible
+            rng: XorShiftRng::new_unseeded(),
+            pending: Vec::new(),
+            next_index: 0,
+            num_particles: 0,
+        }
+    }
+
+    pub fn update(&mut self, change: &EntityChange) {
+        if let &EntityChange::Insert(_, ComponentValue::ParticleEmitter(info)) = change {
+            self.pending.push(info);
+        }
+    }
+
+    pub fn emit(&mut self, particles: &mut [Particle], total_time_ms: u64) {
+        let spawn_time_ms = [total_time_ms as u32, (total_time_ms >> 32) as u32];
+
+        for info in self.pending.drain(..) {
+            let burst = info.typ.burst();
+            let aimed = info.direction.magnitude2() > 0.0;
+            let base_angle = info.direction.y.atan2(info.direction.x);
+
+            for _ in 0..burst.count {
+                let angle = if aimed {
+                    base_angle + pick(&mut self.rng, (-burst.spread, burst.spread))
+                } else {
+                    pick(&mut self.rng, (-PI, PI))
+                };
+                let speed = pick(&mut self.rng, burst.speed);
+
+                particles[self.next_index] = Particle {
+                    position: info.position.into(),
+                    velocity: [angle.cos() * speed, angle.sin() * speed],
+                    colour: burst.colour,
+                    size_px: pick(&mut self.rng, burst.size_px).round(),
+                    gravity: burst.gravity,
+                    spawn_time_ms,
+                    lifetime_ms: pick(&mut self.rng, burst.lifetime_ms),
+                };
+
+                self.next_index = (self.next_index + 1) % particles.len();
+                if self.num_particles < particles.len() {
+                    self.num_particles += 1;
+                }
+            }
+        }
+    }
+
+    pub fn num_particles(&self) -> usize {
+        self.num_particles
+    }
+}
+
+pub struct ParticleRenderer<R: gfx::Resources> {
+    bundle: gfx::pso::bundle::Bundle<R, pipe::Data<R>>,
+    upload: gfx::handle::Buffer<R, Particle>,
+    emitter: ParticleEmitter,
+}
+
+impl<R: gfx::Resources> ParticleRenderer<R> {
+    pub fn new<F>(target: &RenderTarget<R>,
+                  dimensions: &Dimensions<R>,
+                  vision_buffer: &VisionBuffer<R>,
+                  light_table: &gfx::handle::ShaderResourceView<R, u8>,
+                  light_list: &gfx::handle::Buffer<R, Light>,
+                  sky_table: &gfx::handle::ShaderResourceView<R, u8>,
+                  ambient_light_buffer: &AmbientLightBuffer<R>,
+                  frame_info_buffer: &FrameInfoBuffer<R>,
+                  scroll_offset_buffer: &ScrollOffsetBuffer<R>,
+                  shader_source: &ShaderSource,
+                  factory: &mut F) -> Self
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let pso = Self::create_pso(shader_source, factory).expect("Failed to create pipeline");
+
+        let vertex_data: Vec<Vertex> = common::QUAD_VERTICES_REFL.iter()
+            .map(|v| {
+                Vertex {
+                    pos: *v,
+                }
+            }).collect();
+
+        let (vertex_buffer, mut slice) =
+            factory.create_vertex_buffer_with_slice(
+                &vertex_data,
+                &common::QUAD_INDICES[..]);
+        slice.instances = Some((0, 0));
+
+        let data = pipe::Data {
+            vision_table: vision_buffer.srv.clone(),
+            light_table: light_table.clone(),
+            light_list: light_list.clone(),
+            sky_table: sky_table.clone(),
+            ambient_light: ambient_light_buffer.clone(),
+            fixed_dimensions: dimensions.fixed_dimensions.clone(),
+            output_dimensions: dimensions.output_dimensions.clone(),
+            world_dimensions: dimensions.world_dimensions.clone(),
+            scroll_offset: scroll_offset_buffer.clone(),
+            frame_info: frame_info_buffer.clone(),
+            vertex: vertex_buffer,
+            instance: common::create_instance_buffer(sizes::MAX_NUM_PARTICLES, factory)
+                .expect("Failed to create instance buffer"),
+            out_colour: target.rtv.clone(),
+            out_depth: target.dsv.clone(),
+        };
+
+        Self {
+            bundle: gfx::pso::bundle::Bundle::new(slice, pso, data),
+            upload: factory.create_upload_buffer(sizes::MAX_NUM_PARTICLES)
+                .expect("Failed to create upload buffer"),
+            emitter: ParticleEmitter::new(),
+        }
+    }
+
+    fn create_pso<F>(shader_source: &ShaderSource, factory: &mut F) -> Result<gfx::pso::PipelineState<R, pipe::Meta>, String>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let (handlebars, context) = template::make_shader_template_context(shader_source)?;
+        let vert = template::populate_shader(&handlebars, &context, shader!(shader_source, "particles.150.hbs.vert")?.as_ref())?;
+        let frag = template::populate_shader(&handlebars, &context, shader!(shader_source, "particles.150.hbs.frag")?.as_ref())?;
+
+        factory.create_pipeline_simple(vert.as_bytes(), frag.as_bytes(), pipe::new())
+            .map_err(|e| format!("Failed to create particle pipeline: {:?}", e))
+    }
+
+    pub fn reload_shaders<F>(&mut self, shader_source: &ShaderSource, factory: &mut F) -> Result<(), String>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        self.bundle.pso = Self::create_pso(shader_source, factory)?;
+        Ok(())
+    }
+
+    pub fn handle_resize(&mut self, target: &RenderTarget<R>) {
+        self.bundle.data.out_colour = target.rtv.clone();
+        self.bundle.data.out_depth = target.dsv.clone();
+    }
+
+    // particles are tested against depth but don't write it, so they never hide each other
+    pub fn draw<C>(&self, encoder: &mut gfx::Encoder<R, C>)
+        where C: gfx::CommandBuffer<R>,
+    {
+        let num_particles = self.emitter.num_particles();
+        if num_particles == 0 {
+            return;
+        }
+        encoder.copy_buffer(&self.upload, &self.bundle.data.instance, 0, 0, num_particles)
+            .expect("Failed to copy particles");
+        encoder.draw(&self.bundle.slice, &self.bundle.pso, &self.bundle.data);
+    }
+
+    pub fn world_state<'a, F>(&'a mut self, factory: &mut F) -> ParticleWorldState<'a, R>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let writer = factory.write_mapping(&self.upload)
+            .expect("Failed to map upload buffer");
+
+        ParticleWorldState {
+            writer,
+            emitter: &mut self.emitter,
+            slice: &mut self.bundle.slice,
+        }
+    }
+}
+
+pub struct ParticleWorldState<'a, R: gfx::Resources> {
+    writer: gfx::mapping::Writer<'a, R, Particle>,
+    emitter: &'a mut ParticleEmitter,
+    slice: &'a mut gfx::Slice<R>,
+}
+
+impl<'a, R: gfx::Resources> ParticleWorldState<'a, R> {
+    pub fn update(&mut self, change: &EntityChange) {
+        self.emitter.update(change);
+    }
+
+    pub fn finalise(mut self, total_time_ms: u64) {
+        self.emitter.emit(&mut self.writer, total_time_ms);
+        self.slice.instances = Some((self.emitter.num_particles() as u32, 0));
+    }
+}
@@ -1,11 +1,39 @@
 use gfx;
+use gfx::format::{Formatted, ChannelTyped};
+use gfx::memory::Usage;
 use renderer::formats::{ColourFormat, DepthFormat};
+use renderer::zoom::Zoom;
 use res::input_sprite;
 
 const NUM_ROWS: u16 = 15;
 const HEIGHT_PX: u16 = NUM_ROWS * input_sprite::HEIGHT_PX as u16;
 
+type ColourSurface = <ColourFormat as Formatted>::Surface;
+type ColourChannel = <ColourFormat as Formatted>::Channel;
+
+// the scale factor at which the target is a fixed number of rows high
+pub fn fit_factor(scaled_window_dimensions: (u16, u16)) -> f32 {
+    scaled_window_dimensions.1 as f32 / HEIGHT_PX as f32
+}
+
+// the target has the window's aspect ratio, rounded up to cover the whole window
+pub fn target_dimensions(scaled_window_dimensions: (u16, u16), zoom: Zoom) -> (u16, u16) {
+    match zoom {
+        Zoom::Fit => {
+            let width = ((scaled_window_dimensions.0 as u32 * HEIGHT_PX as u32) /
+                scaled_window_dimensions.1 as u32) as u16;
+            (width, HEIGHT_PX)
+        }
+        Zoom::Factor(factor) => {
+            let width = (scaled_window_dimensions.0 as f32 / factor).ceil().max(1.0) as u16;
+            let height = (scaled_window_dimensions.1 as f32 / factor).ceil().max(1.0) as u16;
+            (width, height)
+        }
+    }
+}
+
 pub struct RenderTarget<R: gfx::Resources> {
+    pub texture: gfx::handle::Texture<R, ColourSurface>,
     pub rtv: gfx::handle::RenderTargetView<R, ColourFormat>,
     pub dsv: gfx::handle::DepthStencilView<R, DepthFormat>,
     pub srv: gfx::handle::ShaderResourceView<R, [f32; 4]>,
@@ -15,24 +43,35 @@ pub struct RenderTarget<R: gfx::Resources> {
 }
 
 impl<R: gfx::Resources> RenderTarget<R> {
-    pub fn new<F>(scaled_window_dimensions: (u16, u16), factory: &mut F) -> Self
+    pub fn new<F>(scaled_window_dimensions: (u16, u16), zoom: Zoom, factory: &mut F) -> Self
         where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
     {
-        let width = ((scaled_window_dimensions.0 as u32 * HEIGHT_PX as u32) /
-            scaled_window_dimensions.1 as u32) as u16;
+        let (width, height) = target_dimensions(scaled_window_dimensions, zoom);
+        let sprite_height = input_sprite::HEIGHT_PX as u16;
+        let num_rows = (height + sprite_height - 1) / sprite_height;
 
-        let (_, srv, rtv) = factory.create_render_target(width, HEIGHT_PX)
+        // created by hand rather than with create_render_target so it can be copied out of for screenshots
+        let kind = gfx::texture::Kind::D2(width, height, gfx::texture::AaMode::Single);
+        let channel_type = ColourChannel::get_channel_type();
+        let texture = factory.create_texture::<ColourSurface>(
+            kind, 1, gfx::SHADER_RESOURCE | gfx::RENDER_TARGET | gfx::TRANSFER_SRC, Usage::Data, Some(channel_type))
             .expect("Failed to create render target");
-        let (_, _, dsv) = factory.create_depth_stencil(width, HEIGHT_PX)
+        let srv = factory.view_texture_as_shader_resource::<ColourFormat>(&texture, (0, 0), gfx::format::Swizzle::new())
+            .expect("Failed to view render target as shader resource");
+        let rtv = factory.view_texture_as_render_target(&texture, 0, None)
+            .expect("Failed to view render target as render target");
+
+        let (_, _, dsv) = factory.create_depth_stencil(width, height)
             .expect("Failed to create depth stencil");
 
         Self {
+            texture,
             rtv,
             dsv,
             srv,
             width,
-            height: HEIGHT_PX,
-            num_rows: NUM_ROWS,
+            height,
+            num_rows,
         }
     }
 }
@@ -1,19 +1,27 @@
 use gfx;
-use image;
+use image::{self, RgbaImage};
+use cgmath::Vector2;
 
 use renderer::tile_renderer::{TileRenderer, RendererWorldState};
 use renderer::scale::Scale;
 use renderer::field_ui::FieldUi;
+use renderer::minimap::Minimap;
+use renderer::description::Description;
+use renderer::template::ShaderSource;
+use renderer::hot_reload::HotReload;
+use renderer::sprite_sheet::{SpriteSheetTexture, TileSpriteTable, FieldUiSpriteTable};
 use renderer::formats::ColourFormat;
 use renderer::sprite_sheet;
 use renderer::render_target::RenderTarget;
+use renderer::zoom::Zoom;
 use renderer::dimensions::Dimensions;
 use renderer::vision_buffer::VisionBuffer;
 use renderer::frame_info::FrameInfo;
 use renderer::sizes;
 use renderer::scroll_offset;
+use renderer::capture;
 
-use res::{input_sprite, paths, files};
+use res::{paths, files};
 
 use entity_store::EntityStore;
 
@@ -21,12 +29,36 @@ pub struct Renderer<R: gfx::Resources> {
     target: RenderTarget<R>,
     tile_renderer: TileRenderer<R>,
     field_ui: FieldUi<R>,
+    minimap: Minimap<R>,
+    description: Description<R>,
     scale: Scale<R>,
     dimensions: Dimensions<R>,
+    window_dimensions: (u16, u16),
+    zoom: Zoom,
+    hot_reload: Option<HotReload>,
+}
+
+fn load_sprite_sheet<R, C, F, D>(factory: &mut F, encoder: &mut gfx::Encoder<R, C>, device: &mut D)
+    -> Result<(SpriteSheetTexture<R>, TileSpriteTable, FieldUiSpriteTable), String>
+    where R: gfx::Resources,
+          C: gfx::CommandBuffer<R>,
+          F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+          D: gfx::traits::Device<Resources=R, CommandBuffer=C>,
+{
+    let sprite_sheet_path = paths::res_path(files::SPRITE_SHEET);
+    let image = image::open(&sprite_sheet_path)
+        .map_err(|e| format!("Failed to open sprite sheet (looked for {}): {}", sprite_sheet_path.display(), e))?
+        .to_rgba();
+    let normal_map = sprite_sheet::load_normal_map(&paths::res_path(files::SPRITE_NORMAL_MAP))?;
+
+    sprite_sheet::create(image, normal_map, &paths::res_path(files::SPRITE_MANIFEST), factory, encoder, device)
 }
 
 impl<R: gfx::Resources> Renderer<R> {
+    // with hot reloading, shaders are read from the source tree and rebuilt when they change,
+    // as is the sprite sheet
     pub fn new<C, F, D>(rtv: &gfx::handle::RenderTargetView<R, ColourFormat>,
+                        hot_reload: bool,
                         factory: &mut F,
                         encoder: &mut gfx::Encoder<R, C>,
                         device: &mut D) -> Self
@@ -34,18 +66,18 @@ impl<R: gfx::Resources> Renderer<R> {
               F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
               D: gfx::traits::Device<Resources=R, CommandBuffer=C>,
     {
-        let sprite_sheet_path = paths::res_path(files::SPRITE_SHEET);
-        let image = image::open(&sprite_sheet_path)
-            .expect(format!("Failed to open sprite sheet (looked for {})",
-                            sprite_sheet_path.display()).as_ref())
-            .to_rgba();
-        let (sprite_sheet, tile_table, field_ui_table) =
-            sprite_sheet::create(image, input_sprite::input_sprites(),
-                                 factory, encoder, device);
+        let (sprite_sheet, tile_table, field_ui_table) = load_sprite_sheet(factory, encoder, device)
+            .unwrap_or_else(|e| panic!("{}", e));
+
+        let hot_reload = if hot_reload { Some(HotReload::new()) } else { None };
+        let shader_source = hot_reload.as_ref().map_or_else(ShaderSource::embedded, |hot_reload| {
+            hot_reload.shader_source().clone()
+        });
 
         let (width, height, ..) = rtv.get_dimensions();
 
-        let target = RenderTarget::new((width, height), factory);
+        let zoom = Zoom::Fit;
+        let target = RenderTarget::new((width, height), zoom, factory);
 
         let dimensions = Dimensions::new(factory);
         dimensions.update_fixed_dimensions(&sprite_sheet, encoder);
@@ -62,6 +94,7 @@ impl<R: gfx::Resources> Renderer<R> {
                                               &vision_buffer,
                                               &frame_info_buffer,
                                               &scroll_offset_buffer,
+                                              &shader_source,
                                               factory);
 
         let field_ui = FieldUi::new(&sprite_sheet,
@@ -71,16 +104,32 @@ impl<R: gfx::Resources> Renderer<R> {
                                     &vision_buffer,
                                     &frame_info_buffer,
                                     &scroll_offset_buffer,
+                                    &shader_source,
                                     factory);
 
-        let scale = Scale::new(rtv.clone(), target.srv.clone(), target.width, target.height, factory, encoder);
+        let minimap = Minimap::new(&target,
+                                   &dimensions,
+                                   &vision_buffer,
+                                   &frame_info_buffer,
+                                   &shader_source,
+                                   factory);
+
+        let description = Description::new(&target, &dimensions, &shader_source, factory);
+
+        let scale = Scale::new(rtv.clone(), target.srv.clone(), target.width, target.height,
+                               &shader_source, factory, encoder);
 
         Renderer {
             target,
             tile_renderer,
             field_ui,
+            minimap,
+            description,
             scale,
             dimensions,
+            window_dimensions: (width, height),
+            zoom,
+            hot_reload,
         }
     }
 
@@ -98,13 +147,56 @@ impl<R: gfx::Resources> Renderer<R> {
     {
         self.tile_renderer.draw(encoder);
         self.field_ui.draw(entity_store, encoder, factory);
+        self.minimap.draw(entity_store, encoder, factory);
+        self.description.draw(encoder, factory);
         self.scale.draw(encoder);
     }
 
+    // called once per frame; reports problems with changed files rather than crashing,
+    // leaving whatever was there before in place
+    pub fn poll_hot_reload<C, F, D>(&mut self, factory: &mut F, encoder: &mut gfx::Encoder<R, C>, device: &mut D)
+        where C: gfx::CommandBuffer<R>,
+              F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+              D: gfx::traits::Device<Resources=R, CommandBuffer=C>,
+    {
+        let (changes, shader_source) = if let Some(ref mut hot_reload) = self.hot_reload {
+            (hot_reload.poll(), hot_reload.shader_source().clone())
+        } else {
+            return;
+        };
+
+        if changes.shaders {
+            let results = vec![
+                self.tile_renderer.reload_shaders(&shader_source, factory),
+                self.field_ui.reload_shaders(&shader_source, factory),
+                self.minimap.reload_shaders(&shader_source, factory),
+                self.description.reload_shaders(&shader_source, factory),
+                self.scale.reload_shaders(&shader_source, factory),
+            ];
+            for result in results {
+                if let Err(e) = result {
+                    eprintln!("Failed to reload shaders: {}", e);
+                }
+            }
+        }
+
+        if changes.sprite_sheet {
+            match load_sprite_sheet(factory, encoder, device) {
+                Ok((sprite_sheet, _, _)) => {
+                    // the manifest isn't watched, so the layout is the same and instances needn't change
+                    self.tile_renderer.set_sprite_sheet(&sprite_sheet);
+                    self.field_ui.set_sprite_sheet(&sprite_sheet);
+                    self.dimensions.update_fixed_dimensions(&sprite_sheet, encoder);
+                }
+                Err(e) => eprintln!("Failed to reload sprite sheet: {}", e),
+            }
+        }
+    }
+
     pub fn world_state<F>(&mut self, factory: &mut F) -> RendererWorldState<R>
         where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
     {
-        self.tile_renderer.world_state(&self.target, factory)
+        self.tile_renderer.world_state(&self.target, self.minimap.terrain(), factory)
     }
 
     pub fn handle_resize<C, F>(&mut self, rtv: &gfx::handle::RenderTargetView<R, ColourFormat>,
@@ -113,18 +205,76 @@ impl<R: gfx::Resources> Renderer<R> {
               F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
     {
         let (width, height, ..) = rtv.get_dimensions();
-        self.target = RenderTarget::new((width, height), factory);
+        self.window_dimensions = (width, height);
+        self.target = RenderTarget::new((width, height), self.zoom, factory);
         self.dimensions.update_output_dimensions(&self.target, encoder);
         self.tile_renderer.handle_resize(&self.target, encoder);
         self.field_ui.handle_resize(&self.target, encoder);
+        self.minimap.handle_resize(&self.target, encoder);
+        self.description.handle_resize(&self.target);
         self.scale.handle_resize(rtv.clone(), self.target.srv.clone(), self.target.width, self.target.height, encoder, factory);
     }
 
+    pub fn zoom_in<C, F>(&mut self, rtv: &gfx::handle::RenderTargetView<R, ColourFormat>,
+                         encoder: &mut gfx::Encoder<R, C>, factory: &mut F)
+        where C: gfx::CommandBuffer<R>,
+              F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let zoom = self.zoom.zoom_in(self.window_dimensions);
+        self.set_zoom(zoom, rtv, encoder, factory);
+    }
+
+    pub fn zoom_out<C, F>(&mut self, rtv: &gfx::handle::RenderTargetView<R, ColourFormat>,
+                          encoder: &mut gfx::Encoder<R, C>, factory: &mut F)
+        where C: gfx::CommandBuffer<R>,
+              F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let zoom = self.zoom.zoom_out(self.window_dimensions);
+        self.set_zoom(zoom, rtv, encoder, factory);
+    }
+
+    fn set_zoom<C, F>(&mut self, zoom: Zoom, rtv: &gfx::handle::RenderTargetView<R, ColourFormat>,
+                      encoder: &mut gfx::Encoder<R, C>, factory: &mut F)
+        where C: gfx::CommandBuffer<R>,
+              F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        if zoom != self.zoom {
+            self.zoom = zoom;
+            // the target is rebuilt at the new size, exactly as if the window had changed size
+            self.handle_resize(rtv, encoder, factory);
+        }
+    }
+
+    // the scale pass draws straight to the window, which can't be read back, so this captures the unscaled frame
+    pub fn capture<C, F, D>(&self, encoder: &mut gfx::Encoder<R, C>, factory: &mut F, device: &mut D) -> RgbaImage
+        where C: gfx::CommandBuffer<R>,
+              F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+              D: gfx::traits::Device<Resources=R, CommandBuffer=C>,
+    {
+        capture::read_target(&self.target, encoder, factory, device)
+    }
+
+    pub fn set_cursor(&mut self, cursor: Option<Vector2<i32>>) {
+        self.field_ui.set_cursor(cursor);
+    }
+
+    pub fn set_description(&mut self, description: Option<&str>) {
+        self.description.set_text(description);
+    }
+
+    pub fn screen_to_world(&self, window_px: Vector2<i32>) -> Vector2<i32> {
+        // the render target is scaled uniformly to fill the window
+        let scale = self.target.height as f32 / self.window_dimensions.1 as f32;
+        let target_px = window_px.cast::<f32>() * scale;
+        self.tile_renderer.target_to_world(&self.target, target_px)
+    }
+
     pub fn update_world_size<C>(&mut self, width: u32, height: u32,
                                 encoder: &mut gfx::Encoder<R, C>)
         where C: gfx::CommandBuffer<R>,
     {
         self.dimensions.update_world_dimensions((width, height), encoder);
         self.tile_renderer.update_world_size(width, height);
+        self.minimap.update_world_size(width, height, encoder);
     }
 }
@@ -2,6 +2,7 @@ use gfx;
 
 use renderer::formats::ColourFormat;
 use renderer::common;
+use renderer::template::ShaderSource;
 
 gfx_vertex_struct!( Vertex {
     pos: [f32; 2] = "a_Pos",
@@ -32,15 +33,13 @@ impl<R: gfx::Resources> Scale<R> {
                      in_srv: gfx::handle::ShaderResourceView<R, [f32; 4]>,
                      srv_width: u16,
                      srv_height: u16,
+                     shader_source: &ShaderSource,
                      factory: &mut F,
                      encoder: &mut gfx::Encoder<R, C>) -> Self
         where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
               C: gfx::CommandBuffer<R>,
     {
-        let pso = factory.create_pipeline_simple(
-            include_bytes!("shaders/scale.150.vert"),
-            include_bytes!("shaders/scale.150.frag"),
-            pipe::new()).expect("Failed to create pipeline");
+        let pso = Self::create_pso(shader_source, factory).expect("Failed to create pipeline");
 
         let vertex_data: Vec<Vertex> = izip!(&common::QUAD_VERTICES, &common::QUAD_TEX_COORDS_UPSIDE_DOWN)
             .map(|(v, t)| {
@@ -75,25 +74,44 @@ impl<R: gfx::Resources> Scale<R> {
         ret
     }
 
+    fn create_pso<F>(shader_source: &ShaderSource, factory: &mut F) -> Result<gfx::pso::PipelineState<R, pipe::Meta>, String>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let vert = shader!(shader_source, "scale.150.vert")?;
+        let frag = shader!(shader_source, "scale.150.frag")?;
+
+        factory.create_pipeline_simple(vert.as_bytes(), frag.as_bytes(), pipe::new())
+            .map_err(|e| format!("Failed to create scale pipeline: {:?}", e))
+    }
+
+    pub fn reload_shaders<F>(&mut self, shader_source: &ShaderSource, factory: &mut F) -> Result<(), String>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        self.bundle.pso = Self::create_pso(shader_source, factory)?;
+        Ok(())
+    }
+
     fn init<C>(&self, encoder: &mut gfx::Encoder<R, C>)
         where C: gfx::CommandBuffer<R>,
     {
         let (out_width, ..) = self.bundle.data.out_colour.get_dimensions();
 
-        // minimum integer scale factor to make the texture size >= the screen size
-        let upscale = (out_width / self.in_width) + if out_width % self.in_width == 0 { 0 } else { 1 };
+        // Number of screen pixels per texel. This needn't be an integer when zoomed,
+        // and the target is never larger than the screen, so it's at least 1.
+        let upscale = (out_width as f32 / self.in_width as f32).max(1.0);
 
         // Distance in texels along an axis from the centre of a texel to the point
         // at which interpolation begins.
         // (0.5 / upscale) is the width of the interpolation strip around the inside
-        // of a texel. Subtract it from 0.5 to get the distance from the centre to
-        // the inner edge of the interpolation strip.
-        let interpolate_threshold_from_centre = 0.5 - 0.5 / upscale as f32;
+        // of a texel - half a screen pixel. Subtract it from 0.5 to get the distance
+        // from the centre to the inner edge of the interpolation strip.
+        let interpolate_threshold_from_centre = 0.5 - 0.5 / upscale;
 
         // Width of interpolation strips in texels. For a given pair of adjacent
-        // texels, there is a (0.5 / upscale) texel strip along the boundary inside
-        // which interpolation will occur.
-        let interpolate_strip_width = 1.0 / upscale as f32;
+        // texels, there is a (1 / upscale) texel strip along the boundary inside
+        // which interpolation will occur, so at most one screen pixel per texel
+        // boundary is blended, no matter how far from an integer the scale is.
+        let interpolate_strip_width = 1.0 / upscale;
 
         let info = Info {
             tex_size: [self.in_width as f32, self.in_height as f32],
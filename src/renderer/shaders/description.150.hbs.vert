@@ -0,0 +1,18 @@
+#version 150 core
+
+{{INCLUDE_DIMENSIONS}}
+
+in vec2 a_Pos;
+out vec4 v_Colour;
+
+in vec2 a_Coord;
+in vec2 a_Size;
+in vec4 a_Colour;
+
+void main() {
+    v_Colour = a_Colour;
+
+    vec2 out_pix = a_Coord + a_Pos * a_Size;
+    vec2 out_scaled = out_pix / u_OutputSize;
+    gl_Position = vec4(out_scaled.x * 2.0 - 1.0, 1.0 - out_scaled.y * 2.0, 0.0, 1.0);
+}
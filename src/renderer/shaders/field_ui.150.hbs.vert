@@ -15,14 +15,19 @@ in vec2 a_Position;
 in vec2 a_PixSize;
 in vec2 a_PixOffset;
 in float a_Depth;
+in uint a_Flags;
+
+const uint FLAGS_IGNORE_VISION = {{FIELD_UI_FLAGS_IGNORE_VISION}}u;
 
 void main() {
-    uint cell_index = get_cell_index(a_Position);
-    int vision_base = int(cell_index * TBO_VISION_ENTRY_SIZE);
-    uvec2 vision_timestamp = get_vision_timestamp(vision_base, t_VisionTable);
-    if (!timestamp_is_visible(vision_timestamp)) {
-        gl_Position = vec4(0.0, 0.0, -1.0, 0.0);
-        return;
+    if ((a_Flags & FLAGS_IGNORE_VISION) == 0u) {
+        uint cell_index = get_cell_index(a_Position);
+        int vision_base = int(cell_index * TBO_VISION_ENTRY_SIZE);
+        uvec2 vision_timestamp = get_vision_timestamp(vision_base, t_VisionTable);
+        if (!timestamp_is_visible(vision_timestamp)) {
+            gl_Position = vec4(0.0, 0.0, -1.0, 0.0);
+            return;
+        }
     }
 
     v_TexCoord = get_tex_coord_inverted(a_SpriteSheetPixCoord, a_Pos, a_PixSize);
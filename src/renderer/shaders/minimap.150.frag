@@ -0,0 +1,8 @@
+#version 150 core
+
+in vec4 v_Colour;
+out vec4 Target0;
+
+void main() {
+    Target0 = v_Colour;
+}
@@ -0,0 +1,41 @@
+#version 150 core
+
+{{INCLUDE_VISION}}
+{{INCLUDE_DIMENSIONS}}
+
+uniform samplerBuffer t_VisionTable;
+
+uniform MinimapInfo {
+    vec2 u_MinimapOrigin;
+    vec2 u_MinimapCellSize;
+};
+
+in vec2 a_Pos;
+out vec4 v_Colour;
+
+in vec2 a_Coord;
+in vec2 a_Size;
+in vec4 a_Colour;
+in uint a_Flags;
+
+const uint FLAGS_HIDE_UNSEEN = {{MINIMAP_FLAGS_HIDE_UNSEEN}}u;
+const uint FLAGS_HIDE_INVISIBLE = {{MINIMAP_FLAGS_HIDE_INVISIBLE}}u;
+
+void main() {
+    if ((a_Flags & (FLAGS_HIDE_UNSEEN | FLAGS_HIDE_INVISIBLE)) != 0u) {
+        uint cell_index = get_cell_index(a_Coord);
+        int vision_base = int(cell_index * TBO_VISION_ENTRY_SIZE);
+        uvec2 vision_timestamp = get_vision_timestamp(vision_base, t_VisionTable);
+        if (((a_Flags & FLAGS_HIDE_UNSEEN) != 0u && !timestamp_is_seen(vision_timestamp)) ||
+            ((a_Flags & FLAGS_HIDE_INVISIBLE) != 0u && !timestamp_is_visible(vision_timestamp))) {
+            gl_Position = vec4(0.0, 0.0, -1.0, 0.0);
+            return;
+        }
+    }
+
+    v_Colour = a_Colour;
+
+    vec2 out_pix = u_MinimapOrigin + (a_Coord + a_Pos * a_Size) * u_MinimapCellSize;
+    vec2 out_scaled = out_pix / u_OutputSize;
+    gl_Position = vec4(out_scaled.x * 2.0 - 1.0, 1.0 - out_scaled.y * 2.0, 0.0, 1.0);
+}
@@ -0,0 +1,16 @@
+#version 150 core
+
+uniform sampler2D t_Texture;
+uniform sampler2D t_NormalMap;
+
+in vec2 v_TexCoord;
+
+out vec4 Target0;
+
+void main() {
+    // the sprite sheet decides which pixels belong to sprites, so the normal map's alpha doesn't matter
+    if (texture(t_Texture, v_TexCoord).a < 0.001) {
+        discard;
+    }
+    Target0 = vec4(texture(t_NormalMap, v_TexCoord).rgb, 1.0);
+}
@@ -0,0 +1,44 @@
+#version 150 core
+
+{{INCLUDE_VISION}}
+{{INCLUDE_AMBIENT_LIGHT}}
+
+struct Light {
+    vec4 colour;
+    vec4 position;
+};
+
+uniform LightList {
+    Light u_Lights[MAX_NUM_LIGHTS];
+};
+
+uniform samplerBuffer t_LightTable;
+uniform samplerBuffer t_SkyTable;
+
+in vec3 v_Colour;
+in float v_Alpha;
+flat in vec2 v_ParticlePosition;
+flat in uint v_CellIndex;
+
+out vec4 Target0;
+
+// particles are lit as though they face upwards, like a flat sprite
+vec3 diffuse_light(Light light, vec3 light_filter) {
+    vec3 direction = normalize(light.position.xyz - vec3(v_ParticlePosition, 0));
+    return v_Colour * light.colour.rgb * light_filter * light.colour.a * max(direction.z, 0.0);
+}
+
+void main() {
+    vec3 diffuse_total = vec3(0);
+    for (uint i = 0u; i < u_NumLights; i++) {
+        int base = int(i * TBO_VISION_BUFFER_SIZE + v_CellIndex * TBO_VISION_ENTRY_SIZE);
+        if (timestamp_is_visible(get_vision_timestamp(base, t_LightTable)) &&
+                get_vision_bitmap(base, t_LightTable) != 0u) {
+            diffuse_total += diffuse_light(u_Lights[i], get_vision_filter(base, t_LightTable));
+        }
+    }
+
+    vec3 ambient_total = v_Colour * get_ambient_light(v_CellIndex, t_SkyTable);
+
+    Target0 = vec4(ambient_total + diffuse_total, v_Alpha);
+}
@@ -0,0 +1,63 @@
+#version 150 core
+
+{{INCLUDE_VISION}}
+{{INCLUDE_DIMENSIONS}}
+{{INCLUDE_SCROLL_OFFSET}}
+{{INCLUDE_POSITIONS}}
+
+uniform samplerBuffer t_VisionTable;
+
+in vec2 a_Pos;
+
+in vec2 a_Position;
+in vec2 a_Velocity;
+in vec3 a_Colour;
+in float a_SizePx;
+in float a_Gravity;
+in uvec2 a_SpawnTimeMs_u64;
+in float a_LifetimeMs;
+
+out vec3 v_Colour;
+out float v_Alpha;
+flat out vec2 v_ParticlePosition;
+flat out uint v_CellIndex;
+
+float u64_uvec2_to_float(uvec2 u) {
+    const float MAXUINT_FLOAT = 4294967296.0;
+    return float(u[1]) * MAXUINT_FLOAT + float(u[0]);
+}
+
+void main() {
+    float age_ms = u64_uvec2_to_float(u_TotalTimeMs_u64) - u64_uvec2_to_float(a_SpawnTimeMs_u64);
+    if (age_ms < 0.0 || age_ms >= a_LifetimeMs) {
+        gl_Position = vec4(0.0, 0.0, -1.0, 0.0);
+        return;
+    }
+
+    float t = age_ms / 1000.0;
+    vec2 position = a_Position + a_Velocity * t + vec2(0.0, 0.5 * a_Gravity * t * t);
+
+    if (position.x < 0.0 || position.y < 0.0 || position.x >= u_WorldSize.x || position.y >= u_WorldSize.y) {
+        gl_Position = vec4(0.0, 0.0, -1.0, 0.0);
+        return;
+    }
+
+    // positions of particles are points, rather than the top-left corners of cells
+    v_CellIndex = get_cell_index(position - vec2(0.5));
+    int vision_base = int(v_CellIndex * TBO_VISION_ENTRY_SIZE);
+    if (!timestamp_is_visible(get_vision_timestamp(vision_base, t_VisionTable))) {
+        gl_Position = vec4(0.0, 0.0, -1.0, 0.0);
+        return;
+    }
+
+    v_Colour = a_Colour;
+    v_Alpha = 1.0 - smoothstep(0.5, 1.0, age_ms / a_LifetimeMs);
+    v_ParticlePosition = position;
+
+    // depth is from where the particle was emitted, so it doesn't move behind things as it falls
+    float depth = 1.0 - a_Position.y / u_WorldSize.y;
+
+    vec2 size = vec2(a_SizePx);
+    vec2 dst = get_output_vertex(position, size * 0.5, size, a_Pos);
+    gl_Position = vec4(dst, depth, 1.0);
+}
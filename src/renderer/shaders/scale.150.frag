@@ -18,6 +18,17 @@ vec4 sample_texture(vec2 texel_coord) {
     return texture(t_Texture, texel_coord / u_TexSize);
 }
 
+// Weight of the neighbouring texel along one axis. This is 0 outside the
+// interpolation strip, rising to 0.5 at the boundary between the texels, so
+// the colour is continuous across the boundary.
+float neighbour_weight(float from_texel_centre) {
+    float into_strip = abs(from_texel_centre) - u_InterpolateThresholdFromCentre;
+    if (into_strip > INTERPOLATE_THRESHOLD_EPSILON) {
+        return into_strip / u_InterpolateStripWidth;
+    }
+    return 0.0;
+}
+
 void main() {
     // Centre of texel containing sampling point.
     vec2 texel_centre = floor(v_Texel) + vec2(0.5);
@@ -26,51 +37,30 @@ void main() {
     // the sampling point itself.
     vec2 from_texel_centre = fract(v_Texel) - vec2(0.5);
 
-    // Colour of texel containing sampling point. Note that this
-    // assumes the sampler uses nearest-neighbour interpolation.
-    vec4 main_col = sample_texture(texel_centre);
+    // Direction of the nearest neighbouring texel along each axis.
+    vec2 towards_neighbour = vec2(from_texel_centre.x < 0.0 ? -1.0 : 1.0,
+                                  from_texel_centre.y < 0.0 ? -1.0 : 1.0);
 
-    vec4 x_col = main_col;
-    if ((from_texel_centre.x - u_InterpolateThresholdFromCentre) > INTERPOLATE_THRESHOLD_EPSILON) {
-        // sample within interpolation strip on right side of texel
-        vec2 next_texel_coord = texel_centre + vec2(1.0, 0.0);
-        if (next_texel_coord.x <= u_TexSize.x) {
-            vec4 next_col = sample_texture(next_texel_coord);
-            float weight = (from_texel_centre.x - u_InterpolateThresholdFromCentre)
-                / u_InterpolateStripWidth;
-            x_col = weight * main_col + (1.0 - weight) * next_col;
-        }
-    } else if ((from_texel_centre.x + u_InterpolateThresholdFromCentre) < -INTERPOLATE_THRESHOLD_EPSILON) {
-        // sample within interpolation strip on left side of texel
-        vec2 next_texel_coord = texel_centre - vec2(1.0, 0.0);
-        if (next_texel_coord.x >= 0.0) {
-            vec4 next_col = sample_texture(next_texel_coord);
-            float weight = -(from_texel_centre.x + u_InterpolateThresholdFromCentre)
-                / u_InterpolateStripWidth;
-            x_col = weight * main_col + (1.0 - weight) * next_col;
-        }
-    }
+    vec2 weight = vec2(neighbour_weight(from_texel_centre.x),
+                       neighbour_weight(from_texel_centre.y));
 
-    vec4 y_col = main_col;
-    if ((from_texel_centre.y - u_InterpolateThresholdFromCentre) > INTERPOLATE_THRESHOLD_EPSILON) {
-        // sample within interpolation strip on bottom side of texel
-        vec2 next_texel_coord = texel_centre + vec2(0.0, 1.0);
-        if (next_texel_coord.y <= u_TexSize.y) {
-            vec4 next_col = sample_texture(next_texel_coord);
-            float weight = (from_texel_centre.y - u_InterpolateThresholdFromCentre)
-                / u_InterpolateStripWidth;
-            y_col = weight * main_col + (1.0 - weight) * next_col;
-        }
-    } else if ((from_texel_centre.y + u_InterpolateThresholdFromCentre) < -INTERPOLATE_THRESHOLD_EPSILON) {
-        // sample within interpolation strip on top side of texel
-        vec2 next_texel_coord = texel_centre - vec2(0.0, 1.0);
-        if (next_texel_coord.y >= 0.0) {
-            vec4 next_col = sample_texture(next_texel_coord);
-            float weight = -(from_texel_centre.y + u_InterpolateThresholdFromCentre)
-                / u_InterpolateStripWidth;
-            y_col = weight * main_col + (1.0 - weight) * next_col;
-        }
+    // don't blend with texels beyond the edge of the texture
+    vec2 neighbour_centre = texel_centre + towards_neighbour;
+    if (neighbour_centre.x < 0.0 || neighbour_centre.x > u_TexSize.x) {
+        weight.x = 0.0;
+    }
+    if (neighbour_centre.y < 0.0 || neighbour_centre.y > u_TexSize.y) {
+        weight.y = 0.0;
     }
 
-    Target0 = (x_col + y_col) / 2.0;
+    // Colours of the texel containing the sampling point and its neighbours.
+    // Note that this assumes the sampler uses nearest-neighbour interpolation.
+    vec4 main_col = sample_texture(texel_centre);
+    vec4 x_col = sample_texture(vec2(neighbour_centre.x, texel_centre.y));
+    vec4 y_col = sample_texture(vec2(texel_centre.x, neighbour_centre.y));
+    vec4 xy_col = sample_texture(neighbour_centre);
+
+    // Interpolate along each axis. Away from texel boundaries both weights
+    // are 0 and the texel's own colour comes through unchanged.
+    Target0 = mix(mix(main_col, x_col, weight.x), mix(y_col, xy_col, weight.x), weight.y);
 }
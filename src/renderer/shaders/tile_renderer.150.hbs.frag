@@ -1,6 +1,8 @@
 #version 150 core
 
 {{INCLUDE_VISION}}
+{{INCLUDE_DIMENSIONS}}
+{{INCLUDE_AMBIENT_LIGHT}}
 
 struct Light {
     vec4 colour;
@@ -13,60 +15,228 @@ uniform LightList {
 
 uniform samplerBuffer t_LightTable;
 uniform samplerBuffer t_VisionTable;
+uniform samplerBuffer t_SkyTable;
 
 uniform sampler2D t_Texture;
+uniform sampler2D t_NormalMap;
 
 in vec2 v_FragPosition;
+in vec2 v_CellPosition;
+flat in float v_Raise;
 in vec2 v_TexCoord;
 in float v_ColourMult;
 flat in uint v_CellIndex;
+flat in uint v_SpriteEffect;
+flat in vec4 v_SpriteEffectArgs;
+flat in vec2 v_TexCoordMin;
+flat in vec2 v_TexCoordMax;
+flat in float v_Emissive;
+flat in vec4 v_OutlineColour;
+flat in vec4 v_Flash;
 
 out vec4 Target0;
 
+const uint DIRECTION_NORTH = {{DIRECTION_NORTH}}u;
+const uint DIRECTION_EAST = {{DIRECTION_EAST}}u;
+const uint DIRECTION_SOUTH = {{DIRECTION_SOUTH}}u;
+const uint DIRECTION_WEST = {{DIRECTION_WEST}}u;
+const uint DIRECTION_NORTH_EAST = {{DIRECTION_NORTH_EAST}}u;
+const uint DIRECTION_SOUTH_EAST = {{DIRECTION_SOUTH_EAST}}u;
+const uint DIRECTION_SOUTH_WEST = {{DIRECTION_SOUTH_WEST}}u;
+const uint DIRECTION_NORTH_WEST = {{DIRECTION_NORTH_WEST}}u;
+const uint DIRECTION_ALL = {{DIRECTION_ALL}}u;
+
+const float SIDE_EDGE_WIDTH = {{SIDE_EDGE_WIDTH}};
+
+const uint SPRITE_EFFECT_SMOKE = {{SPRITE_EFFECT_SMOKE}}u;
+const uint SPRITE_EFFECT_HEAT_SHIMMER = {{SPRITE_EFFECT_HEAT_SHIMMER}}u;
+const uint SPRITE_EFFECT_GLOW = {{SPRITE_EFFECT_GLOW}}u;
+const uint SPRITE_EFFECT_DISSOLVE = {{SPRITE_EFFECT_DISSOLVE}}u;
+
+const vec3 SMOKE_COLOUR = {{SMOKE_COLOUR}};
+const float SMOKE_MAX_ALPHA = {{SMOKE_MAX_ALPHA}};
+
+const vec3 DISSOLVE_EDGE_COLOUR = {{DISSOLVE_EDGE_COLOUR}};
+const float DISSOLVE_EDGE_WIDTH = {{DISSOLVE_EDGE_WIDTH}};
+
+int get_light_base(uint i) {
+    return int(i * TBO_VISION_BUFFER_SIZE + v_CellIndex * TBO_VISION_ENTRY_SIZE);
+}
+
 uint get_lit_sides(uint i) {
-    int base = int(i * TBO_VISION_BUFFER_SIZE + v_CellIndex * TBO_VISION_ENTRY_SIZE);
+    int base = get_light_base(i);
     if (timestamp_is_visible(get_vision_timestamp(base, t_LightTable))) {
         return get_vision_bitmap(base, t_LightTable);
     }
     return 0u;
 }
 
-const vec3 VERTICAL = vec3(0, 0, 1);
+// the colour of anything tinted the light passed through on its way to the cell
+vec3 get_light_filter(uint i) {
+    return get_vision_filter(get_light_base(i), t_LightTable);
+}
+
+// normal maps point green up the image, which is towards negative y in the world
+vec3 get_normal(vec2 tex_coord) {
+    vec3 encoded = texture(t_NormalMap, tex_coord).rgb * 2.0 - 1.0;
+    return normalize(vec3(encoded.x, -encoded.y, encoded.z));
+}
 
-vec3 diffuse_light(Light light, vec3 surface_colour) {
+vec3 diffuse_light(Light light, vec3 light_filter, vec3 surface_colour, vec3 normal) {
     vec3 direction = normalize(light.position.xyz - vec3(v_FragPosition, 0));
-    return surface_colour * light.colour.rgb * light.colour.a * dot(direction, VERTICAL);
+    return surface_colour * light.colour.rgb * light_filter * light.colour.a * max(dot(direction, normal), 0.0);
 }
 
-const float AMBIENT_LIGHT_MULT = 0.1;
+// a raised sprite's top is drawn v_Raise cells above its cell, and below that is its south face.
+// fragments on the edges of the top face the adjacent sides, and the middle of the top faces every side.
+uint get_side_bitmap() {
+    vec2 top = vec2(v_CellPosition.x, v_CellPosition.y + v_Raise);
+    if (top.y > 1.0) {
+        return DIRECTION_SOUTH | DIRECTION_SOUTH_EAST | DIRECTION_SOUTH_WEST;
+    }
+
+    bool north = top.y < SIDE_EDGE_WIDTH;
+    bool south = top.y > 1.0 - SIDE_EDGE_WIDTH;
+    bool west = top.x < SIDE_EDGE_WIDTH;
+    bool east = top.x > 1.0 - SIDE_EDGE_WIDTH;
+
+    uint bitmap = 0u;
+    if (north) { bitmap |= DIRECTION_NORTH; }
+    if (south) { bitmap |= DIRECTION_SOUTH; }
+    if (west) { bitmap |= DIRECTION_WEST; }
+    if (east) { bitmap |= DIRECTION_EAST; }
+    if (north && east) { bitmap |= DIRECTION_NORTH_EAST; }
+    if (south && east) { bitmap |= DIRECTION_SOUTH_EAST; }
+    if (south && west) { bitmap |= DIRECTION_SOUTH_WEST; }
+    if (north && west) { bitmap |= DIRECTION_NORTH_WEST; }
+
+    if (bitmap == 0u) {
+        return DIRECTION_ALL;
+    }
+    return bitmap;
+}
+
+float u64_uvec2_to_float(uvec2 u) {
+    const float MAXUINT_FLOAT = 4294967296.0;
+    return float(u[1]) * MAXUINT_FLOAT + float(u[0]);
+}
+
+// slowly drifting wisps, so a cloud doesn't look like a grid of squares
+vec4 smoke_colour(float density) {
+    float t = u64_uvec2_to_float(u_TotalTimeMs_u64) / 1000.0;
+    vec2 p = v_FragPosition * 3.0;
+    float wisps =
+        sin(p.x + t * 0.7 + sin(p.y * 1.3 + t * 0.3)) +
+        sin(p.y * 0.8 - t * 0.5 + sin(p.x * 1.7 - t * 0.2));
+    float variation = 0.75 + wisps * 0.125;
+    return vec4(SMOKE_COLOUR, density * SMOKE_MAX_ALPHA * variation);
+}
+
+// shifts each row of pixels from side to side
+vec2 heat_shimmer(vec2 tex_coord, float amplitude_px, float speed) {
+    float t = u64_uvec2_to_float(u_TotalTimeMs_u64) / 1000.0;
+    float row = floor(v_FragPosition.y * u_CellSize.y);
+    float offset_px = floor(sin(row * 0.8 + t * speed) * amplitude_px + 0.5);
+    float half_texel = 0.5 / u_SpriteSheetSize.x;
+    tex_coord.x = clamp(tex_coord.x + offset_px / u_SpriteSheetSize.x,
+                        v_TexCoordMin.x + half_texel, v_TexCoordMax.x - half_texel);
+    return tex_coord;
+}
+
+bool is_opaque_within_sprite(vec2 tex_coord) {
+    if (any(lessThan(tex_coord, v_TexCoordMin)) || any(greaterThan(tex_coord, v_TexCoordMax))) {
+        return false;
+    }
+    return texture(t_Texture, tex_coord).a >= 0.001;
+}
+
+// a transparent pixel is on the outline if it's next to an opaque one
+bool is_outline(vec2 tex_coord) {
+    vec2 texel = 1.0 / u_SpriteSheetSize;
+    return is_opaque_within_sprite(tex_coord + vec2(texel.x, 0.0)) ||
+        is_opaque_within_sprite(tex_coord - vec2(texel.x, 0.0)) ||
+        is_opaque_within_sprite(tex_coord + vec2(0.0, texel.y)) ||
+        is_opaque_within_sprite(tex_coord - vec2(0.0, texel.y));
+}
+
+// a fixed random value for each pixel of the sprite sheet, for deciding the order pixels dissolve in
+float dissolve_noise(vec2 tex_coord) {
+    vec2 pixel = floor(tex_coord * u_SpriteSheetSize);
+    return fract(sin(dot(pixel, vec2(12.9898, 78.233))) * 43758.5453);
+}
 
 void main() {
 
-    vec4 tex_colour = texture(t_Texture, v_TexCoord);
+    vec2 tex_coord = v_TexCoord;
+    if (v_SpriteEffect == SPRITE_EFFECT_HEAT_SHIMMER) {
+        tex_coord = heat_shimmer(tex_coord, v_SpriteEffectArgs[0], v_SpriteEffectArgs[1]);
+    }
+
+    vec4 tex_colour;
+    if (v_SpriteEffect == SPRITE_EFFECT_SMOKE) {
+        tex_colour = smoke_colour(v_SpriteEffectArgs[0]);
+    } else {
+        tex_colour = texture(t_Texture, tex_coord);
+    }
     if (tex_colour.a < 0.001) {
+        // outlines aren't lit, so they stand out in the dark
+        if (v_OutlineColour.a > 0.0 && is_outline(tex_coord)) {
+            Target0 = vec4(v_OutlineColour.rgb, 1.0);
+            return;
+        }
         discard;
     }
 
+    float dissolve_distance = 1.0;
+    if (v_SpriteEffect == SPRITE_EFFECT_DISSOLVE) {
+        // the threshold starts below 0 so nothing is on the edge before the sprite starts to dissolve
+        float threshold = mix(-DISSOLVE_EDGE_WIDTH, 1.0, v_SpriteEffectArgs[0]);
+        dissolve_distance = dissolve_noise(tex_coord) - threshold;
+        if (dissolve_distance < 0.0) {
+            discard;
+        }
+    }
+
     vec3 base_colour = tex_colour.rgb * v_ColourMult;
 
     int vision_base = int(v_CellIndex * TBO_VISION_ENTRY_SIZE);
     uint vision_bitmap = get_vision_bitmap(vision_base, t_VisionTable);
     uvec2 vision_timestamp = get_vision_timestamp(vision_base, t_VisionTable);
 
-    uint side_bitmap;
+    // cells that don't block light are lit from every side, so this only affects opaque cells
+    uint side_bitmap = get_side_bitmap();
 
     vec3 diffuse_total = vec3(0);
+    vec3 emissive_total = vec3(0);
     if (timestamp_is_visible(vision_timestamp)) {
+        // emissive sprites light themselves whether or not other lights reach them, but light nothing else.
+        // remembered cells show how they looked, not what glows there now, so they get no emissive light
+        // a glowing instance overrides its sprite's emissive value
+        float emissive = v_SpriteEffect == SPRITE_EFFECT_GLOW ? v_SpriteEffectArgs[0] : v_Emissive;
+        emissive_total = base_colour * emissive;
+
+        vec3 normal = get_normal(tex_coord);
         for (uint i = 0u; i < u_NumLights; i++) {
             uint lit_sides = get_lit_sides(i);
-            uint visible_lit_sides = lit_sides & vision_bitmap;
+            uint visible_lit_sides = lit_sides & vision_bitmap & side_bitmap;
             if (visible_lit_sides != 0u) {
-                diffuse_total += diffuse_light(u_Lights[i], base_colour);
+                diffuse_total += diffuse_light(u_Lights[i], get_light_filter(i), base_colour, normal);
             }
         }
     }
 
-    vec3 ambient_total = base_colour * AMBIENT_LIGHT_MULT;
+    vec3 ambient_total = base_colour * get_ambient_light(v_CellIndex, t_SkyTable);
+
+    vec3 colour = ambient_total + diffuse_total + emissive_total;
+    if (v_SpriteEffect == SPRITE_EFFECT_DISSOLVE && dissolve_distance < DISSOLVE_EDGE_WIDTH) {
+        colour = DISSOLVE_EDGE_COLOUR;
+    }
+
+    // a flash sits on top of the sprite effect rather than replacing it
+    colour = mix(colour, v_Flash.rgb, v_Flash.a);
+
+    // only translucent sprites are blended, so the edges of other sprites stay solid
+    float alpha = v_SpriteEffect == SPRITE_EFFECT_SMOKE ? tex_colour.a : 1.0;
 
-    Target0 = vec4((ambient_total + diffuse_total), 1);
+    Target0 = vec4(colour, alpha);
 }
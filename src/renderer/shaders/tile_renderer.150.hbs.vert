@@ -19,19 +19,36 @@ in uint a_Flags;
 in uint a_SpriteEffect;
 in vec4 a_SpriteEffectArgs;
 in uint a_HideInDark;
+in float a_Emissive;
+in vec4 a_OutlineColour;
+in vec4 a_Flash;
 
 out vec2 v_TexCoord;
 out float v_ColourMult;
 flat out uint v_CellIndex;
 out vec2 v_FragPosition;
+out vec2 v_CellPosition;
+flat out float v_Raise;
+flat out uint v_SpriteEffect;
+flat out vec4 v_SpriteEffectArgs;
+flat out vec2 v_TexCoordMin;
+flat out vec2 v_TexCoordMax;
+flat out float v_Emissive;
+flat out vec4 v_OutlineColour;
+flat out vec4 v_Flash;
 
 const uint FLAGS_ENABLED = {{FLAGS_ENABLED}}u;
 const uint FLAGS_SPRITE_EFFECT = {{FLAGS_SPRITE_EFFECT}}u;
+const uint FLAGS_TRANSLUCENT = {{FLAGS_TRANSLUCENT}}u;
+
+// opaque and translucent instances are drawn by separate pipelines
+const bool TRANSLUCENT_PASS = {{TRANSLUCENT_PASS}} == 1;
 
 const uint DEPTH_FIXED = {{DEPTH_FIXED}}u;
 const uint DEPTH_GRADIENT = {{DEPTH_GRADIENT}}u;
 const uint DEPTH_BOTTOM = {{DEPTH_BOTTOM}}u;
 
+const uint SPRITE_EFFECT_NONE = 0xffffffffu;
 const uint SPRITE_EFFECT_WATER = {{SPRITE_EFFECT_WATER}}u;
 
 float u64_uvec2_to_float(uvec2 u) {
@@ -63,14 +80,20 @@ float water_colour_mult(float steps, float base_mult, float max_mult) {
 }
 
 void main() {
-    if ((a_Flags & FLAGS_ENABLED) == 0u) {
+    if ((a_Flags & FLAGS_ENABLED) == 0u || ((a_Flags & FLAGS_TRANSLUCENT) != 0u) != TRANSLUCENT_PASS) {
         gl_Position = vec4(0.0, 0.0, 0.0, -1.0);
         return;
     }
 
     v_ColourMult = 1.0;
+    v_Emissive = a_Emissive;
+    v_OutlineColour = a_OutlineColour;
+    v_Flash = a_Flash;
+    v_SpriteEffect = SPRITE_EFFECT_NONE;
+    v_SpriteEffectArgs = a_SpriteEffectArgs;
 
     if ((a_Flags & FLAGS_SPRITE_EFFECT) != 0u) {
+        v_SpriteEffect = a_SpriteEffect;
         switch (a_SpriteEffect) {
             case SPRITE_EFFECT_WATER:
                 v_ColourMult *= water_colour_mult(a_SpriteEffectArgs[0], a_SpriteEffectArgs[1], a_SpriteEffectArgs[2]);
@@ -109,8 +132,18 @@ void main() {
     }
 
     v_TexCoord = get_tex_coord_inverted(a_SpriteSheetPixCoord, a_Pos, a_PixSize);
+
+    // effects that sample around a pixel stay within the instance's sprite
+    vec2 corner_a = get_tex_coord_inverted(a_SpriteSheetPixCoord, vec2(0.0), a_PixSize);
+    vec2 corner_b = get_tex_coord_inverted(a_SpriteSheetPixCoord, vec2(1.0), a_PixSize);
+    v_TexCoordMin = min(corner_a, corner_b);
+    v_TexCoordMax = max(corner_a, corner_b);
     v_FragPosition = a_Position + a_Pos;
 
+    // in cells relative to the instance's cell, so the part of a sprite drawn above its cell has negative y
+    v_CellPosition = (a_Pos * a_PixSize - a_PixOffset) / u_CellSize;
+    v_Raise = a_PixOffset.y / u_CellSize.y;
+
     vec2 dst = get_output_vertex(a_Position, a_PixOffset, a_PixSize, a_Pos);
     gl_Position = vec4(dst, depth, 1.0);
 }
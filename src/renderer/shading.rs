@@ -0,0 +1,11 @@
+// values shared by the tile renderer's shaders, which get them through the template, and the software renderer
+
+// how far into the top of a cell its edges face outwards
+pub const SIDE_EDGE_WIDTH: f32 = 0.25;
+
+pub const SMOKE_COLOUR: [f32; 3] = [0.7, 0.7, 0.75];
+pub const SMOKE_MAX_ALPHA: f32 = 0.8;
+
+// pixels about to dissolve glow this colour
+pub const DISSOLVE_EDGE_COLOUR: [f32; 3] = [1.0, 0.5, 0.2];
+pub const DISSOLVE_EDGE_WIDTH: f32 = 0.1;
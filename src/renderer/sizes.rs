@@ -1,13 +1,23 @@
 pub const MAX_NUM_INSTANCES: usize = 65536;
 pub const MAX_NUM_LIGHTS: usize = 32;
+pub const MAX_NUM_PARTICLES: usize = 4096;
 
 pub const MAX_CELL_TABLE_SIZE: usize = 16384;
 pub const TBO_VISION_FRAME_COUNT_SIZE: usize = 5; // 40 bit uint
 pub const TBO_VISION_BITMAP_SIZE: usize = 1; // 8 bit bitmap
+pub const TBO_VISION_FILTER_SIZE: usize = 3; // 8 bits per channel
 pub const TBO_VISION_BITMAP_OFFSET: usize = TBO_VISION_FRAME_COUNT_SIZE;
-pub const TBO_VISION_ENTRY_SIZE: usize = TBO_VISION_FRAME_COUNT_SIZE + TBO_VISION_BITMAP_SIZE;
+pub const TBO_VISION_FILTER_OFFSET: usize = TBO_VISION_BITMAP_OFFSET + TBO_VISION_BITMAP_SIZE;
+pub const TBO_VISION_ENTRY_SIZE: usize = TBO_VISION_FRAME_COUNT_SIZE + TBO_VISION_BITMAP_SIZE + TBO_VISION_FILTER_SIZE;
 pub const TBO_VISION_BUFFER_SIZE: usize = TBO_VISION_ENTRY_SIZE * MAX_CELL_TABLE_SIZE;
 
 pub const LIGHT_BUFFER_SIZE: usize = TBO_VISION_BUFFER_SIZE * MAX_NUM_LIGHTS;
 
 pub const FIELD_UI_MAX_NUM_INSTANCES: usize = 16384;
+
+// a background, a cell for each cell in the world, and markers
+pub const MINIMAP_MAX_NUM_MARKERS: usize = 1024;
+pub const MINIMAP_MAX_NUM_INSTANCES: usize = 1 + MAX_CELL_TABLE_SIZE + MINIMAP_MAX_NUM_MARKERS;
+
+// a background and a pixel of text for each instance after it
+pub const DESCRIPTION_MAX_NUM_INSTANCES: usize = 4096;
@@ -0,0 +1,639 @@
+use std::time::Duration;
+use image::{self, RgbaImage, Rgba};
+use cgmath::{Vector2, Vector3, ElementWise, InnerSpace};
+
+use renderer::sprite_sheet::{self, TileSpriteTable};
+use renderer::tile_renderer::{Instance, instance_flags, compute_scroll_offset};
+use renderer::instance_manager::InstanceManager;
+use renderer::particles::{Particle, ParticleEmitter};
+use renderer::render_target;
+use renderer::zoom::Zoom;
+use renderer::sizes;
+use renderer::shading::{SIDE_EDGE_WIDTH, SMOKE_COLOUR, SMOKE_MAX_ALPHA, DISSOLVE_EDGE_COLOUR, DISSOLVE_EDGE_WIDTH};
+
+use content::{DepthType, SpriteEffect, AmbientLightInfo};
+use entity_store::{EntityStore, EntityChange};
+use spatial_hash::SpatialHashTable;
+use static_grid::StaticGrid;
+use vision::{VisionCell, NO_FILTER};
+use direction::{Direction, DirectionBitmap};
+
+use frontend::{self, OutputWorldState, LightUpdate};
+use res::{input_sprite, paths, files};
+use util::time::duration_millis;
+use camera::{Camera, CameraFocus};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SoftwareLight {
+    position: [f32; 3],
+    colour: [f32; 3],
+    intensity: f32,
+}
+
+impl LightUpdate for SoftwareLight {
+    fn set_position(&mut self, position: Vector2<f32>) {
+        self.position[0] = position.x;
+        self.position[1] = position.y;
+    }
+    fn set_height(&mut self, height: f32) {
+        self.position[2] = height;
+    }
+    fn set_colour(&mut self, colour: [f32; 3]) {
+        self.colour = colour;
+    }
+    fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+}
+
+// mirrors the water effect in the tile renderer's vertex shader
+fn water_colour_mult(position: [f32; 2], total_time_ms: u64, steps: f32, base_mult: f32, max_mult: f32) -> f32 {
+    let x = position[0] * 10.0;
+    let y = position[1] * 10.0;
+    let t = total_time_ms as f32 / 160.0;
+
+    const PARTS: f32 = 4.0;
+    let total =
+        ((x + (t / 23.0).sin() * 11.0) / 7.0 + t / 11.0).sin() +
+        ((x - (t / 17.0).sin() * 13.0) / 5.0 + t / 29.0).sin() +
+        ((y + (t / 11.0).sin() * 17.0) / 11.0 + t / 17.0).sin() +
+        ((y - (t / 19.0).sin() * 23.0) / 13.0 + t / 23.0).sin();
+
+    let val = ((total / PARTS) + 1.0) / 2.0;
+
+    let stepped = (val * steps).floor() / steps;
+
+    base_mult + stepped * (max_mult - base_mult)
+}
+
+// mirrors the smoke effect in the tile renderer's fragment shader
+fn smoke_colour(frag_position: Vector2<f32>, total_time_ms: u64, density: f32) -> ([f32; 3], f32) {
+    let t = total_time_ms as f32 / 1000.0;
+    let p = frag_position * 3.0;
+    let wisps =
+        (p.x + t * 0.7 + (p.y * 1.3 + t * 0.3).sin()).sin() +
+        (p.y * 0.8 - t * 0.5 + (p.x * 1.7 - t * 0.2).sin()).sin();
+    let variation = 0.75 + wisps * 0.125;
+    (SMOKE_COLOUR, density * SMOKE_MAX_ALPHA * variation)
+}
+
+// mirrors the heat shimmer effect in the tile renderer's fragment shader, giving a horizontal offset in pixels
+fn heat_shimmer_offset(frag_position: Vector2<f32>, total_time_ms: u64, amplitude_px: f32, speed: f32) -> f32 {
+    let t = total_time_ms as f32 / 1000.0;
+    let row = (frag_position.y * input_sprite::HEIGHT_PX as f32).floor();
+    ((row * 0.8 + t * speed).sin() * amplitude_px + 0.5).floor()
+}
+
+// mirrors the dissolve noise in the tile renderer's fragment shader, where texture coordinates are upside down
+fn dissolve_noise(sheet_x: f32, sheet_y_inverted: f32) -> f32 {
+    let x = (sheet_x * 12.9898 + sheet_y_inverted * 78.233).sin() * 43758.5453;
+    x - x.floor()
+}
+
+// mirrors the choice of which sides of its cell a fragment faces in the tile renderer's fragment shader
+fn side_bitmap(cell_position: Vector2<f32>, raise: f32) -> u8 {
+    let top = Vector2::new(cell_position.x, cell_position.y + raise);
+    if top.y > 1.0 {
+        return (Direction::South.bitmap() | Direction::SouthEast.bitmap() | Direction::SouthWest.bitmap()).raw;
+    }
+
+    let north = top.y < SIDE_EDGE_WIDTH;
+    let south = top.y > 1.0 - SIDE_EDGE_WIDTH;
+    let west = top.x < SIDE_EDGE_WIDTH;
+    let east = top.x > 1.0 - SIDE_EDGE_WIDTH;
+
+    let sides = [
+        (north, Direction::North),
+        (south, Direction::South),
+        (west, Direction::West),
+        (east, Direction::East),
+        (north && east, Direction::NorthEast),
+        (south && east, Direction::SouthEast),
+        (south && west, Direction::SouthWest),
+        (north && west, Direction::NorthWest),
+    ];
+
+    let mut bitmap = DirectionBitmap::empty();
+    for &(faces, direction) in sides.iter() {
+        if faces {
+            bitmap |= direction.bitmap();
+        }
+    }
+
+    if bitmap.raw == 0 {
+        DirectionBitmap::all().raw
+    } else {
+        bitmap.raw
+    }
+}
+
+// a cpu implementation of the tile renderer, for producing frames without a gpu
+pub struct SoftwareRenderer {
+    sprite_sheet: RgbaImage,
+    normal_sheet: RgbaImage,
+    sprite_table: TileSpriteTable,
+    instances: Vec<Instance>,
+    instance_manager: InstanceManager,
+    particles: Vec<Particle>,
+    particle_emitter: ParticleEmitter,
+    vision: StaticGrid<VisionCell>,
+    lights: Vec<(StaticGrid<VisionCell>, SoftwareLight)>,
+    num_lights: usize,
+    sky: StaticGrid<bool>,
+    ambient_light: [f32; 3],
+    sky_light: [f32; 3],
+    camera: Camera,
+    frame_count: u64,
+    total_time_ms: u64,
+    fixed_frame_duration: Option<Duration>,
+    output: RgbaImage,
+    depth: Vec<f32>,
+}
+
+impl SoftwareRenderer {
+    pub fn new(window_dimensions: (u16, u16)) -> Self {
+        let sprite_sheet_path = paths::res_path(files::SPRITE_SHEET);
+        let image = image::open(&sprite_sheet_path)
+            .expect(format!("Failed to open sprite sheet (looked for {})",
+                            sprite_sheet_path.display()).as_ref())
+            .to_rgba();
+        let normal_map = sprite_sheet::load_normal_map(&paths::res_path(files::SPRITE_NORMAL_MAP))
+            .unwrap_or_else(|e| panic!("{}", e));
+        let (sprite_sheet, normal_sheet, sprite_table, _) =
+            sprite_sheet::create_image(&image, normal_map.as_ref(), &paths::res_path(files::SPRITE_MANIFEST));
+
+        let mut instances = Vec::with_capacity(sizes::MAX_NUM_INSTANCES);
+        for _ in 0..sizes::MAX_NUM_INSTANCES {
+            instances.push(Instance::default());
+        }
+
+        let (width, height) = render_target::target_dimensions(window_dimensions, Zoom::Fit);
+
+        Self {
+            sprite_sheet,
+            normal_sheet,
+            sprite_table,
+            instances,
+            instance_manager: InstanceManager::new(),
+            particles: vec![Particle::default(); sizes::MAX_NUM_PARTICLES],
+            particle_emitter: ParticleEmitter::new(),
+            vision: StaticGrid::new_default(0, 0),
+            lights: Vec::new(),
+            num_lights: 0,
+            sky: StaticGrid::new_copy(0, 0, false),
+            ambient_light: [0.0; 3],
+            sky_light: [0.0; 3],
+            camera: Camera::new(),
+            frame_count: 0,
+            total_time_ms: 0,
+            fixed_frame_duration: None,
+            output: RgbaImage::new(width as u32, height as u32),
+            depth: vec![1.0; (width as usize) * (height as usize)],
+        }
+    }
+
+    pub fn world_state(&mut self) -> SoftwareWorldState {
+        SoftwareWorldState {
+            renderer: self,
+            player_position: None,
+            next_light_index: 0,
+        }
+    }
+
+    // derives the time from the frame count, so effects don't depend on how long frames took to render
+    pub fn set_fixed_frame_duration(&mut self, frame_duration: Option<Duration>) {
+        self.fixed_frame_duration = frame_duration;
+    }
+
+    pub fn handle_resize(&mut self, window_dimensions: (u16, u16)) {
+        let (width, height) = render_target::target_dimensions(window_dimensions, Zoom::Fit);
+        self.output = RgbaImage::new(width as u32, height as u32);
+        self.depth = vec![1.0; (width as usize) * (height as usize)];
+    }
+
+    pub fn update_world_size(&mut self, width: u32, height: u32) {
+        self.vision = StaticGrid::new_default(width, height);
+        self.lights.clear();
+        self.sky = StaticGrid::new_copy(width, height, false);
+    }
+
+    pub fn output(&self) -> &RgbaImage {
+        &self.output
+    }
+
+    pub fn target_to_world(&self, target_px: Vector2<f32>) -> Vector2<i32> {
+        let scroll_offset = self.scroll_offset();
+        let world_px = target_px + scroll_offset;
+        Vector2::new((world_px.x / input_sprite::WIDTH_PX as f32).floor() as i32,
+                     (world_px.y / input_sprite::HEIGHT_PX as f32).floor() as i32)
+    }
+
+    fn scroll_offset(&self) -> Vector2<f32> {
+        compute_scroll_offset(self.output.width() as u16, self.output.height() as u16, self.camera.position())
+    }
+
+    pub fn render(&mut self) {
+        for pixel in self.output.pixels_mut() {
+            *pixel = Rgba { data: [0, 0, 0, 255] };
+        }
+        for depth in self.depth.iter_mut() {
+            *depth = 1.0;
+        }
+
+        let scroll_offset = self.scroll_offset();
+        let num_instances = self.instance_manager.num_instances() as usize;
+
+        self.draw_instances(num_instances, false, scroll_offset);
+
+        for i in 0..self.particle_emitter.num_particles() {
+            let particle = self.particles[i];
+            self.draw_particle(&particle, scroll_offset);
+        }
+
+        // translucent instances are drawn last, like the tile renderer's second pass
+        self.draw_instances(num_instances, true, scroll_offset);
+    }
+
+    fn draw_instances(&mut self, num_instances: usize, translucent: bool, scroll_offset: Vector2<f32>) {
+        for i in 0..num_instances {
+            let instance = self.instances[i];
+            if (instance.flags & instance_flags::TRANSLUCENT != 0) == translucent {
+                self.draw_instance(&instance, scroll_offset);
+            }
+        }
+    }
+
+    // mirrors the particle shaders
+    fn draw_particle(&mut self, particle: &Particle, scroll_offset: Vector2<f32>) {
+        let (position, alpha) = if let Some(state) = particle.state(self.total_time_ms) {
+            state
+        } else {
+            return;
+        };
+
+        let world_height = self.vision.height() as f32;
+        if position.x < 0.0 || position.y < 0.0 ||
+            position.x >= self.vision.width() as f32 || position.y >= world_height {
+            return;
+        }
+
+        let cell_coord = Vector2::new(position.x.floor() as i32, position.y.floor() as i32);
+        let vision_cell = if let Some(vision_cell) = self.vision.get_signed(cell_coord) {
+            *vision_cell
+        } else {
+            return;
+        };
+        if vision_cell.time != self.frame_count {
+            return;
+        }
+
+        // lit as though facing upwards, from every side
+        let up = Vector3::new(0.0, 0.0, 1.0);
+        let light_mult = self.light_mult(cell_coord, vision_cell, true, position, up, DirectionBitmap::all().raw);
+        let ambient_mult = self.ambient_mult(cell_coord);
+        let mut colour = [0.0; 3];
+        for i in 0..3 {
+            let base = particle.colour[i];
+            colour[i] = (base * ambient_mult[i] + base * light_mult[i]).max(0.0).min(1.0);
+        }
+
+        let depth = 1.0 - particle.position[1] / world_height;
+        let size = particle.size_px;
+        let cell_size = Vector2::new(input_sprite::WIDTH_PX as f32, input_sprite::HEIGHT_PX as f32);
+        let origin = position.mul_element_wise(cell_size) - scroll_offset - Vector2::new(size, size) * 0.5;
+
+        let x_start = (origin.x - 0.5).ceil().max(0.0) as u32;
+        let y_start = (origin.y - 0.5).ceil().max(0.0) as u32;
+        let x_end = ((origin.x + size - 0.5).ceil().max(0.0) as u32).min(self.output.width());
+        let y_end = ((origin.y + size - 0.5).ceil().max(0.0) as u32).min(self.output.height());
+
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                let depth_index = (y * self.output.width() + x) as usize;
+                if depth > self.depth[depth_index] {
+                    continue;
+                }
+                let behind = self.output.get_pixel(x, y).data;
+                let mut out_colour = [0, 0, 0, 255];
+                for i in 0..3 {
+                    let blended = colour[i] * alpha + (behind[i] as f32 / 255.0) * (1.0 - alpha);
+                    out_colour[i] = (blended * 255.0).round() as u8;
+                }
+                self.output.put_pixel(x, y, Rgba { data: out_colour });
+            }
+        }
+    }
+
+    fn draw_instance(&mut self, instance: &Instance, scroll_offset: Vector2<f32>) {
+        if instance.flags & instance_flags::ENABLED == 0 {
+            return;
+        }
+
+        let position: Vector2<f32> = instance.position.into();
+        let cell_coord = (position + Vector2::new(0.5, 0.5)).cast::<i32>();
+        let vision_cell = if let Some(vision_cell) = self.vision.get_signed(cell_coord) {
+            *vision_cell
+        } else {
+            return;
+        };
+
+        if vision_cell.time == 0 {
+            // if a cell has never been seen, don't draw it
+            return;
+        }
+
+        let visible = vision_cell.time == self.frame_count;
+        if !visible && instance.hide_in_dark == 1 {
+            return;
+        }
+
+        let has_effect = |effect: SpriteEffect| {
+            instance.flags & instance_flags::SPRITE_EFFECT != 0 && instance.sprite_effect == effect as u32
+        };
+        let smoke = has_effect(SpriteEffect::Smoke);
+        let args = instance.sprite_effect_args;
+
+        let mut colour_mult = 1.0;
+        if has_effect(SpriteEffect::Water) {
+            colour_mult *= water_colour_mult(instance.position, self.total_time_ms, args[0], args[1], args[2]);
+        }
+
+        let cell_size = Vector2::new(input_sprite::WIDTH_PX, input_sprite::HEIGHT_PX).cast::<f32>();
+        let size: Vector2<f32> = instance.pix_size.into();
+        let origin = position.mul_element_wise(cell_size) - scroll_offset - Vector2::from(instance.pix_offset);
+        let world_height = self.vision.height() as f32;
+        let raise = instance.pix_offset[1] / cell_size.y;
+        let sprite_min: Vector2<f32> = instance.sprite_sheet_pix_coord.into();
+        let sprite_max = sprite_min + size - Vector2::new(1.0, 1.0);
+
+        // a pixel is covered if its centre lies within the instance
+        let x_start = (origin.x - 0.5).ceil().max(0.0) as u32;
+        let y_start = (origin.y - 0.5).ceil().max(0.0) as u32;
+        let x_end = ((origin.x + size.x - 0.5).ceil().max(0.0) as u32).min(self.output.width());
+        let y_end = ((origin.y + size.y - 0.5).ceil().max(0.0) as u32).min(self.output.height());
+
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                let local = Vector2::new(x as f32 + 0.5 - origin.x, y as f32 + 0.5 - origin.y);
+                let vertex_position = local.div_element_wise(size);
+
+                let depth = if instance.depth_type == DepthType::Fixed as u32 {
+                    1.0 - instance.depth / world_height
+                } else if instance.depth_type == DepthType::Gradient as u32 {
+                    1.0 - (instance.depth - 1.0 + vertex_position.y) / world_height
+                } else if instance.depth_type == DepthType::Bottom as u32 {
+                    1.0 - instance.depth
+                } else {
+                    -1.0
+                };
+                if depth < -1.0 || depth > 1.0 {
+                    continue;
+                }
+
+                let depth_index = (y * self.output.width() + x) as usize;
+                if depth > self.depth[depth_index] {
+                    continue;
+                }
+
+                let frag_position = position + vertex_position;
+                let mut sheet_x = instance.sprite_sheet_pix_coord[0] + local.x.floor();
+                let sheet_y = instance.sprite_sheet_pix_coord[1] + local.y.floor();
+                if has_effect(SpriteEffect::HeatShimmer) {
+                    let offset = heat_shimmer_offset(frag_position, self.total_time_ms, args[0], args[1]);
+                    sheet_x = (sheet_x + offset).max(sprite_min.x).min(sprite_max.x);
+                }
+                if sheet_x < 0.0 || sheet_y < 0.0 ||
+                    sheet_x as u32 >= self.sprite_sheet.width() || sheet_y as u32 >= self.sprite_sheet.height() {
+                    continue;
+                }
+
+                let (tex_colour, alpha) = if smoke {
+                    smoke_colour(frag_position, self.total_time_ms, instance.sprite_effect_args[0])
+                } else {
+                    let data = self.sprite_sheet.get_pixel(sheet_x as u32, sheet_y as u32).data;
+                    ([data[0] as f32 / 255.0, data[1] as f32 / 255.0, data[2] as f32 / 255.0],
+                     data[3] as f32 / 255.0)
+                };
+                if alpha < 0.001 {
+                    // outlines aren't lit, so they stand out in the dark
+                    let outlined = instance.outline_colour[3] > 0.0;
+                    if outlined && self.is_outline(sheet_x, sheet_y, sprite_min, sprite_max) {
+                        let mut out_colour = [0, 0, 0, 255];
+                        for i in 0..3 {
+                            out_colour[i] = (instance.outline_colour[i].max(0.0).min(1.0) * 255.0).round() as u8;
+                        }
+                        self.output.put_pixel(x, y, Rgba { data: out_colour });
+                        self.depth[depth_index] = depth;
+                    }
+                    continue;
+                }
+
+                let mut dissolve_distance = 1.0;
+                if has_effect(SpriteEffect::Dissolve) {
+                    let threshold = -DISSOLVE_EDGE_WIDTH + (1.0 + DISSOLVE_EDGE_WIDTH) * args[0];
+                    let sheet_y_inverted = self.sprite_sheet.height() as f32 - 1.0 - sheet_y;
+                    dissolve_distance = dissolve_noise(sheet_x, sheet_y_inverted) - threshold;
+                    if dissolve_distance < 0.0 {
+                        continue;
+                    }
+                }
+
+                // only translucent sprites are blended, so the edges of other sprites stay solid
+                let alpha = if smoke { alpha.min(1.0) } else { 1.0 };
+
+                let mut base_colour = [0.0; 3];
+                for i in 0..3 {
+                    base_colour[i] = tex_colour[i] * colour_mult;
+                }
+
+                let normal = self.normal(sheet_x as u32, sheet_y as u32);
+                let cell_position = (local - Vector2::from(instance.pix_offset)).div_element_wise(cell_size);
+                let sides = side_bitmap(cell_position, raise);
+                let light_mult = self.light_mult(cell_coord, vision_cell, visible, frag_position, normal, sides);
+                let ambient_mult = self.ambient_mult(cell_coord);
+                // remembered cells get no emissive light, and a glow overrides the sprite's, as in the tile renderer
+                let emissive = if !visible {
+                    0.0
+                } else if has_effect(SpriteEffect::Glow) {
+                    args[0]
+                } else {
+                    instance.emissive
+                };
+
+                let mut colour = [0.0; 3];
+                for i in 0..3 {
+                    // emissive sprites light themselves whether or not other lights reach them
+                    colour[i] = base_colour[i] * (ambient_mult[i] + light_mult[i] + emissive);
+                    if has_effect(SpriteEffect::Dissolve) && dissolve_distance < DISSOLVE_EDGE_WIDTH {
+                        colour[i] = DISSOLVE_EDGE_COLOUR[i];
+                    }
+                    // a flash sits on top of the sprite effect rather than replacing it
+                    colour[i] += (instance.flash[i] - colour[i]) * instance.flash[3];
+                }
+
+                let behind = self.output.get_pixel(x, y).data;
+                let mut out_colour = [0, 0, 0, 255];
+                for i in 0..3 {
+                    let channel = colour[i].max(0.0).min(1.0);
+                    let blended = channel * alpha + (behind[i] as f32 / 255.0) * (1.0 - alpha);
+                    out_colour[i] = (blended * 255.0).round() as u8;
+                }
+
+                self.output.put_pixel(x, y, Rgba { data: out_colour });
+                if instance.flags & instance_flags::TRANSLUCENT == 0 {
+                    self.depth[depth_index] = depth;
+                }
+            }
+        }
+    }
+
+    // a transparent pixel is on the outline if it's next to an opaque one within the same sprite
+    fn is_outline(&self, sheet_x: f32, sheet_y: f32, sprite_min: Vector2<f32>, sprite_max: Vector2<f32>) -> bool {
+        let neighbours = [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)];
+        neighbours.iter().any(|&(dx, dy)| {
+            let x = sheet_x + dx;
+            let y = sheet_y + dy;
+            if x < sprite_min.x || y < sprite_min.y || x > sprite_max.x || y > sprite_max.y {
+                return false;
+            }
+            if x < 0.0 || y < 0.0 || x as u32 >= self.sprite_sheet.width() || y as u32 >= self.sprite_sheet.height() {
+                return false;
+            }
+            self.sprite_sheet.get_pixel(x as u32, y as u32).data[3] > 0
+        })
+    }
+
+    // normal maps point green up the image, which is towards negative y in the world
+    fn normal(&self, sheet_x: u32, sheet_y: u32) -> Vector3<f32> {
+        let encoded = self.normal_sheet.get_pixel(sheet_x, sheet_y).data;
+        let decode = |channel: u8| channel as f32 / 255.0 * 2.0 - 1.0;
+        Vector3::new(decode(encoded[0]), -decode(encoded[1]), decode(encoded[2])).normalize()
+    }
+
+    // the diffuse contribution of each light, mirroring the tile renderer's fragment shader
+    fn light_mult(&self, cell_coord: Vector2<i32>, vision_cell: VisionCell,
+                  visible: bool, frag_position: Vector2<f32>, normal: Vector3<f32>,
+                  side_bitmap: u8) -> [f32; 3] {
+        let mut total = [0.0; 3];
+        if !visible {
+            return total;
+        }
+
+        for &(ref grid, ref light) in self.lights[..self.num_lights].iter() {
+            let (lit_sides, filter) = match grid.get_signed(cell_coord) {
+                Some(cell) if cell.time == self.frame_count => (cell.bitmap, cell.filter),
+                _ => (0, NO_FILTER),
+            };
+            if lit_sides & vision_cell.bitmap & side_bitmap == 0 {
+                continue;
+            }
+
+            let direction = Vector3::new(light.position[0] - frag_position.x,
+                                         light.position[1] - frag_position.y,
+                                         light.position[2]).normalize();
+            let facing = direction.dot(normal).max(0.0);
+            for i in 0..3 {
+                total[i] += light.colour[i] * filter[i] * light.intensity * facing;
+            }
+        }
+
+        total
+    }
+
+    // mirrors get_ambient_light in the shaders
+    fn ambient_mult(&self, cell_coord: Vector2<i32>) -> [f32; 3] {
+        let mut total = self.ambient_light;
+        if self.sky.get_signed(cell_coord).cloned().unwrap_or(false) {
+            for i in 0..3 {
+                total[i] += self.sky_light[i];
+            }
+        }
+        total
+    }
+}
+
+pub struct SoftwareWorldState<'a> {
+    renderer: &'a mut SoftwareRenderer,
+    player_position: Option<Vector2<f32>>,
+    next_light_index: usize,
+}
+
+impl<'a, 'b> OutputWorldState<'a, 'b> for SoftwareWorldState<'a> {
+
+    type VisionCellGrid = &'b mut StaticGrid<VisionCell>;
+    type LightCellGrid = &'b mut StaticGrid<VisionCell>;
+    type LightUpdate = SoftwareLight;
+
+    fn update(&mut self, change: &EntityChange, entity_store: &EntityStore, spatial_hash: &SpatialHashTable) {
+        let renderer = &mut *self.renderer;
+        renderer.instance_manager.update(&mut renderer.instances, change, entity_store, spatial_hash, &renderer.sprite_table);
+        renderer.particle_emitter.update(change);
+        renderer.camera.update_focus(change);
+        let sky = &mut renderer.sky;
+        frontend::sky_change(change, entity_store, spatial_hash, |coord, open| {
+            if let Some(cell) = sky.get_signed_mut(coord) {
+                *cell = open;
+            }
+        });
+    }
+
+    fn set_player_position(&mut self, player_position: Vector2<f32>) {
+        self.player_position = Some(player_position);
+    }
+
+    fn set_frame_info(&mut self, frame_count: u64, total_time: Duration) {
+        self.renderer.frame_count = frame_count;
+        self.renderer.total_time_ms = if let Some(frame_duration) = self.renderer.fixed_frame_duration {
+            duration_millis(frame_duration) * frame_count
+        } else {
+            duration_millis(total_time)
+        };
+    }
+
+    fn set_ambient_light(&mut self, ambient: AmbientLightInfo, sky: AmbientLightInfo) {
+        self.renderer.ambient_light = ambient.total();
+        self.renderer.sky_light = sky.total();
+    }
+
+    fn focus_camera(&mut self, focus: CameraFocus, entity_store: &EntityStore, duration: Duration) {
+        self.renderer.camera.focus(focus, entity_store, duration);
+    }
+
+    fn vision_grid(&'b mut self) -> Self::VisionCellGrid {
+        &mut self.renderer.vision
+    }
+
+    fn next_light(&'b mut self) -> Option<(Self::LightCellGrid, &'b mut Self::LightUpdate)> {
+        if self.next_light_index < sizes::MAX_NUM_LIGHTS {
+            let index = self.next_light_index;
+            self.next_light_index += 1;
+
+            let renderer = &mut *self.renderer;
+            if index == renderer.lights.len() {
+                let grid = StaticGrid::new_default(renderer.vision.width(), renderer.vision.height());
+                renderer.lights.push((grid, Default::default()));
+            }
+
+            let &mut (ref mut grid, ref mut light) = &mut renderer.lights[index];
+            Some((grid, light))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> SoftwareWorldState<'a> {
+    pub fn finalise(self) {
+        let renderer = self.renderer;
+        renderer.num_lights = self.next_light_index;
+        renderer.particle_emitter.emit(&mut renderer.particles, renderer.total_time_ms);
+        if let Some(player_position) = self.player_position {
+            renderer.camera.set_target(player_position);
+        }
+
+        let view_size = Vector2::new(renderer.output.width() as f32 / input_sprite::WIDTH_PX as f32,
+                                     renderer.output.height() as f32 / input_sprite::HEIGHT_PX as f32);
+        let world_size = Vector2::new(renderer.vision.width(), renderer.vision.height()).cast();
+        renderer.camera.update(renderer.total_time_ms, view_size, world_size);
+    }
+}
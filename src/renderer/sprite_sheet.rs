@@ -1,12 +1,13 @@
 use std::cmp;
 use std::collections::BTreeMap;
+use std::path::Path;
 
 use gfx;
-use image::RgbaImage;
+use image::{self, RgbaImage, Rgba};
 use cgmath::Vector2;
 
 use direction::{Direction, OrdinalDirections, DirectionBitmap, CardinalDirection};
-use renderer::formats::{ColourFormat, DepthFormat};
+use renderer::formats::{ColourFormat, DepthFormat, NormalMapFormat};
 use renderer::common;
 use res::input_sprite::{self, InputSprite, InputSpriteLocation};
 use content::tile_sprite::{self, TileSprite};
@@ -15,9 +16,6 @@ use content::field_ui_sprite::{self, FieldUiSprite};
 // one for each combination of wall neighbours
 const TILES_PER_WALL: u32 = 256;
 
-// one for front and one for top
-const INSTANCES_PER_WALL_FIT: u32 = 2;
-
 // one for the top and one for each possible decoration
 const MAX_INSTANCES_PER_WALL: u32 = 5;
 
@@ -25,11 +23,16 @@ const SIMPLE_DEPTH: f32 = 0.1;
 const WALL_TOP_DEPTH: f32 = 0.9;
 const WALL_DECORATION_DEPTH: f32 = 0.1;
 
+// a normal pointing straight out of the screen, for sprites with no normal map
+const FLAT_NORMAL: [u8; 4] = [128, 128, 255, 255];
+
 #[derive(Clone, Copy, Debug)]
 pub struct SpriteLocation {
     pub position: f32,
     pub size: Vector2<f32>,
     pub offset: Vector2<f32>,
+    // how brightly the sprite lights itself, regardless of other lights
+    pub emissive: f32,
 }
 
 impl Default for SpriteLocation {
@@ -38,6 +41,7 @@ impl Default for SpriteLocation {
             position: 0.0,
             size: Vector2::new(0.0, 0.0),
             offset: Vector2::new(0.0, 0.0),
+            emissive: 0.0,
         }
     }
 }
@@ -58,6 +62,9 @@ impl WallSpriteLocation {
     pub fn offset(&self) -> &Vector2<f32> {
         &self.0.offset
     }
+    pub fn emissive(&self) -> f32 {
+        self.0.emissive
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -109,6 +116,7 @@ impl FieldUiSpriteTable {
 
 pub struct SpriteSheetTexture<R: gfx::Resources> {
     pub srv: gfx::handle::ShaderResourceView<R, [f32; 4]>,
+    pub normal_srv: gfx::handle::ShaderResourceView<R, [f32; 4]>,
     pub width: u32,
     pub height: u32,
 }
@@ -139,25 +147,29 @@ gfx_pipeline!( pipe {
         gfx::preset::depth::LESS_EQUAL_WRITE,
 });
 
-struct SpriteSheetBuilder<R: gfx::Resources> {
-    srv: gfx::handle::ShaderResourceView<R, [f32; 4]>,
+// pixels that are transparent in the sprite sheet are discarded, so normals can be written without blending
+gfx_pipeline!( normal_pipe {
+    vertex: gfx::VertexBuffer<Vertex> = (),
+    instance: gfx::InstanceBuffer<Instance> = (),
+    locals: gfx::ConstantBuffer<Locals> = "Locals",
+    mask: gfx::TextureSampler<[f32; 4]> = "t_Texture",
+    tex: gfx::TextureSampler<[f32; 4]> = "t_NormalMap",
+    out: gfx::RenderTarget<NormalMapFormat> = "Target0",
+    depth: gfx::DepthTarget<DepthFormat> =
+        gfx::preset::depth::LESS_EQUAL_WRITE,
+});
+
+// where each input sprite is placed on the sprite sheet, independent of how the sheet gets drawn
+struct SpriteSheetLayout {
     width: u32,
     height: u32,
-    input_sprites: Vec<input_sprite::InputSprite>,
+    instances: Vec<Instance>,
     tile_sprite_table: Vec<SpriteResolution>,
     field_ui_sprite_table: Vec<SpriteLocation>,
-    image: RgbaImage,
-    bundle: gfx::pso::bundle::Bundle<R, pipe::Data<R>>,
-    upload: gfx::handle::Buffer<R, Instance>,
-    num_instances: usize,
 }
 
-impl<R: gfx::Resources> SpriteSheetBuilder<R> {
-    fn new<F>(image: RgbaImage, input_sprites: Vec<InputSprite>, factory: &mut F) -> Self
-        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
-    {
-
-        let mut num_instances = 0;
+impl SpriteSheetLayout {
+    fn new(input_sprites: &[InputSprite]) -> Self {
         let mut width = input_sprite::WIDTH_PX; // leave room for blank sprite
         let mut height = 0;
 
@@ -165,31 +177,24 @@ impl<R: gfx::Resources> SpriteSheetBuilder<R> {
             use self::InputSprite::*;
             match sprite {
                 &Simple { location, .. } => {
-                    num_instances += 1;
                     width += location.size.x;
                     height = cmp::max(height, location.size.y);
                 }
                 &Wall { top, .. } => {
-                    num_instances += TILES_PER_WALL * MAX_INSTANCES_PER_WALL;
                     width += top.size.x * TILES_PER_WALL;
                     height = cmp::max(height, top.size.y);
                 }
                 &WallFit { top, front, .. } => {
-                    num_instances += INSTANCES_PER_WALL_FIT;
                     width += top.size.x + front.size.x;
                     height = cmp::max(cmp::max(top.size.y, front.size.y), height);
                 }
                 &FieldUi { location, .. } => {
-                    num_instances += 1;
                     width += location.size.x;
                     height = cmp::max(height, location.size.y);
                 }
             }
         }
 
-        let (_, srv, rtv) = factory.create_render_target(width as u16, height as u16)
-            .expect("Failed to create render target for sprite sheet");
-
         let mut tile_sprite_table = Vec::new();
         for _ in 0..tile_sprite::NUM_TILE_SPRITES {
             tile_sprite_table.push(SpriteResolution::default());
@@ -200,108 +205,59 @@ impl<R: gfx::Resources> SpriteSheetBuilder<R> {
             field_ui_sprite_table.push(SpriteLocation::default());
         }
 
-        let pso = factory.create_pipeline_simple(
-            include_bytes!("shaders/sprite_sheet.150.vert"),
-            include_bytes!("shaders/general.150.frag"),
-            pipe::new()).expect("Failed to create pso");
-
-        let vertex_data: Vec<Vertex> = common::QUAD_VERTICES_REFL.iter()
-            .map(|v| {
-                Vertex { pos: *v }
-            }).collect();
-
-        let (vertex_buffer, slice) =
-            factory.create_vertex_buffer_with_slice(
-                &vertex_data,
-                &common::QUAD_INDICES[..]);
-
-        let (img_width, img_height) = image.dimensions();
-        let tex_kind = gfx::texture::Kind::D2(img_width as u16, img_height as u16, gfx::texture::AaMode::Single);
-        let (_, texture) = factory.create_texture_immutable_u8::<ColourFormat>(tex_kind, &[&image])
-            .expect("Failed to create texture");
-
-        let sampler = factory.create_sampler(
-            gfx::texture::SamplerInfo::new(gfx::texture::FilterMethod::Scale,
-                                           gfx::texture::WrapMode::Tile));
-
-        let (_, _, depth_rtv) = factory.create_depth_stencil(width as u16, height as u16)
-            .expect("Failed to create depth stencil");
-
-        let data = pipe::Data {
-            vertex: vertex_buffer,
-            instance: common::create_instance_buffer(num_instances as usize, factory)
-                .expect("Failed to create instance buffer"),
-            locals: factory.create_constant_buffer(1),
-            tex: (texture, sampler),
-            out: rtv,
-            depth: depth_rtv,
-        };
-
-        let bundle = gfx::pso::bundle::Bundle::new(slice, pso, data);
-
-        let upload = factory.create_upload_buffer(num_instances as usize)
-            .expect("Failed to create upload buffer");
-
-        SpriteSheetBuilder {
-            srv,
+        let mut layout = SpriteSheetLayout {
             width,
             height,
-            input_sprites,
+            instances: Vec::new(),
             tile_sprite_table,
             field_ui_sprite_table,
-            image,
-            bundle,
-            upload,
-            num_instances: 0,
-        }
-    }
+        };
 
-    fn populate<F>(&mut self, factory: &mut F)
-        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
-    {
-        let mut mapping = factory.write_mapping(&self.upload)
-            .expect("Failed to map upload buffer");
+        layout.populate(input_sprites);
 
+        layout
+    }
+
+    fn populate(&mut self, input_sprites: &[InputSprite]) {
         // leave a blank sprite at the start
         self.tile_sprite_table[TileSprite::Blank as usize] = SpriteResolution::Simple(SpriteLocation {
             position: 0.0,
             size: input_sprite::DIMENSIONS.cast().into(),
             offset: Vector2::new(0.0, 0.0),
+            emissive: 0.0,
         });
 
         // leave room for blank sprite
         let mut sprite_sheet_x = input_sprite::WIDTH_PX;
-        let mut instance_index = 0;
 
-        for input_sprite in self.input_sprites.iter() {
+        for input_sprite in input_sprites.iter() {
             match input_sprite {
                 &InputSprite::Simple { sprite, location } => {
                     self.tile_sprite_table[sprite as usize] = SpriteResolution::Simple(SpriteLocation {
                         position: sprite_sheet_x as f32,
                         size: location.size.cast(),
                         offset: location.offset.cast(),
+                        emissive: location.emissive,
                     });
-                    mapping[instance_index] = Instance {
+                    self.instances.push(Instance {
                         in_pix_pos: location.position.cast().into(),
                         out_pix_pos: [sprite_sheet_x as f32, 0.0],
                         pix_size: location.size.cast().into(),
                         depth: SIMPLE_DEPTH,
-                    };
+                    });
                     sprite_sheet_x += location.size.x;
-                    instance_index += 1;
                 }
                 &InputSprite::Wall { sprite, top, ref decorations } => {
                     self.tile_sprite_table[sprite as usize] = SpriteResolution::Wall(WallSpriteLocation(SpriteLocation {
                         position: sprite_sheet_x as f32,
                         size: top.size.cast(),
                         offset: top.offset.cast(),
+                        emissive: top.emissive,
                     }));
                     for i in 0..TILES_PER_WALL {
-                        let instance_offset = Self::populate_wall(&mut mapping[instance_index..],
-                                                                  DirectionBitmap::new(i as u8), top,
-                                                                  decorations, sprite_sheet_x);
+                        Self::populate_wall(&mut self.instances, DirectionBitmap::new(i as u8), top,
+                                            decorations, sprite_sheet_x);
                         sprite_sheet_x += top.size.x;
-                        instance_index += instance_offset;
                     }
                 }
                 &InputSprite::WallFit { sprite, top, front } => {
@@ -315,64 +271,60 @@ impl<R: gfx::Resources> SpriteSheetBuilder<R> {
                             position: front_x as f32,
                             size: front.size.cast(),
                             offset: front.offset.cast(),
+                            emissive: front.emissive,
                         },
                         top: SpriteLocation {
                             position: top_x as f32,
                             size: top.size.cast(),
                             offset: top.offset.cast(),
+                            emissive: top.emissive,
                         },
                     };
 
-                    mapping[instance_index] = Instance {
+                    self.instances.push(Instance {
                         in_pix_pos: front.position.cast().into(),
                         out_pix_pos: [front_x as f32, 0.0],
                         pix_size: front.size.cast().into(),
                         depth: SIMPLE_DEPTH,
-                    };
-                    instance_index += 1;
+                    });
 
-                    mapping[instance_index] = Instance {
+                    self.instances.push(Instance {
                         in_pix_pos: top.position.cast().into(),
                         out_pix_pos: [top_x as f32, 0.0],
                         pix_size: top.size.cast().into(),
                         depth: SIMPLE_DEPTH,
-                    };
-                    instance_index += 1;
+                    });
                 }
                 &InputSprite::FieldUi { sprite, location } => {
                     self.field_ui_sprite_table[sprite as usize] = SpriteLocation {
                         position: sprite_sheet_x as f32,
                         size: location.size.cast(),
                         offset: location.offset.cast(),
+                        emissive: location.emissive,
                     };
-                    mapping[instance_index] = Instance {
+                    self.instances.push(Instance {
                         in_pix_pos: location.position.cast().into(),
                         out_pix_pos: [sprite_sheet_x as f32, 0.0],
                         pix_size: location.size.cast().into(),
                         depth: SIMPLE_DEPTH,
-                    };
-                    instance_index += 1;
+                    });
                     sprite_sheet_x += location.size.x;
                 }
             }
         }
-
-        self.num_instances = instance_index;
-        self.bundle.slice.instances = Some((self.num_instances as u32, 0));
     }
 
-    fn populate_wall(mapping: &mut [Instance], neighbour_bits: DirectionBitmap, top: InputSpriteLocation,
-                     decorations: &BTreeMap<Direction, Vector2<u32>>, sprite_sheet_x: u32) -> usize {
-        let mut instance_offset = 0;
+    fn populate_wall(instances: &mut Vec<Instance>, neighbour_bits: DirectionBitmap, top: InputSpriteLocation,
+                     decorations: &BTreeMap<Direction, Vector2<u32>>, sprite_sheet_x: u32) {
+        let start = instances.len();
 
-        mapping[instance_offset] = Instance {
+        instances.push(Instance {
             in_pix_pos: top.position.cast().into(),
             out_pix_pos: [sprite_sheet_x as f32, 0.0],
             pix_size: top.size.cast().into(),
             depth: WALL_TOP_DEPTH,
-        };
+        });
 
-        instance_offset += 1;
         use self::CardinalDirection::*;
         for cdir in &[North, East, West, South] {
             let dir = cdir.direction();
@@ -380,13 +332,12 @@ impl<R: gfx::Resources> SpriteSheetBuilder<R> {
                 // neighbour is absent
                 let decoration = *decorations.get(&dir)
                     .expect(format!("Missing decoration for {:?}", dir).as_ref());
-                mapping[instance_offset] = Instance {
+                instances.push(Instance {
                     in_pix_pos: decoration.cast().into(),
                     out_pix_pos: [sprite_sheet_x as f32, 0.0],
                     pix_size: top.size.cast().into(),
                     depth: WALL_DECORATION_DEPTH,
-                };
-                instance_offset += 1;
+                });
             }
         }
 
@@ -396,20 +347,170 @@ impl<R: gfx::Resources> SpriteSheetBuilder<R> {
                 // both cardinal neighbours are present but ordinal neighbour is absent
                 let decoration = *decorations.get(&ord.direction())
                     .expect(format!("Missing decoration for {:?}", ord.direction()).as_ref());
-                mapping[instance_offset] = Instance {
+                instances.push(Instance {
                     in_pix_pos: decoration.cast().into(),
                     out_pix_pos: [sprite_sheet_x as f32, 0.0],
                     pix_size: top.size.cast().into(),
                     depth: WALL_DECORATION_DEPTH,
-                };
-                instance_offset += 1;
+                });
             }
         }
 
-        assert!(instance_offset <= MAX_INSTANCES_PER_WALL as usize);
+        assert!(instances.len() - start <= MAX_INSTANCES_PER_WALL as usize);
+    }
+
+    // draws the sprite sheet on the cpu, in the same order the depth test would
+    // copies the pixels of the image where the mask, which has the same layout, isn't transparent
+    fn draw_image(&self, image: &RgbaImage, mask: &RgbaImage, background: Rgba<u8>) -> RgbaImage {
+        let mut sprite_sheet = RgbaImage::from_pixel(self.width, self.height, background);
+
+        let mut instances = self.instances.iter().collect::<Vec<_>>();
+        instances.sort_by(|a, b| b.depth.partial_cmp(&a.depth).expect("Invalid depth"));
+
+        for instance in instances {
+            let size = Vector2::new(instance.pix_size[0] as u32, instance.pix_size[1] as u32);
+            for y in 0..size.y {
+                for x in 0..size.x {
+                    let in_x = instance.in_pix_pos[0] as u32 + x;
+                    let in_y = instance.in_pix_pos[1] as u32 + y;
+                    let out_x = instance.out_pix_pos[0] as u32 + x;
+                    let out_y = instance.out_pix_pos[1] as u32 + y;
+                    if in_x >= image.width() || in_y >= image.height() ||
+                        out_x >= self.width || out_y >= self.height {
+                        continue;
+                    }
+                    if mask.get_pixel(in_x, in_y).data[3] == 0 {
+                        continue;
+                    }
+                    sprite_sheet.put_pixel(out_x, out_y, *image.get_pixel(in_x, in_y));
+                }
+            }
+        }
+
+        sprite_sheet
+    }
+}
+
+struct SpriteSheetBuilder<R: gfx::Resources> {
+    srv: gfx::handle::ShaderResourceView<R, [f32; 4]>,
+    normal_srv: gfx::handle::ShaderResourceView<R, [f32; 4]>,
+    layout: SpriteSheetLayout,
+    image: RgbaImage,
+    bundle: gfx::pso::bundle::Bundle<R, pipe::Data<R>>,
+    normal_bundle: Option<gfx::pso::bundle::Bundle<R, normal_pipe::Data<R>>>,
+    upload: gfx::handle::Buffer<R, Instance>,
+    num_instances: usize,
+}
+
+impl<R: gfx::Resources> SpriteSheetBuilder<R> {
+    // the normal map is packed with the same layout as the image, so it must be the same size
+    fn new<F>(image: RgbaImage, normal_map: Option<RgbaImage>, input_sprites: Vec<InputSprite>, factory: &mut F) -> Self
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let layout = SpriteSheetLayout::new(&input_sprites);
+        let num_instances = layout.instances.len();
+        let width = layout.width;
+        let height = layout.height;
+
+        let (_, srv, rtv) = factory.create_render_target(width as u16, height as u16)
+            .expect("Failed to create render target for sprite sheet");
+
+        let pso = factory.create_pipeline_simple(
+            include_bytes!("shaders/sprite_sheet.150.vert"),
+            include_bytes!("shaders/general.150.frag"),
+            pipe::new()).expect("Failed to create pso");
+
+        let vertex_data: Vec<Vertex> = common::QUAD_VERTICES_REFL.iter()
+            .map(|v| {
+                Vertex { pos: *v }
+            }).collect();
+
+        let (vertex_buffer, slice) =
+            factory.create_vertex_buffer_with_slice(
+                &vertex_data,
+                &common::QUAD_INDICES[..]);
+
+        let (img_width, img_height) = image.dimensions();
+        let tex_kind = gfx::texture::Kind::D2(img_width as u16, img_height as u16, gfx::texture::AaMode::Single);
+        let (_, texture) = factory.create_texture_immutable_u8::<ColourFormat>(tex_kind, &[&image])
+            .expect("Failed to create texture");
+
+        let sampler = factory.create_sampler(
+            gfx::texture::SamplerInfo::new(gfx::texture::FilterMethod::Scale,
+                                           gfx::texture::WrapMode::Tile));
+
+        let (_, _, depth_rtv) = factory.create_depth_stencil(width as u16, height as u16)
+            .expect("Failed to create depth stencil");
+
+        let data = pipe::Data {
+            vertex: vertex_buffer,
+            instance: common::create_instance_buffer(num_instances, factory)
+                .expect("Failed to create instance buffer"),
+            locals: factory.create_constant_buffer(1),
+            tex: (texture, sampler.clone()),
+            out: rtv,
+            depth: depth_rtv,
+        };
+
+        let (normal_srv, normal_bundle) = if let Some(normal_map) = normal_map {
+            let (_, normal_srv, normal_rtv) = factory.create_render_target::<NormalMapFormat>(width as u16, height as u16)
+                .expect("Failed to create render target for normal map");
+
+            let normal_pso = factory.create_pipeline_simple(
+                include_bytes!("shaders/sprite_sheet.150.vert"),
+                include_bytes!("shaders/normal_map.150.frag"),
+                normal_pipe::new()).expect("Failed to create pso");
+
+            let (_, normal_texture) = factory.create_texture_immutable_u8::<NormalMapFormat>(tex_kind, &[&normal_map])
+                .expect("Failed to create texture");
+
+            let normal_data = normal_pipe::Data {
+                vertex: data.vertex.clone(),
+                instance: data.instance.clone(),
+                locals: data.locals.clone(),
+                mask: data.tex.clone(),
+                tex: (normal_texture, sampler),
+                out: normal_rtv,
+                depth: data.depth.clone(),
+            };
+
+            (normal_srv, Some(gfx::pso::bundle::Bundle::new(slice.clone(), normal_pso, normal_data)))
+        } else {
+            (create_flat_normal_map(factory), None)
+        };
+
+        let bundle = gfx::pso::bundle::Bundle::new(slice, pso, data);
+
+        let upload = factory.create_upload_buffer(num_instances)
+            .expect("Failed to create upload buffer");
+
+        SpriteSheetBuilder {
+            srv,
+            normal_srv,
+            layout,
+            image,
+            bundle,
+            normal_bundle,
+            upload,
+            num_instances: 0,
+        }
+    }
+
+    fn populate<F>(&mut self, factory: &mut F)
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let mut mapping = factory.write_mapping(&self.upload)
+            .expect("Failed to map upload buffer");
 
-        instance_offset
+        for (index, instance) in self.layout.instances.iter().enumerate() {
+            mapping[index] = *instance;
+        }
 
+        self.num_instances = self.layout.instances.len();
+        self.bundle.slice.instances = Some((self.num_instances as u32, 0));
+        if let Some(ref mut normal_bundle) = self.normal_bundle {
+            normal_bundle.slice.instances = Some((self.num_instances as u32, 0));
+        }
     }
 
     fn draw<C, D>(&self, encoder: &mut gfx::Encoder<R, C>, device: &mut D)
@@ -424,17 +525,28 @@ impl<R: gfx::Resources> SpriteSheetBuilder<R> {
         let in_tex_dimensions = self.image.dimensions();
         encoder.update_constant_buffer(&self.bundle.data.locals, &Locals {
             in_tex_size: [in_tex_dimensions.0 as f32, in_tex_dimensions.1 as f32],
-            out_tex_size: [self.width as f32, self.height as f32],
+            out_tex_size: [self.layout.width as f32, self.layout.height as f32],
         });
 
         encoder.draw(&self.bundle.slice, &self.bundle.pso, &self.bundle.data);
+
+        if let Some(ref normal_bundle) = self.normal_bundle {
+            // the depth buffer is shared, and must be reset so the normals are layered like the colours
+            let flat = [0.5, 0.5, 1.0, 1.0];
+            encoder.clear(&normal_bundle.data.out, flat);
+            encoder.clear_depth(&normal_bundle.data.depth, 1.0);
+            encoder.draw(&normal_bundle.slice, &normal_bundle.pso, &normal_bundle.data);
+        }
+
         encoder.flush(device);
     }
 
     fn build(self) -> (SpriteSheetTexture<R>, TileSpriteTable, FieldUiSpriteTable) {
-        let Self { srv, width, height, tile_sprite_table, field_ui_sprite_table, .. } = self;
+        let Self { srv, normal_srv, layout, .. } = self;
+        let SpriteSheetLayout { width, height, tile_sprite_table, field_ui_sprite_table, .. } = layout;
         let sprite_sheet = SpriteSheetTexture {
             srv,
+            normal_srv,
             width,
             height,
         };
@@ -443,16 +555,118 @@ impl<R: gfx::Resources> SpriteSheetBuilder<R> {
     }
 }
 
-pub fn create<R, C, F, D>(image: RgbaImage, input_sprites: Vec<InputSprite>,
+fn create_flat_normal_map<R, F>(factory: &mut F) -> gfx::handle::ShaderResourceView<R, [f32; 4]>
+    where R: gfx::Resources,
+          F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+{
+    let tex_kind = gfx::texture::Kind::D2(1, 1, gfx::texture::AaMode::Single);
+    let (_, srv) = factory.create_texture_immutable_u8::<NormalMapFormat>(tex_kind, &[&FLAT_NORMAL])
+        .expect("Failed to create texture");
+    srv
+}
+
+// the normal map is optional, so a missing file isn't an error. its alpha is ignored, as the sprite sheet
+// decides which pixels belong to sprites.
+pub fn load_normal_map(path: &Path) -> Result<Option<RgbaImage>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    image::open(path)
+        .map(|image| Some(image.to_rgba()))
+        .map_err(|e| format!("Failed to open normal map {}: {}", path.display(), e))
+}
+
+fn check_normal_map(image: &RgbaImage, normal_map: Option<&RgbaImage>) -> Result<(), String> {
+    if let Some(normal_map) = normal_map {
+        if normal_map.dimensions() != image.dimensions() {
+            return Err(format!("Normal map is {:?} but sprite sheet is {:?}",
+                               normal_map.dimensions(), image.dimensions()));
+        }
+    }
+    Ok(())
+}
+
+// the manifest describes where each sprite is in the image
+fn load_input_sprites(image: &RgbaImage, manifest_path: &Path) -> Result<Vec<InputSprite>, String> {
+    input_sprite::load(manifest_path, Vector2::new(image.width(), image.height()))
+        .map_err(|e| format!("Failed to load sprite manifest {}: {}", manifest_path.display(), e))
+}
+
+pub fn create<R, C, F, D>(image: RgbaImage, normal_map: Option<RgbaImage>, manifest_path: &Path,
                           factory: &mut F, encoder: &mut gfx::Encoder<R, C>,
-                          device: &mut D) -> (SpriteSheetTexture<R>, TileSpriteTable, FieldUiSpriteTable)
+                          device: &mut D) -> Result<(SpriteSheetTexture<R>, TileSpriteTable, FieldUiSpriteTable), String>
     where R: gfx::Resources,
           C: gfx::CommandBuffer<R>,
           F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
           D: gfx::traits::Device<Resources=R, CommandBuffer=C>,
 {
-    let mut builder = SpriteSheetBuilder::new(image, input_sprites, factory);
+    check_normal_map(&image, normal_map.as_ref())?;
+    let input_sprites = load_input_sprites(&image, manifest_path)?;
+    let mut builder = SpriteSheetBuilder::new(image, normal_map, input_sprites, factory);
     builder.populate(factory);
     builder.draw(encoder, device);
-    builder.build()
+    Ok(builder.build())
+}
+
+// without a normal map, the packed normal sheet is flat
+pub fn create_image(image: &RgbaImage, normal_map: Option<&RgbaImage>, manifest_path: &Path)
+    -> (RgbaImage, RgbaImage, TileSpriteTable, FieldUiSpriteTable)
+{
+    let input_sprites = check_normal_map(image, normal_map)
+        .and_then(|_| load_input_sprites(image, manifest_path))
+        .unwrap_or_else(|e| panic!("{}", e));
+    let layout = SpriteSheetLayout::new(&input_sprites);
+    let sprite_sheet = layout.draw_image(image, image, Rgba { data: [0, 0, 0, 0] });
+    let flat = Rgba { data: FLAT_NORMAL };
+    let normal_sheet = if let Some(normal_map) = normal_map {
+        layout.draw_image(normal_map, image, flat)
+    } else {
+        RgbaImage::from_pixel(layout.width, layout.height, flat)
+    };
+    let SpriteSheetLayout { tile_sprite_table, field_ui_sprite_table, .. } = layout;
+
+    (sprite_sheet, normal_sheet, TileSpriteTable(tile_sprite_table), FieldUiSpriteTable(field_ui_sprite_table))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use res::files;
+
+    fn res_dir() -> PathBuf {
+        PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src/res"))
+    }
+
+    // a normal map facing east everywhere, and fully transparent to show its alpha is ignored
+    const EAST_NORMAL: [u8; 4] = [255, 128, 128, 0];
+
+    #[test]
+    fn normal_map_follows_sprite_sheet() {
+        let image = image::open(res_dir().join(files::SPRITE_SHEET)).expect("Failed to open sprite sheet").to_rgba();
+        let normal_map = RgbaImage::from_pixel(image.width(), image.height(), Rgba { data: EAST_NORMAL });
+
+        let (sprite_sheet, normal_sheet, _, _) =
+            create_image(&image, Some(&normal_map), &res_dir().join(files::SPRITE_MANIFEST));
+        assert_eq!(sprite_sheet.dimensions(), normal_sheet.dimensions());
+
+        let mut num_sprite_pixels = 0;
+        for (x, y, pixel) in sprite_sheet.enumerate_pixels() {
+            let normal = normal_sheet.get_pixel(x, y).data;
+            if pixel.data[3] == 0 {
+                assert_eq!(normal, FLAT_NORMAL, "Pixel ({}, {}) isn't part of a sprite", x, y);
+            } else {
+                assert_eq!(normal[..3], EAST_NORMAL[..3], "Pixel ({}, {}) is part of a sprite", x, y);
+                num_sprite_pixels += 1;
+            }
+        }
+        assert!(num_sprite_pixels > 0);
+    }
+
+    #[test]
+    fn missing_normal_map_is_flat() {
+        let image = image::open(res_dir().join(files::SPRITE_SHEET)).expect("Failed to open sprite sheet").to_rgba();
+        let (_, normal_sheet, _, _) = create_image(&image, None, &res_dir().join(files::SPRITE_MANIFEST));
+        assert!(normal_sheet.pixels().all(|pixel| pixel.data == FLAT_NORMAL));
+    }
 }
@@ -1,25 +1,82 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use handlebars::Handlebars;
 use toml::Value;
 
-use renderer::sizes;
+use renderer::{sizes, shading};
 use renderer::tile_renderer::instance_flags;
+use renderer::field_ui::field_ui_flags;
+use renderer::minimap::minimap_flags;
 use content::{DepthType, SpriteEffect};
+use direction::{Direction, DirectionBitmap};
+use simple_file;
+
+// reads a shader by name, from the source tree if the source says so, otherwise from the binary
+macro_rules! shader {
+    ($source:expr, $file_name:expr) => {
+        $source.read($file_name, include_bytes!(concat!("shaders/", $file_name)))
+    }
+}
 
 macro_rules! include_shader_part {
-    ($table:expr, $handlebars:expr, $key:expr, $file_name:expr) => {
+    ($table:expr, $handlebars:expr, $source:expr, $key:expr, $file_name:expr) => {
         {
-            let bytes = include_bytes!(concat!("shaders/", $file_name));
-            let shader_str = ::std::str::from_utf8(bytes)
-                .expect("Failed to convert part to utf8");
-            let expanded = $handlebars.template_render(shader_str, &$table)
-                .expect("Failed to render part template");
+            let shader_str = shader!($source, $file_name)?;
+            let expanded = $handlebars.template_render(shader_str.as_ref(), &$table)
+                .map_err(|e| format!("Failed to render part template {}: {:?}", $file_name, e))?;
             $table.insert($key, Value::String(expanded));
         }
     }
 }
 
-pub fn make_shader_template_context() -> (Handlebars, HashMap<&'static str, Value>) {
+// where shaders are read from
+#[derive(Debug, Clone)]
+pub struct ShaderSource {
+    dir: Option<PathBuf>,
+}
+
+impl ShaderSource {
+    // the shaders the binary was built with
+    pub fn embedded() -> Self {
+        Self {
+            dir: None,
+        }
+    }
+
+    // the shaders in the source tree, so changes show up without rebuilding
+    pub fn source_tree() -> Self {
+        Self {
+            dir: Some(PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src/renderer/shaders"))),
+        }
+    }
+
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_ref().map(|dir| dir.as_path())
+    }
+
+    pub fn read(&self, file_name: &str, embedded: &'static [u8]) -> Result<String, String> {
+        if let Some(ref dir) = self.dir {
+            let path = dir.join(file_name);
+            simple_file::read_string(&path)
+                .map_err(|e| format!("Failed to read shader {}: {:?}", path.display(), e))
+        } else {
+            ::std::str::from_utf8(embedded)
+                .map(|s| s.to_string())
+                .map_err(|_| format!("Failed to convert shader {} to utf8", file_name))
+        }
+    }
+}
+
+fn glsl_float(value: f32) -> Value {
+    // debug formatting always includes a decimal point, so glsl reads it as a float
+    Value::String(format!("{:?}", value))
+}
+
+fn glsl_vec3(value: [f32; 3]) -> Value {
+    Value::String(format!("vec3({:?}, {:?}, {:?})", value[0], value[1], value[2]))
+}
+
+pub fn make_shader_template_context(source: &ShaderSource) -> Result<(Handlebars, HashMap<&'static str, Value>), String> {
     let handlebars = {
         let mut h = Handlebars::new();
         h.register_escape_fn(|input| input.to_string());
@@ -30,29 +87,50 @@ pub fn make_shader_template_context() -> (Handlebars, HashMap<&'static str, Valu
     let mut table = hashmap!{
         "FLAGS_ENABLED" => Integer(instance_flags::ENABLED as i64),
         "FLAGS_SPRITE_EFFECT" => Integer(instance_flags::SPRITE_EFFECT as i64),
+        "FLAGS_TRANSLUCENT" => Integer(instance_flags::TRANSLUCENT as i64),
+        "FIELD_UI_FLAGS_IGNORE_VISION" => Integer(field_ui_flags::IGNORE_VISION as i64),
+        "MINIMAP_FLAGS_HIDE_UNSEEN" => Integer(minimap_flags::HIDE_UNSEEN as i64),
+        "MINIMAP_FLAGS_HIDE_INVISIBLE" => Integer(minimap_flags::HIDE_INVISIBLE as i64),
         "DEPTH_FIXED" => Integer(DepthType::Fixed as i64),
         "DEPTH_GRADIENT" => Integer(DepthType::Gradient as i64),
         "DEPTH_BOTTOM" => Integer(DepthType::Bottom as i64),
         "MAX_CELL_TABLE_SIZE" => Integer(sizes::MAX_CELL_TABLE_SIZE as i64),
         "SPRITE_EFFECT_WATER" => Integer(SpriteEffect::Water as i64),
+        "SPRITE_EFFECT_SMOKE" => Integer(SpriteEffect::Smoke as i64),
+        "SPRITE_EFFECT_HEAT_SHIMMER" => Integer(SpriteEffect::HeatShimmer as i64),
+        "SPRITE_EFFECT_GLOW" => Integer(SpriteEffect::Glow as i64),
+        "SPRITE_EFFECT_DISSOLVE" => Integer(SpriteEffect::Dissolve as i64),
         "MAX_NUM_LIGHTS" => Integer(sizes::MAX_NUM_LIGHTS as i64),
         "TBO_VISION_ENTRY_SIZE" => Integer(sizes::TBO_VISION_ENTRY_SIZE as i64),
         "TBO_VISION_BITMAP_OFFSET" => Integer(sizes::TBO_VISION_BITMAP_OFFSET as i64),
+        "TBO_VISION_FILTER_OFFSET" => Integer(sizes::TBO_VISION_FILTER_OFFSET as i64),
         "TBO_VISION_BUFFER_SIZE" => Integer(sizes::TBO_VISION_BUFFER_SIZE as i64),
+        "DIRECTION_NORTH" => Integer(Direction::North.bitmap().raw as i64),
+        "DIRECTION_EAST" => Integer(Direction::East.bitmap().raw as i64),
+        "DIRECTION_SOUTH" => Integer(Direction::South.bitmap().raw as i64),
+        "DIRECTION_WEST" => Integer(Direction::West.bitmap().raw as i64),
+        "DIRECTION_NORTH_EAST" => Integer(Direction::NorthEast.bitmap().raw as i64),
+        "DIRECTION_SOUTH_EAST" => Integer(Direction::SouthEast.bitmap().raw as i64),
+        "DIRECTION_SOUTH_WEST" => Integer(Direction::SouthWest.bitmap().raw as i64),
+        "DIRECTION_NORTH_WEST" => Integer(Direction::NorthWest.bitmap().raw as i64),
+        "DIRECTION_ALL" => Integer(DirectionBitmap::all().raw as i64),
+        "SIDE_EDGE_WIDTH" => glsl_float(shading::SIDE_EDGE_WIDTH),
+        "SMOKE_COLOUR" => glsl_vec3(shading::SMOKE_COLOUR),
+        "SMOKE_MAX_ALPHA" => glsl_float(shading::SMOKE_MAX_ALPHA),
+        "DISSOLVE_EDGE_COLOUR" => glsl_vec3(shading::DISSOLVE_EDGE_COLOUR),
+        "DISSOLVE_EDGE_WIDTH" => glsl_float(shading::DISSOLVE_EDGE_WIDTH),
     };
 
-    include_shader_part!(table, handlebars, "INCLUDE_VISION", "vision.150.hbs.comp");
-    include_shader_part!(table, handlebars, "INCLUDE_DIMENSIONS", "dimensions.150.hbs.comp");
-    include_shader_part!(table, handlebars, "INCLUDE_SCROLL_OFFSET", "scroll_offset.150.hbs.comp");
-    include_shader_part!(table, handlebars, "INCLUDE_POSITIONS", "positions.150.hbs.comp");
+    include_shader_part!(table, handlebars, source, "INCLUDE_VISION", "vision.150.hbs.comp");
+    include_shader_part!(table, handlebars, source, "INCLUDE_DIMENSIONS", "dimensions.150.hbs.comp");
+    include_shader_part!(table, handlebars, source, "INCLUDE_SCROLL_OFFSET", "scroll_offset.150.hbs.comp");
+    include_shader_part!(table, handlebars, source, "INCLUDE_POSITIONS", "positions.150.hbs.comp");
+    include_shader_part!(table, handlebars, source, "INCLUDE_AMBIENT_LIGHT", "ambient_light.150.hbs.comp");
 
-    (handlebars, table)
+    Ok((handlebars, table))
 }
 
-pub fn populate_shader(handlebars: &Handlebars, table: &HashMap<&'static str, Value>, shader: &[u8]) -> String {
-    let shader_str = ::std::str::from_utf8(shader)
-        .expect("Failed to convert shader to utf8");
-
-    handlebars.template_render(shader_str, table)
-        .expect("Failed to render shader template")
+pub fn populate_shader(handlebars: &Handlebars, table: &HashMap<&'static str, Value>, shader: &str) -> Result<String, String> {
+    handlebars.template_render(shader, table)
+        .map_err(|e| format!("Failed to render shader template: {:?}", e))
 }
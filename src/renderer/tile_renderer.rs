@@ -2,6 +2,7 @@ use std::time::Duration;
 use gfx;
 
 use cgmath::{Vector2, ElementWise};
+use toml::Value;
 
 use renderer::sprite_sheet::{SpriteSheetTexture, TileSpriteTable, SpriteResolution};
 use renderer::formats::{ColourFormat, DepthFormat};
@@ -12,18 +13,22 @@ use renderer::sizes;
 use renderer::dimensions::{Dimensions, FixedDimensions, OutputDimensions, WorldDimensions};
 use renderer::vision_buffer::VisionBuffer;
 use renderer::frame_info::{FrameInfo, FrameInfoBuffer};
-use renderer::template;
+use renderer::template::{self, ShaderSource};
 use renderer::scroll_offset::{ScrollOffset, ScrollOffsetBuffer};
+use renderer::minimap::MinimapTerrain;
+use renderer::particles::{ParticleRenderer, ParticleWorldState};
+use renderer::ambient_light::{self, AmbientLight};
 
 use direction::{Direction, DirectionBitmap};
-use content::{TileSprite, DepthType, DepthInfo};
+use content::{TileSprite, DepthType, DepthInfo, AmbientLightInfo, OutlineInfo, FlashInfo};
 use entity_store::{EntityStore, EntityChange};
 use spatial_hash::SpatialHashTable;
 use vision::VisionGrid;
 
-use frontend::{OutputWorldState, LightUpdate};
+use frontend::{self, OutputWorldState, LightUpdate};
 use res::input_sprite;
 use util::time::duration_millis;
+use camera::{Camera, CameraFocus};
 
 gfx_vertex_struct!( Vertex {
     pos: [f32; 2] = "a_Pos",
@@ -40,6 +45,9 @@ gfx_vertex_struct!( Instance {
     sprite_effect: u32 = "a_SpriteEffect",
     sprite_effect_args: [f32; 4] = "a_SpriteEffectArgs",
     hide_in_dark: u32 = "a_HideInDark",
+    emissive: f32 = "a_Emissive",
+    outline_colour: [f32; 4] = "a_OutlineColour",
+    flash: [f32; 4] = "a_Flash",
 });
 
 gfx_constant_struct!( Light {
@@ -51,6 +59,8 @@ gfx_pipeline!( pipe {
     vision_table: gfx::ShaderResource<u8> = "t_VisionTable",
     light_table: gfx::ShaderResource<u8> = "t_LightTable",
     light_list: gfx::ConstantBuffer<Light> = "LightList",
+    sky_table: gfx::ShaderResource<u8> = "t_SkyTable",
+    ambient_light: gfx::ConstantBuffer<AmbientLight> = "AmbientLight",
     fixed_dimensions: gfx::ConstantBuffer<FixedDimensions> = "FixedDimensions",
     output_dimensions: gfx::ConstantBuffer<OutputDimensions> = "OutputDimensions",
     world_dimensions: gfx::ConstantBuffer<WorldDimensions> = "WorldDimensions",
@@ -59,6 +69,7 @@ gfx_pipeline!( pipe {
     vertex: gfx::VertexBuffer<Vertex> = (),
     instance: gfx::InstanceBuffer<Instance> = (),
     tex: gfx::TextureSampler<[f32; 4]> = "t_Texture",
+    normal_map: gfx::TextureSampler<[f32; 4]> = "t_NormalMap",
     out_colour: gfx::BlendTarget<ColourFormat> = ("Target0", gfx::state::ColorMask::all(), gfx::preset::blend::ALPHA),
     out_depth: gfx::DepthTarget<DepthFormat> = gfx::preset::depth::LESS_EQUAL_WRITE,
 });
@@ -66,6 +77,7 @@ gfx_pipeline!( pipe {
 pub mod instance_flags {
     pub const ENABLED: u32 = 1 << 0;
     pub const SPRITE_EFFECT: u32 = 1 << 1;
+    pub const TRANSLUCENT: u32 = 1 << 2;
 }
 
 impl Default for Instance {
@@ -83,16 +95,20 @@ impl Default for Instance {
             sprite_effect: 0,
             sprite_effect_args: [0.0, 0.0, 0.0, 0.0],
             hide_in_dark: 0,
+            emissive: 0.0,
+            outline_colour: [0.0, 0.0, 0.0, 0.0],
+            flash: [0.0, 0.0, 0.0, 0.0],
         }
     }
 }
 
 impl Instance {
     pub fn update_sprite_info(&mut self, sprite_info: SpriteRenderInfo) {
-        let SpriteRenderInfo { position, size, offset, .. } = sprite_info;
+        let SpriteRenderInfo { position, size, offset, emissive, .. } = sprite_info;
         self.sprite_sheet_pix_coord = position;
         self.pix_size = size;
         self.pix_offset = offset;
+        self.emissive = emissive;
     }
 
     pub fn update_depth(&mut self, y_position: f32, max_y_position: f32, depth: DepthInfo) {
@@ -114,21 +130,36 @@ impl Instance {
             }
         }
     }
+
+    // the alpha marks whether the instance is outlined at all
+    pub fn update_outline(&mut self, outline: OutlineInfo) {
+        self.outline_colour = [outline.colour[0], outline.colour[1], outline.colour[2], 1.0];
+    }
+
+    pub fn update_flash(&mut self, flash: FlashInfo) {
+        self.flash = [flash.colour[0], flash.colour[1], flash.colour[2], flash.amount];
+    }
 }
 
 pub struct TileRenderer<R: gfx::Resources> {
     bundle: gfx::pso::bundle::Bundle<R, pipe::Data<R>>,
+    translucent_pso: gfx::pso::PipelineState<R, pipe::Meta>,
+    particles: ParticleRenderer<R>,
     instance_upload: gfx::handle::Buffer<R, Instance>,
     vision_upload: gfx::handle::Buffer<R, u8>,
     light_upload: gfx::handle::Buffer<R, u8>,
     light_list_upload: gfx::handle::Buffer<R, Light>,
+    sky_upload: gfx::handle::Buffer<R, u8>,
     vision_buffer: gfx::handle::Buffer<R, u8>,
     light_buffer: gfx::handle::Buffer<R, u8>,
+    sky_buffer: gfx::handle::Buffer<R, u8>,
     sprite_table: TileSpriteTable,
     num_instances: usize,
     num_cells: usize,
+    // set when the world changes size, so the sky table left by the old world is cleared
+    clear_sky: bool,
     instance_manager: InstanceManager,
-    mid_position: Vector2<f32>,
+    camera: Camera,
     world_width: u32,
     world_height: u32,
 }
@@ -137,6 +168,7 @@ pub struct SpriteRenderInfo {
     pub position: [f32; 2],
     pub size: [f32; 2],
     pub offset: [f32; 2],
+    pub emissive: f32,
     pub wall_info: Option<WallSpriteRenderInfo>,
 }
 
@@ -165,14 +197,14 @@ impl SpriteRenderInfo {
     pub fn resolve(sprite: TileSprite, sprite_table: &TileSpriteTable,
                position: Vector2<f32>, spatial_hash: &SpatialHashTable) -> Option<Self> {
         if let Some(sprite_resolution) = sprite_table.get(sprite) {
-            let (position_x, size, offset, wall_info) = match sprite_resolution {
+            let (position_x, size, offset, emissive, wall_info) = match sprite_resolution {
                 &SpriteResolution::Simple(location) => {
-                    (location.position, location.size, location.offset, None)
+                    (location.position, location.size, location.offset, location.emissive, None)
                 }
                 &SpriteResolution::Wall(location) => {
                     if let Some(sh_cell) = spatial_hash.get_float(position) {
                         let bitmap = sh_cell.wall_neighbours.bitmap_raw();
-                        (location.position(bitmap), *location.size(), *location.offset(), Some(WallSpriteRenderInfo {
+                        (location.position(bitmap), *location.size(), *location.offset(), location.emissive(), Some(WallSpriteRenderInfo {
                             base_x: location.base(),
                             size: location.size().x,
                         }))
@@ -187,7 +219,7 @@ impl SpriteRenderInfo {
                         } else {
                             front
                         };
-                        (location.position, location.size, location.offset, None)
+                        (location.position, location.size, location.offset, location.emissive, None)
                     } else {
                         return None;
                     }
@@ -198,6 +230,7 @@ impl SpriteRenderInfo {
                 position: [position_x, 0.0],
                 size: size.into(),
                 offset: offset.into(),
+                emissive,
                 wall_info,
             });
         }
@@ -210,6 +243,7 @@ impl SpriteRenderInfo {
             position: [0.0, 0.0],
             size: [input_sprite::WIDTH_PX as f32, input_sprite::HEIGHT_PX as f32],
             offset: [0.0, 0.0],
+            emissive: 0.0,
             wall_info: None,
         }
     }
@@ -223,15 +257,11 @@ impl<R: gfx::Resources> TileRenderer<R> {
                   vision_buffer: &VisionBuffer<R>,
                   frame_info_buffer: &FrameInfoBuffer<R>,
                   scroll_offset_buffer: &ScrollOffsetBuffer<R>,
+                  shader_source: &ShaderSource,
                   factory: &mut F) -> Self
         where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
     {
-        let (handlebars, context) = template::make_shader_template_context();
-
-        let pso = factory.create_pipeline_simple(
-            template::populate_shader(&handlebars, &context, include_bytes!("shaders/tile_renderer.150.hbs.vert")).as_bytes(),
-            template::populate_shader(&handlebars, &context, include_bytes!("shaders/tile_renderer.150.hbs.frag")).as_bytes(),
-            pipe::new()).expect("Failed to create pipeline");
+        let (pso, translucent_pso) = Self::create_psos(shader_source, factory).expect("Failed to create pipeline");
 
         let vertex_data: Vec<Vertex> = common::QUAD_VERTICES_REFL.iter()
             .map(|v| {
@@ -258,10 +288,33 @@ impl<R: gfx::Resources> TileRenderer<R> {
         let light_list = common::create_transfer_dst_buffer(sizes::MAX_NUM_LIGHTS, factory)
             .expect("Failed to create light list");
 
+        let sky_buffer = common::create_transfer_dst_buffer(sizes::MAX_CELL_TABLE_SIZE, factory)
+            .expect("Failed to create sky buffer");
+
+        let sky_buffer_srv = factory.view_buffer_as_shader_resource(&sky_buffer)
+            .expect("Failed to view sky buffer as shader resource");
+
+        let ambient_light_buffer = ambient_light::create_buffer(factory);
+
+        // particles are lit by the same lights as tiles
+        let particles = ParticleRenderer::new(target,
+                                              dimensions,
+                                              vision_buffer,
+                                              &light_buffer_srv,
+                                              &light_list,
+                                              &sky_buffer_srv,
+                                              &ambient_light_buffer,
+                                              frame_info_buffer,
+                                              scroll_offset_buffer,
+                                              shader_source,
+                                              factory);
+
         let data = pipe::Data {
             vision_table: vision_buffer.srv.clone(),
             light_table: light_buffer_srv,
             light_list,
+            sky_table: sky_buffer_srv,
+            ambient_light: ambient_light_buffer,
             fixed_dimensions: dimensions.fixed_dimensions.clone(),
             output_dimensions: dimensions.output_dimensions.clone(),
             world_dimensions: dimensions.world_dimensions.clone(),
@@ -272,11 +325,14 @@ impl<R: gfx::Resources> TileRenderer<R> {
                 .expect("Failed to create instance buffer"),
             out_colour: target.rtv.clone(),
             out_depth: target.dsv.clone(),
-            tex: (sprite_sheet.srv.clone(), sampler),
+            tex: (sprite_sheet.srv.clone(), sampler.clone()),
+            normal_map: (sprite_sheet.normal_srv.clone(), sampler),
         };
 
         let ret = Self {
             bundle: gfx::pso::bundle::Bundle::new(slice, pso, data),
+            translucent_pso,
+            particles,
             instance_upload: factory.create_upload_buffer(sizes::MAX_NUM_INSTANCES)
                 .expect("Failed to create upload buffer"),
             vision_upload: factory.create_upload_buffer(sizes::TBO_VISION_BUFFER_SIZE)
@@ -285,19 +341,69 @@ impl<R: gfx::Resources> TileRenderer<R> {
                 .expect("Failed to create upload buffer"),
             light_list_upload: factory.create_upload_buffer(sizes::MAX_NUM_LIGHTS)
                 .expect("Failed to create upload buffer"),
+            sky_upload: factory.create_upload_buffer(sizes::MAX_CELL_TABLE_SIZE)
+                .expect("Failed to create upload buffer"),
             vision_buffer: vision_buffer.buffer.clone(),
             light_buffer,
+            sky_buffer,
             sprite_table,
             num_instances: 0,
             num_cells: 0,
+            clear_sky: false,
             instance_manager: InstanceManager::new(),
-            mid_position: Vector2::new(0.0, 0.0),
+            camera: Camera::new(),
             world_width: 0,
             world_height: 0,
         };
         ret
     }
 
+    // translucent instances are drawn in a second pass, after everything they could be in front of,
+    // and without writing depth so they don't hide anything drawn behind them
+    fn create_psos<F>(shader_source: &ShaderSource, factory: &mut F)
+        -> Result<(gfx::pso::PipelineState<R, pipe::Meta>, gfx::pso::PipelineState<R, pipe::Meta>), String>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let (handlebars, mut context) = template::make_shader_template_context(shader_source)?;
+        let vert_template = shader!(shader_source, "tile_renderer.150.hbs.vert")?;
+        let frag_template = shader!(shader_source, "tile_renderer.150.hbs.frag")?;
+
+        context.insert("TRANSLUCENT_PASS", Value::Integer(0));
+        let vert = template::populate_shader(&handlebars, &context, vert_template.as_ref())?;
+        let frag = template::populate_shader(&handlebars, &context, frag_template.as_ref())?;
+        let pso = factory.create_pipeline_simple(vert.as_bytes(), frag.as_bytes(), pipe::new())
+            .map_err(|e| format!("Failed to create tile renderer pipeline: {:?}", e))?;
+
+        context.insert("TRANSLUCENT_PASS", Value::Integer(1));
+        let vert = template::populate_shader(&handlebars, &context, vert_template.as_ref())?;
+        let frag = template::populate_shader(&handlebars, &context, frag_template.as_ref())?;
+        let translucent_init = pipe::Init {
+            out_depth: gfx::preset::depth::LESS_EQUAL_TEST,
+            .. pipe::new()
+        };
+        let translucent_pso = factory.create_pipeline_simple(vert.as_bytes(), frag.as_bytes(), translucent_init)
+            .map_err(|e| format!("Failed to create translucent tile renderer pipeline: {:?}", e))?;
+
+        Ok((pso, translucent_pso))
+    }
+
+    // leaves the current pipeline in place if the new one fails to build
+    pub fn reload_shaders<F>(&mut self, shader_source: &ShaderSource, factory: &mut F) -> Result<(), String>
+        where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
+    {
+        let (pso, translucent_pso) = Self::create_psos(shader_source, factory)?;
+        self.particles.reload_shaders(shader_source, factory)?;
+        self.bundle.pso = pso;
+        self.translucent_pso = translucent_pso;
+        Ok(())
+    }
+
+    // the sprite sheet must have the same layout as the one it replaces
+    pub fn set_sprite_sheet(&mut self, sprite_sheet: &SpriteSheetTexture<R>) {
+        self.bundle.data.tex.0 = sprite_sheet.srv.clone();
+        self.bundle.data.normal_map.0 = sprite_sheet.normal_srv.clone();
+    }
+
     pub fn clear<C>(&self, encoder: &mut gfx::Encoder<R, C>)
         where C: gfx::CommandBuffer<R>,
     {
@@ -316,10 +422,15 @@ impl<R: gfx::Resources> TileRenderer<R> {
             .expect("Failed to copy light info");
         encoder.copy_buffer(&self.light_list_upload, &self.bundle.data.light_list, 0, 0, sizes::MAX_NUM_LIGHTS)
             .expect("Failed to copy light info");
+        encoder.copy_buffer(&self.sky_upload, &self.sky_buffer, 0, 0, self.num_cells)
+            .expect("Failed to copy sky info");
         encoder.draw(&self.bundle.slice, &self.bundle.pso, &self.bundle.data);
+        self.particles.draw(encoder);
+        encoder.draw(&self.bundle.slice, &self.translucent_pso, &self.bundle.data);
     }
 
-    pub fn world_state<F>(&mut self, target: &RenderTarget<R>, factory: &mut F) -> RendererWorldState<R>
+    pub fn world_state<'a, F>(&'a mut self, target: &RenderTarget<R>, minimap: &'a mut MinimapTerrain,
+                              factory: &mut F) -> RendererWorldState<'a, R>
         where F: gfx::Factory<R> + gfx::traits::FactoryExt<R>,
     {
         let instance_writer = factory.write_mapping(&self.instance_upload)
@@ -334,22 +445,36 @@ impl<R: gfx::Resources> TileRenderer<R> {
         let light_writer = factory.write_mapping(&self.light_list_upload)
             .expect("Failed to map upload buffer");
 
+        let mut sky_writer = factory.write_mapping(&self.sky_upload)
+            .expect("Failed to map upload buffer");
+        if self.clear_sky {
+            for cell in sky_writer[..self.num_cells].iter_mut() {
+                *cell = 0;
+            }
+            self.clear_sky = false;
+        }
+
         RendererWorldState {
             instance_writer,
             vision_writer,
             light_grid_writer,
             light_writer,
+            sky_writer,
             world_width: self.world_width,
+            world_height: self.world_height,
             bundle: &mut self.bundle,
             sprite_table: &self.sprite_table,
             instance_manager: &mut self.instance_manager,
+            particles: self.particles.world_state(factory),
             num_instances: &mut self.num_instances,
             player_position: None,
             width_px: target.width,
             height_px: target.height,
-            mid_position: &mut self.mid_position,
+            camera: &mut self.camera,
+            minimap,
             frame_count: 0,
             total_time_ms: 0,
+            ambient_light: None,
             next_light_index: 0,
             num_rows: target.num_rows,
         }
@@ -360,13 +485,21 @@ impl<R: gfx::Resources> TileRenderer<R> {
     {
         self.bundle.data.out_colour = target.rtv.clone();
         self.bundle.data.out_depth = target.dsv.clone();
+        self.particles.handle_resize(target);
 
-        let scroll_offset = compute_scroll_offset(target.width, target.height, self.mid_position);
+        let scroll_offset = compute_scroll_offset(target.width, target.height, self.camera.position());
         encoder.update_constant_buffer(&self.bundle.data.scroll_offset, &ScrollOffset {
             scroll_offset_pix: scroll_offset.into(),
         });
     }
 
+    pub fn target_to_world(&self, target: &RenderTarget<R>, target_px: Vector2<f32>) -> Vector2<i32> {
+        let scroll_offset = compute_scroll_offset(target.width, target.height, self.camera.position());
+        let world_px = target_px + scroll_offset;
+        Vector2::new((world_px.x / input_sprite::WIDTH_PX as f32).floor() as i32,
+                     (world_px.y / input_sprite::HEIGHT_PX as f32).floor() as i32)
+    }
+
     pub fn update_world_size(&mut self, width: u32, height: u32) {
         let num_cells = (width * height) as usize;
 
@@ -377,6 +510,7 @@ impl<R: gfx::Resources> TileRenderer<R> {
         self.num_cells = num_cells;
         self.world_width = width;
         self.world_height = height;
+        self.clear_sky = true;
     }
 }
 
@@ -385,17 +519,23 @@ pub struct RendererWorldState<'a, R: gfx::Resources> {
     vision_writer: gfx::mapping::Writer<'a, R, u8>,
     light_grid_writer: gfx::mapping::Writer<'a, R, u8>,
     light_writer: gfx::mapping::Writer<'a, R, Light>,
+    // cells open to the sky are 1, and other cells are 0
+    sky_writer: gfx::mapping::Writer<'a, R, u8>,
     world_width: u32,
+    world_height: u32,
     bundle: &'a mut gfx::pso::bundle::Bundle<R, pipe::Data<R>>,
     sprite_table: &'a TileSpriteTable,
     instance_manager: &'a mut InstanceManager,
+    particles: ParticleWorldState<'a, R>,
     num_instances: &'a mut usize,
     player_position: Option<Vector2<f32>>,
-    mid_position: &'a mut Vector2<f32>,
+    camera: &'a mut Camera,
+    minimap: &'a mut MinimapTerrain,
     width_px: u16,
     height_px: u16,
     frame_count: u64,
     total_time_ms: u64,
+    ambient_light: Option<AmbientLight>,
     next_light_index: usize,
     num_rows: u16,
 }
@@ -408,6 +548,18 @@ impl<'a, 'b, R: gfx::Resources> OutputWorldState<'a, 'b> for RendererWorldState<
 
     fn update(&mut self, change: &EntityChange, entity_store: &EntityStore, spatial_hash: &SpatialHashTable) {
         self.instance_manager.update(&mut self.instance_writer, change, entity_store, spatial_hash, self.sprite_table);
+        self.particles.update(change);
+        self.camera.update_focus(change);
+        self.minimap.update(change, entity_store);
+        let world_width = self.world_width;
+        let world_height = self.world_height;
+        let sky_writer = &mut self.sky_writer;
+        frontend::sky_change(change, entity_store, spatial_hash, |coord, open| {
+            if coord.x >= 0 && coord.y >= 0 && (coord.x as u32) < world_width && (coord.y as u32) < world_height {
+                let index = (coord.y as u32 * world_width + coord.x as u32) as usize;
+                sky_writer[index] = open as u8;
+            }
+        });
     }
 
     fn set_player_position(&mut self, player_position: Vector2<f32>) {
@@ -419,6 +571,14 @@ impl<'a, 'b, R: gfx::Resources> OutputWorldState<'a, 'b> for RendererWorldState<
         self.total_time_ms = duration_millis(total_time);
     }
 
+    fn set_ambient_light(&mut self, ambient: AmbientLightInfo, sky: AmbientLightInfo) {
+        self.ambient_light = Some(AmbientLight::new(ambient, sky));
+    }
+
+    fn focus_camera(&mut self, focus: CameraFocus, entity_store: &EntityStore, duration: Duration) {
+        self.camera.focus(focus, entity_store, duration);
+    }
+
     fn vision_grid(&'b mut self) -> Self::VisionCellGrid {
         TboVisionGrid {
             slice: &mut self.vision_writer,
@@ -453,17 +613,27 @@ impl<'a, R: gfx::Resources> RendererWorldState<'a, R> {
         self.bundle.slice.instances = Some((num_instances, 0));
 
         if let Some(player_position) = self.player_position {
-            *self.mid_position = player_position;
-            let scroll_offset = compute_scroll_offset(self.width_px, self.height_px, player_position);
-            encoder.update_constant_buffer(&self.bundle.data.scroll_offset, &ScrollOffset {
-                scroll_offset_pix: scroll_offset.into(),
-            });
+            self.camera.set_target(player_position);
+            self.minimap.set_player_position(player_position);
         }
+
+        let view_size = Vector2::new(self.width_px as f32 / input_sprite::WIDTH_PX as f32,
+                                     self.height_px as f32 / input_sprite::HEIGHT_PX as f32);
+        let world_size = Vector2::new(self.world_width, self.world_height).cast();
+        let mid_position = self.camera.update(self.total_time_ms, view_size, world_size);
+        let scroll_offset = compute_scroll_offset(self.width_px, self.height_px, mid_position);
+        encoder.update_constant_buffer(&self.bundle.data.scroll_offset, &ScrollOffset {
+            scroll_offset_pix: scroll_offset.into(),
+        });
         FrameInfo::update(&self.bundle.data.frame_info, self.frame_count, self.total_time_ms, self.next_light_index, encoder);
+        if let Some(ambient_light) = self.ambient_light {
+            encoder.update_constant_buffer(&self.bundle.data.ambient_light, &ambient_light);
+        }
+        self.particles.finalise(self.total_time_ms);
     }
 }
 
-fn compute_scroll_offset(width: u16, height: u16, mid_position: Vector2<f32>) -> Vector2<f32> {
+pub fn compute_scroll_offset(width: u16, height: u16, mid_position: Vector2<f32>) -> Vector2<f32> {
     let mid = (mid_position + Vector2::new(0.5, 0.5))
         .mul_element_wise(Vector2::new(input_sprite::WIDTH_PX, input_sprite::HEIGHT_PX).cast());
     Vector2::new(mid.x - (width / 2) as f32, mid.y - (height / 2) as f32)
@@ -472,12 +642,15 @@ fn compute_scroll_offset(width: u16, height: u16, mid_position: Vector2<f32>) ->
 struct TboVisionCell<'a>(&'a mut [u8]);
 
 impl<'a> TboVisionCell<'a> {
-    fn see(&mut self, bitmap: DirectionBitmap, mut time: u64) {
+    fn see(&mut self, bitmap: DirectionBitmap, filter: [f32; 3], mut time: u64) {
         for i in 0..sizes::TBO_VISION_FRAME_COUNT_SIZE {
             self.0[i] = time as u8;
             time >>= 8;
         }
         self.0[sizes::TBO_VISION_BITMAP_OFFSET] = bitmap.raw;
+        for i in 0..sizes::TBO_VISION_FILTER_SIZE {
+            self.0[sizes::TBO_VISION_FILTER_OFFSET + i] = (filter[i].max(0.0).min(1.0) * 255.0).round() as u8;
+        }
     }
 }
 
@@ -487,9 +660,9 @@ pub struct TboVisionGrid<'a> {
 }
 
 impl<'a> VisionGrid for TboVisionGrid<'a> {
-    fn see(&mut self, v: Vector2<u32>, bitmap: DirectionBitmap, time: u64) {
+    fn see(&mut self, v: Vector2<u32>, bitmap: DirectionBitmap, filter: [f32; 3], time: u64) {
         let index = ((v.y * self.width + v.x) as usize) * sizes::TBO_VISION_ENTRY_SIZE;
-        TboVisionCell(&mut self.slice[index..index + sizes::TBO_VISION_ENTRY_SIZE]).see(bitmap, time);
+        TboVisionCell(&mut self.slice[index..index + sizes::TBO_VISION_ENTRY_SIZE]).see(bitmap, filter, time);
     }
 }
 
@@ -0,0 +1,35 @@
+use renderer::render_target;
+
+// integer levels are pixel perfect; the scale pass keeps the others crisp
+const LEVELS: [f32; 9] = [1.0, 1.5, 2.0, 2.5, 3.0, 4.0, 5.0, 6.0, 8.0];
+
+// avoids skipping a level when the current factor is a hair away from it
+const EPSILON: f32 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zoom {
+    // the target is a fixed number of rows high, whatever the size of the window
+    Fit,
+    Factor(f32),
+}
+
+impl Zoom {
+    pub fn factor(self, scaled_window_dimensions: (u16, u16)) -> f32 {
+        match self {
+            Zoom::Fit => render_target::fit_factor(scaled_window_dimensions),
+            Zoom::Factor(factor) => factor,
+        }
+    }
+
+    pub fn zoom_in(self, scaled_window_dimensions: (u16, u16)) -> Self {
+        let factor = self.factor(scaled_window_dimensions);
+        LEVELS.iter().find(|&&level| level > factor + EPSILON)
+            .map_or(self, |&level| Zoom::Factor(level))
+    }
+
+    pub fn zoom_out(self, scaled_window_dimensions: (u16, u16)) -> Self {
+        let factor = self.factor(scaled_window_dimensions);
+        LEVELS.iter().rev().find(|&&level| level < factor - EPSILON)
+            .map_or(self, |&level| Zoom::Factor(level))
+    }
+}
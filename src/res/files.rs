@@ -1,2 +1,8 @@
 pub const SPRITE_SHEET: &'static str = "sprites.png";
+pub const SPRITE_MANIFEST: &'static str = "sprites.toml";
+pub const SPRITE_NORMAL_MAP: &'static str = "sprites_normal.png";
+pub const ENCOUNTER_TABLES: &'static str = "encounters.toml";
+pub const KEY_BINDINGS: &'static str = "key_bindings.toml";
+pub const SETTINGS: &'static str = "settings.toml";
 pub const RES_DIR: &'static str = "res";
+pub const SCREENSHOT_DIR: &'static str = "screenshots";
@@ -1,59 +1,25 @@
 use std::collections::BTreeMap;
+use std::path::Path;
+use std::fmt;
 use cgmath::{Vector2, ElementWise};
 use direction::Direction;
+use enum_primitive::FromPrimitive;
+use toml;
 use content::{TileSprite, FieldUiSprite};
+use content::tile_sprite::NUM_TILE_SPRITES;
+use content::field_ui_sprite::NUM_FIELD_UI_SPRITES;
+use simple_file;
 
 pub const WIDTH_PX: u32 = 16;
 pub const HEIGHT_PX: u32 = 16;
 pub const DIMENSIONS: Vector2<u32> = Vector2 { x: WIDTH_PX, y: HEIGHT_PX };
 
-pub fn input_sprites() -> Vec<InputSprite> {
-
-    use self::TileSprite::*;
-
-    vec![
-        character(Angler, [0, 0], Some([0, 8]), None),
-        character(Crab, [1, 0], Some([0, 8]), None),
-        character(Snail, [2, 0], Some([0, 8]), None),
-
-        floor(InnerFloor, [0, 0], None, None),
-        floor(OuterFloor, [0, 1], None, None),
-        floor(InnerWater, [1, 1], None, None),
-        wall(InnerWall, [0, 0], None, None),
-        wall(OuterWall, [0, 1], None, None),
-
-        door(InnerDoor, [0, 0]),
-        door(InnerDoorOpening1, [1, 0]),
-        door(InnerDoorOpening2, [2, 0]),
-        door(InnerDoorOpening3, [3, 0]),
-        door(InnerDoorOpening4, [4, 0]),
-        door(InnerDoorOpening5, [5, 0]),
-        door(InnerDoorOpening6, [6, 0]),
-        door(InnerDoorOpen, [7, 0]),
-
-        door(OuterDoor, [0, 1]),
-        door(OuterDoorOpening1, [1, 1]),
-        door(OuterDoorOpening2, [2, 1]),
-        door(OuterDoorOpening3, [3, 1]),
-        door(OuterDoorOpening4, [4, 1]),
-        door(OuterDoorOpening5, [5, 1]),
-        door(OuterDoorOpening6, [6, 1]),
-        door(OuterDoorOpen, [7, 1]),
-
-        general_wall_fit(Window, [0, 0], None, None),
-
-        feature(Light, [0, 0], None, None),
-
-        field_ui(FieldUiSprite::HealthFull, [0, 0], Some([2, 1])),
-        field_ui(FieldUiSprite::HealthEmpty, [1, 0], Some([2, 1])),
-    ]
-}
-
 #[derive(Clone, Copy, Debug)]
 pub struct InputSpriteLocation {
     pub position: Vector2<u32>,
     pub size: Vector2<u32>,
     pub offset: Vector2<i32>,
+    pub emissive: f32,
 }
 
 #[derive(Clone, Debug)]
@@ -78,9 +44,6 @@ pub enum InputSprite {
     },
 }
 
-const WALL_START: Vector2<u32> = Vector2 { x: 0, y: 0 };
-const WALL_DIMENSIONS: Vector2<u32> = Vector2 { x: 16, y: 22 };
-const WALL_OFFSET: Vector2<i32> = Vector2 { x: 0, y: 6 };
 const WALL_DIRECTION_ORDER: [Direction; 8] = [
     Direction::North,
     Direction::East,
@@ -91,157 +54,276 @@ const WALL_DIRECTION_ORDER: [Direction; 8] = [
     Direction::SouthWest,
     Direction::NorthWest,
 ];
-const WALL_BLOCK_DIMENSIONS: Vector2<u32> = Vector2 {
-    x: WALL_DIMENSIONS.x * 9,
-    y: WALL_DIMENSIONS.y,
-};
-const WALL_TOTAL_HEIGHT: u32 = WALL_DIMENSIONS.y * 2;
-
-const CHARACTER_START: Vector2<u32> = Vector2 { x: 0, y: WALL_START.y + WALL_TOTAL_HEIGHT };
-const CHARACTER_DIMENSIONS: Vector2<u32> = Vector2 { x: 16, y: 20 };
-const CHARACTER_OFFSET: Vector2<i32> = Vector2 { x: 0, y: 8 };
-const CHARACTER_TOTAL_HEIGHT: u32 = CHARACTER_DIMENSIONS.y * 1;
-
-const FLOOR_START: Vector2<u32> = Vector2 { x: 0, y: CHARACTER_START.y + CHARACTER_TOTAL_HEIGHT };
-const FLOOR_DIMENSIONS: Vector2<u32> = Vector2 { x: 16, y: 16 };
-const FLOOR_OFFSET: Vector2<i32> = Vector2 { x: 0, y: 0 };
-const FLOOR_TOTAL_HEIGHT: u32 = FLOOR_DIMENSIONS.y * 2;
-
-const DOOR_START: Vector2<u32> = Vector2 { x: 0, y: FLOOR_START.y + FLOOR_TOTAL_HEIGHT };
-const DOOR_FRONT_DIMENSIONS: Vector2<u32> = Vector2 { x: 16, y: 22 };
-const DOOR_TOP_DIMENSIONS: Vector2<u32> = Vector2 { x: 16, y: 22 };
-const DOOR_FRONT_OFFSET: Vector2<i32> = Vector2 { x: 0, y: 6 };
-const DOOR_TOP_OFFSET: Vector2<i32> = Vector2 { x: 0, y: 10 };
-const DOOR_BLOCK_DIMENSIONS: Vector2<u32> = Vector2 { x: 32, y: 22 };
-const DOOR_TOTAL_HEIGHT: u32 = DOOR_BLOCK_DIMENSIONS.y * 2;
-
-const GENERAL_WALL_FIT_START: Vector2<u32> = Vector2 { x: 0, y: DOOR_START.y + DOOR_TOTAL_HEIGHT };
-const GENERAL_WALL_FIT_DIMENSIONS: Vector2<u32> = Vector2 { x: 16, y: 16 };
-const GENERAL_WALL_FIT_OFFSET: Vector2<i32> = Vector2 { x: 0, y: 0 };
-const GENERAL_WALL_FIT_TOTAL_HEIGHT: u32 = GENERAL_WALL_FIT_DIMENSIONS.y * 1;
-const GENERAL_WALL_FIT_BLOCK_DIMENSIONS: Vector2<u32> = Vector2 { x: 32, y: 16 };
-
-const FEATURE_START: Vector2<u32> = Vector2 { x: 0, y: GENERAL_WALL_FIT_START.y + GENERAL_WALL_FIT_TOTAL_HEIGHT };
-const FEATURE_DIMENSIONS: Vector2<u32> = Vector2 { x: 16, y: 16 };
-const FEATURE_OFFSET: Vector2<i32> = Vector2 { x: 0, y: 0 };
-const FEATURE_TOTAL_HEIGHT: u32 = FEATURE_DIMENSIONS.y * 1;
-
-fn character(sprite: TileSprite, position: [u32; 2], offset: Option<[i32; 2]>, size: Option<[u32; 2]>) -> InputSprite {
-    let position = CHARACTER_START + Vector2::from(position).mul_element_wise(CHARACTER_DIMENSIONS);
-    let offset = offset.map(Vector2::from).unwrap_or(CHARACTER_OFFSET);
-    let size = size.map(Vector2::from).unwrap_or(CHARACTER_DIMENSIONS);
-    InputSprite::Simple {
-        sprite,
-        location: InputSpriteLocation {
-            position,
-            size,
-            offset,
-        },
-    }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CategoryKind {
+    Simple,
+    Wall,
+    WallFit,
 }
 
-fn floor(sprite: TileSprite, position: [u32; 2], offset: Option<[i32; 2]>, size: Option<[u32; 2]>) -> InputSprite {
-    let position = FLOOR_START + Vector2::from(position).mul_element_wise(FLOOR_DIMENSIONS);
-    let offset = offset.map(Vector2::from).unwrap_or(FLOOR_OFFSET);
-    let size = size.map(Vector2::from).unwrap_or(FLOOR_DIMENSIONS);
-    InputSprite::Simple {
-        sprite,
-        location: InputSpriteLocation {
-            position,
-            size,
-            offset,
-        },
+#[derive(Clone, Debug, Deserialize)]
+struct Category {
+    kind: CategoryKind,
+    start: [u32; 2],
+    step: [u32; 2],
+    size: [u32; 2],
+    offset: [i32; 2],
+    top_size: Option<[u32; 2]>,
+    top_offset: Option<[i32; 2]>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Entry<S> {
+    sprite: S,
+    category: String,
+    position: [u32; 2],
+    offset: Option<[i32; 2]>,
+    size: Option<[u32; 2]>,
+    #[serde(default)]
+    emissive: f32,
+}
+
+// the contents of the sprite sheet manifest
+#[derive(Clone, Debug, Deserialize)]
+struct Manifest {
+    categories: BTreeMap<String, Category>,
+    #[serde(default)]
+    tile: Vec<Entry<TileSprite>>,
+    #[serde(default)]
+    field_ui: Vec<Entry<FieldUiSprite>>,
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    MissingFile,
+    InvalidFormat(String),
+    UnknownCategory { sprite: String, category: String },
+    WrongCategoryKind { sprite: String, category: String },
+    Duplicate(String),
+    Reserved(String),
+    Missing(Vec<String>),
+    OutOfBounds { sprite: String, image_size: Vector2<u32> },
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::ManifestError::*;
+        match self {
+            &MissingFile => write!(f, "file is missing or unreadable"),
+            &InvalidFormat(ref message) => write!(f, "invalid format: {}", message),
+            &UnknownCategory { ref sprite, ref category } =>
+                write!(f, "{} is in unknown category \"{}\"", sprite, category),
+            &WrongCategoryKind { ref sprite, ref category } =>
+                write!(f, "{} is a field ui sprite, so category \"{}\" must be simple", sprite, category),
+            &Duplicate(ref sprite) => write!(f, "{} appears more than once", sprite),
+            &Reserved(ref sprite) => write!(f, "{} isn't a real sprite, so can't appear", sprite),
+            &Missing(ref sprites) => write!(f, "no entries for {}", sprites.join(", ")),
+            &OutOfBounds { ref sprite, image_size } =>
+                write!(f, "{} extends beyond the {}x{} image", sprite, image_size.x, image_size.y),
+        }
     }
 }
 
-fn wall(sprite: TileSprite, position: [u32; 2], offset: Option<[i32; 2]>, size: Option<[u32; 2]>) -> InputSprite {
-    let position = WALL_START + Vector2::from(position).mul_element_wise(WALL_BLOCK_DIMENSIONS);
-    let offset = offset.map(Vector2::from).unwrap_or(WALL_OFFSET);
-    let size = size.map(Vector2::from).unwrap_or(WALL_DIMENSIONS);
-
-    let mut decorations = BTreeMap::new();
-    for (index, direction) in WALL_DIRECTION_ORDER.iter().enumerate() {
-        decorations.insert(*direction, position + Vector2::new(size.x * index as u32, 0));
-    }
-    let top = InputSpriteLocation {
-        position: position + Vector2::new(size.x * WALL_DIRECTION_ORDER.len() as u32, 0),
-        size,
-        offset,
-    };
-    InputSprite::Wall {
-        sprite,
-        top,
-        decorations,
+impl Category {
+    fn location<S>(&self, entry: &Entry<S>) -> InputSpriteLocation {
+        InputSpriteLocation {
+            position: Vector2::from(self.start) + Vector2::from(entry.position).mul_element_wise(Vector2::from(self.step)),
+            size: Vector2::from(entry.size.unwrap_or(self.size)),
+            offset: Vector2::from(entry.offset.unwrap_or(self.offset)),
+            emissive: entry.emissive,
+        }
+    }
+
+    fn tile_sprite(&self, entry: &Entry<TileSprite>) -> InputSprite {
+        let location = self.location(entry);
+        let sprite = entry.sprite;
+        match self.kind {
+            CategoryKind::Simple => InputSprite::Simple { sprite, location },
+            CategoryKind::Wall => {
+                let mut decorations = BTreeMap::new();
+                for (index, direction) in WALL_DIRECTION_ORDER.iter().enumerate() {
+                    decorations.insert(*direction, location.position + Vector2::new(location.size.x * index as u32, 0));
+                }
+                let top = InputSpriteLocation {
+                    position: location.position + Vector2::new(location.size.x * WALL_DIRECTION_ORDER.len() as u32, 0),
+                    ..location
+                };
+                InputSprite::Wall { sprite, top, decorations }
+            }
+            CategoryKind::WallFit => {
+                let top = InputSpriteLocation {
+                    position: location.position + Vector2::new(location.size.x, 0),
+                    size: self.top_size.map(Vector2::from).unwrap_or(location.size),
+                    offset: self.top_offset.map(Vector2::from).unwrap_or(location.offset),
+                    emissive: location.emissive,
+                };
+                InputSprite::WallFit { sprite, top, front: location }
+            }
+        }
     }
 }
 
-fn door(sprite: TileSprite, position: [u32; 2]) -> InputSprite {
-    let position = DOOR_START + Vector2::from(position).mul_element_wise(DOOR_BLOCK_DIMENSIONS);
+fn extends_beyond(location: &InputSpriteLocation, image_size: Vector2<u32>) -> bool {
+    location.position.x + location.size.x > image_size.x ||
+        location.position.y + location.size.y > image_size.y
+}
+
+impl InputSprite {
+    fn name(&self) -> String {
+        match self {
+            &InputSprite::Simple { sprite, .. } |
+                &InputSprite::Wall { sprite, .. } |
+                &InputSprite::WallFit { sprite, .. } => format!("{:?}", sprite),
+            &InputSprite::FieldUi { sprite, .. } => format!("{:?}", sprite),
+        }
+    }
 
-    let front = InputSpriteLocation {
-        position,
-        size: DOOR_FRONT_DIMENSIONS,
-        offset: DOOR_FRONT_OFFSET,
-    };
+    fn extends_beyond(&self, image_size: Vector2<u32>) -> bool {
+        match self {
+            &InputSprite::Simple { ref location, .. } |
+                &InputSprite::FieldUi { ref location, .. } => extends_beyond(location, image_size),
+            // the top is the right-most part of a wall
+            &InputSprite::Wall { ref top, .. } => extends_beyond(top, image_size),
+            &InputSprite::WallFit { ref top, ref front, .. } =>
+                extends_beyond(top, image_size) || extends_beyond(front, image_size),
+        }
+    }
+}
 
-    let top = InputSpriteLocation {
-        position: position + Vector2::new(DOOR_FRONT_DIMENSIONS.x, 0),
-        size: DOOR_TOP_DIMENSIONS,
-        offset: DOOR_TOP_OFFSET,
-    };
+// marks a sprite as seen, failing if it was already seen or isn't a real sprite
+fn see<S: fmt::Debug>(seen: &mut [bool], sprite: S, index: usize) -> Result<(), ManifestError> {
+    match seen.get_mut(index) {
+        Some(&mut true) => Err(ManifestError::Duplicate(format!("{:?}", sprite))),
+        Some(seen) => {
+            *seen = true;
+            Ok(())
+        }
+        None => Err(ManifestError::Reserved(format!("{:?}", sprite))),
+    }
+}
 
-    InputSprite::WallFit { sprite, front, top }
+// reads the layout of the sprite sheet, checking that every sprite appears exactly once
+// and lies within an image of the given size
+pub fn load<P: AsRef<Path>>(path: P, image_size: Vector2<u32>) -> Result<Vec<InputSprite>, ManifestError> {
+    let string = simple_file::read_string(path).map_err(|_| ManifestError::MissingFile)?;
+    parse(string.as_ref(), image_size)
 }
 
-fn general_wall_fit(sprite: TileSprite, position: [u32; 2], offset: Option<[i32; 2]>, size: Option<[u32; 2]>) -> InputSprite {
+fn parse(string: &str, image_size: Vector2<u32>) -> Result<Vec<InputSprite>, ManifestError> {
+    let manifest: Manifest = toml::from_str(string)
+        .map_err(|e| ManifestError::InvalidFormat(e.to_string()))?;
+
+    let mut input_sprites = Vec::new();
+    let mut tile_seen = vec![false; NUM_TILE_SPRITES];
+    let mut field_ui_seen = vec![false; NUM_FIELD_UI_SPRITES];
+
+    for entry in manifest.tile.iter() {
+        let category = manifest.categories.get(&entry.category).ok_or_else(|| ManifestError::UnknownCategory {
+            sprite: format!("{:?}", entry.sprite),
+            category: entry.category.clone(),
+        })?;
+        // the blank sprite is added by the sprite sheet itself
+        if entry.sprite == TileSprite::Blank {
+            return Err(ManifestError::Reserved(format!("{:?}", entry.sprite)));
+        }
+        see(&mut tile_seen, entry.sprite, entry.sprite as usize)?;
+        input_sprites.push(category.tile_sprite(entry));
+    }
 
-    let position = GENERAL_WALL_FIT_START + Vector2::from(position).mul_element_wise(GENERAL_WALL_FIT_DIMENSIONS);
-    let offset = offset.map(Vector2::from).unwrap_or(GENERAL_WALL_FIT_OFFSET);
-    let size = size.map(Vector2::from).unwrap_or(GENERAL_WALL_FIT_DIMENSIONS);
+    for entry in manifest.field_ui.iter() {
+        let category = manifest.categories.get(&entry.category).ok_or_else(|| ManifestError::UnknownCategory {
+            sprite: format!("{:?}", entry.sprite),
+            category: entry.category.clone(),
+        })?;
+        if category.kind != CategoryKind::Simple {
+            return Err(ManifestError::WrongCategoryKind {
+                sprite: format!("{:?}", entry.sprite),
+                category: entry.category.clone(),
+            });
+        }
+        see(&mut field_ui_seen, entry.sprite, entry.sprite as usize)?;
+        input_sprites.push(InputSprite::FieldUi {
+            sprite: entry.sprite,
+            location: category.location(entry),
+        });
+    }
 
-    let front = InputSpriteLocation {
-        position,
-        size,
-        offset,
-    };
+    let mut missing = Vec::new();
+    for (index, seen) in tile_seen.iter().enumerate() {
+        if !seen && index != TileSprite::Blank as usize {
+            if let Some(sprite) = TileSprite::from_usize(index) {
+                missing.push(format!("{:?}", sprite));
+            }
+        }
+    }
+    for (index, seen) in field_ui_seen.iter().enumerate() {
+        if !seen {
+            if let Some(sprite) = FieldUiSprite::from_usize(index) {
+                missing.push(format!("{:?}", sprite));
+            }
+        }
+    }
+    if !missing.is_empty() {
+        return Err(ManifestError::Missing(missing));
+    }
 
-    let top = InputSpriteLocation {
-        position: position + Vector2::new(size.x, 0),
-        size,
-        offset,
-    };
+    if let Some(sprite) = input_sprites.iter().find(|sprite| sprite.extends_beyond(image_size)) {
+        return Err(ManifestError::OutOfBounds {
+            sprite: sprite.name(),
+            image_size,
+        });
+    }
 
-    InputSprite::WallFit { sprite, front, top }
+    Ok(input_sprites)
 }
 
-fn feature(sprite: TileSprite, position: [u32; 2], offset: Option<[i32; 2]>, size: Option<[u32; 2]>) -> InputSprite {
-    let position = FEATURE_START + Vector2::from(position).mul_element_wise(FEATURE_DIMENSIONS);
-    let offset = offset.map(Vector2::from).unwrap_or(FEATURE_OFFSET);
-    let size = size.map(Vector2::from).unwrap_or(FEATURE_DIMENSIONS);
-    InputSprite::Simple {
-        sprite,
-        location: InputSpriteLocation {
-            position,
-            size,
-            offset,
-        },
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image;
+    use res::files;
+
+    const CATEGORIES: &'static str = "
+        [categories.simple]
+        kind = 'simple'
+        start = [0, 0]
+        step = [16, 16]
+        size = [16, 16]
+        offset = [0, 0]
+    ";
+
+    fn manifest_with(sprite: &str) -> String {
+        format!("{}\n[[tile]]\nsprite = '{}'\ncategory = 'simple'\nposition = [0, 0]\n", CATEGORIES, sprite)
+    }
+
+    #[test]
+    fn shipped_manifest_covers_every_sprite() {
+        let res_dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/src/res"));
+        let image = image::open(res_dir.join(files::SPRITE_SHEET)).expect("Failed to open sprite sheet").to_rgba();
+        let (width, height) = image.dimensions();
+
+        let input_sprites = load(res_dir.join(files::SPRITE_MANIFEST), Vector2::new(width, height))
+            .unwrap_or_else(|e| panic!("{}", e));
+
+        // every sprite but the blank one appears exactly once
+        assert_eq!(input_sprites.len(), NUM_TILE_SPRITES - 1 + NUM_FIELD_UI_SPRITES);
+    }
+
+    #[test]
+    fn blank_is_reserved() {
+        match parse(&manifest_with("Blank"), Vector2::new(256, 256)) {
+            Err(ManifestError::Reserved(_)) => {}
+            other => panic!("Expected reserved sprite error, got {:?}", other),
+        }
     }
-}
 
-const FIELD_UI_START: Vector2<u32> = Vector2 { x: 0, y: FEATURE_START.y + FEATURE_TOTAL_HEIGHT };
-const FIELD_UI_DIMENSIONS: Vector2<u32> = Vector2 { x: 8, y: 8 };
-const FIELD_UI_OFFSET: Vector2<i32> = Vector2 { x: 0, y: 0 };
-const FIELD_UI_TOTAL_HEIGHT: u32 = 7;
-
-fn field_ui(sprite: FieldUiSprite, position: [u32; 2], size: Option<[u32; 2]>) -> InputSprite {
-    let position = FIELD_UI_START + Vector2::from(position).mul_element_wise(FIELD_UI_DIMENSIONS);
-    let size = size.map(Vector2::from).unwrap_or(FIELD_UI_DIMENSIONS);
-    InputSprite::FieldUi {
-        sprite,
-        location: InputSpriteLocation {
-            position,
-            size,
-            offset: FIELD_UI_OFFSET,
-        },
+    #[test]
+    fn num_is_reserved() {
+        match parse(&manifest_with("_Num"), Vector2::new(256, 256)) {
+            Err(ManifestError::Reserved(_)) => {}
+            other => panic!("Expected reserved sprite error, got {:?}", other),
+        }
     }
 }
@@ -1,15 +1,23 @@
 use std::path::{PathBuf, Path};
 use std::env;
 
-use res::files::RES_DIR;
+use res::files::{RES_DIR, SCREENSHOT_DIR};
 
-pub fn res_dir() -> PathBuf {
+fn exe_dir() -> PathBuf {
     let mut exe_path = env::current_exe()
         .expect("Failed to find executable path");
 
     exe_path.pop();
 
-    exe_path.join(RES_DIR)
+    exe_path
+}
+
+pub fn res_dir() -> PathBuf {
+    exe_dir().join(RES_DIR)
+}
+
+pub fn screenshot_dir() -> PathBuf {
+    exe_dir().join(SCREENSHOT_DIR)
 }
 
 pub fn res_path<P: AsRef<Path>>(path: P) -> PathBuf {
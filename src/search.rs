@@ -4,12 +4,13 @@ use std::collections::BinaryHeap;
 use cgmath::Vector2;
 use static_grid::StaticGrid;
 use spatial_hash::{SpatialHashTable, SpatialHashCell};
-use direction::{CardinalDirection, CardinalDirections};
+use direction::Direction;
 use dijkstra_map::DijkstraMap;
+use movement;
 
 #[derive(Debug, Clone, Copy)]
 pub struct PathNode {
-    pub direction: CardinalDirection,
+    pub direction: Direction,
     pub origin: Vector2<i32>,
 }
 
@@ -31,7 +32,7 @@ struct Cell {
     score: u32,
     visited: u64,
     seen: u64,
-    enter_direction: Option<CardinalDirection>,
+    enter_direction: Option<Direction>,
 }
 
 impl Default for Cell {
@@ -85,6 +86,7 @@ impl SearchEnv {
                      spatial_hash: &SpatialHashTable,
                      start: Vector2<i32>,
                      end: Vector2<i32>,
+                     directions: &[Direction],
                      cost_fn: C,
                      dijkstra_map: &DijkstraMap,
                      path: &mut Vec<PathNode>) -> Result<()>
@@ -131,7 +133,7 @@ impl SearchEnv {
 
             let signed_coord = node.coord.cast();
 
-            for direction in CardinalDirections {
+            for direction in directions.iter() {
                 let next_signed_coord = signed_coord + direction.vector();
                 if let Some(cell) = self.grid.get_signed_mut(next_signed_coord) {
 
@@ -140,6 +142,10 @@ impl SearchEnv {
                         continue;
                     }
 
+                    if movement::cuts_corner(spatial_hash, signed_coord, next_signed_coord) {
+                        continue;
+                    }
+
                     let next_coord = next_signed_coord.cast();
 
                     let heuristic = if let Some(distance) = dijkstra_map.get_distance(next_coord) {
@@ -166,7 +172,7 @@ impl SearchEnv {
                     cell.seen = self.seq;
                     cell.cost = cost;
                     cell.score = score;
-                    cell.enter_direction = Some(direction);
+                    cell.enter_direction = Some(*direction);
 
                     self.queue.push(Node {
                         score,
@@ -0,0 +1,46 @@
+use movement::MovementMode;
+use content::{AmbientLightInfo, ambient_light};
+use simple_file;
+use res::{paths, files};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settings {
+    // FourWay or EightWay. an eight-way step can't cut a corner past anything solid, which
+    // includes closed doors as well as walls
+    pub movement_mode: MovementMode,
+    // settings files from before this was configurable don't have it
+    #[serde(default = "default_ambient_light")]
+    pub ambient_light: AmbientLightInfo,
+}
+
+fn default_ambient_light() -> AmbientLightInfo {
+    ambient_light::DEFAULT_AMBIENT_LIGHT
+}
+
+impl Settings {
+    pub fn defaults() -> Self {
+        Self {
+            movement_mode: MovementMode::FourWay,
+            ambient_light: ambient_light::DEFAULT_AMBIENT_LIGHT,
+        }
+    }
+
+    // reads the settings file, writing out the defaults if there isn't one yet
+    pub fn load() -> Self {
+        let path = paths::res_path(files::SETTINGS);
+        match simple_file::read_toml(&path) {
+            Ok(settings) => settings,
+            Err(simple_file::FileError::MissingFile) => {
+                let settings = Self::defaults();
+                if let Err(e) = simple_file::write_toml(&path, &settings) {
+                    eprintln!("Failed to write default settings: {:?}", e);
+                }
+                settings
+            }
+            Err(e) => {
+                eprintln!("Failed to read settings ({:?}), using defaults", e);
+                Self::defaults()
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use cgmath::Vector2;
+
+use frontend::{FrontendOutput, FrontendInput};
+use renderer::{SoftwareRenderer, SoftwareWorldState};
+use input::{Input, System};
+use entity_store::EntityStore;
+
+const WINDOW_DIMENSIONS: (u16, u16) = (960, 720);
+const FRAME_DURATION_MS: u64 = 16;
+
+pub struct SoftwareFrontendOutput {
+    renderer: SoftwareRenderer,
+    output_dir: PathBuf,
+    frame_index: u64,
+}
+
+// there's nobody to press keys, so this just quits after a fixed number of frames
+pub struct SoftwareFrontendInput {
+    frames_remaining: u64,
+}
+
+pub fn create<P: AsRef<Path>>(output_dir: P, num_frames: u64) -> (SoftwareFrontendInput, SoftwareFrontendOutput) {
+    let output_dir = output_dir.as_ref().to_path_buf();
+    fs::create_dir_all(&output_dir)
+        .expect(format!("Failed to create output directory {}", output_dir.display()).as_ref());
+
+    let mut renderer = SoftwareRenderer::new(WINDOW_DIMENSIONS);
+    renderer.set_fixed_frame_duration(Some(Duration::from_millis(FRAME_DURATION_MS)));
+
+    let input = SoftwareFrontendInput {
+        frames_remaining: num_frames,
+    };
+
+    let output = SoftwareFrontendOutput {
+        renderer,
+        output_dir,
+        frame_index: 0,
+    };
+
+    (input, output)
+}
+
+impl FrontendInput for SoftwareFrontendInput {
+    fn with_input<F: FnMut(Input)>(&mut self, mut f: F) {
+        if self.frames_remaining == 0 {
+            f(Input::System(System::Quit));
+        } else {
+            self.frames_remaining -= 1;
+        }
+    }
+}
+
+impl<'a> FrontendOutput<'a> for SoftwareFrontendOutput {
+    type WorldState = SoftwareWorldState<'a>;
+    fn with_world_state<F: FnMut(&mut Self::WorldState)>(&'a mut self, mut f: F) {
+        let mut state = self.renderer.world_state();
+        f(&mut state);
+        state.finalise();
+    }
+    fn draw(&mut self, _entity_store: &EntityStore) {
+        self.renderer.render();
+
+        let path = self.output_dir.join(format!("frame_{:06}.png", self.frame_index));
+        self.renderer.output().save(&path)
+            .expect(format!("Failed to write frame to {}", path.display()).as_ref());
+        self.frame_index += 1;
+    }
+    fn handle_resize(&mut self, width: u16, height: u16) {
+        self.renderer.handle_resize((width, height));
+    }
+    fn update_world_size(&mut self, width: u32, height: u32) {
+        self.renderer.update_world_size(width, height);
+    }
+    fn world_coord(&self, x: i32, y: i32) -> Vector2<i32> {
+        // positions are pixels of the unscaled output
+        self.renderer.target_to_world(Vector2::new(x, y).cast())
+    }
+    fn set_cursor(&mut self, _cursor: Option<Vector2<i32>>) {
+        // the field ui isn't drawn
+    }
+    fn set_description(&mut self, _description: Option<&str>) {}
+    fn request_screenshot(&mut self) {
+        // every frame is already written out
+    }
+    // frames are always written at the unscaled size
+    fn zoom_in(&mut self) {}
+    fn zoom_out(&mut self) {}
+}
@@ -0,0 +1,138 @@
+use fnv::FnvHashMap;
+use cgmath::Vector2;
+use rand::Rng;
+use entity_store::{EntityChange, EntityStore};
+use entity_id_allocator::{EntityIdAllocator, EntityRef};
+use spatial_hash::{SpatialHashTable, SpatialHashCell};
+use static_grid::StaticGrid;
+use dijkstra_map::DijkstraMap;
+use movement::MovementMode;
+use content::EncounterTables;
+use append::Append;
+use turn::{TurnInfo, NUM_TURN_STATES};
+use prototype;
+
+fn can_enter(cell: &SpatialHashCell) -> bool {
+    cell.solid_count == 0
+}
+
+struct SpawnerState {
+    next_spawn: u64,
+    spawned: Vec<EntityRef>,
+}
+
+pub struct SpawnerEnv {
+    tables: EncounterTables,
+    spawners: FnvHashMap<EntityRef, SpawnerState>,
+    candidates: Vec<Vector2<i32>>,
+    reachable: DijkstraMap,
+}
+
+impl SpawnerEnv {
+    pub fn new(tables: EncounterTables, width: u32, height: u32) -> Self {
+        Self {
+            tables,
+            spawners: FnvHashMap::default(),
+            candidates: Vec::new(),
+            reachable: DijkstraMap::new(width, height),
+        }
+    }
+
+    // forgets the state of a spawner that's being deleted
+    pub fn remove_entity(&mut self, entity: EntityRef) {
+        self.spawners.remove(&entity);
+    }
+
+    pub fn spawn<A: Append<EntityChange>, R: Rng>(&mut self,
+                                                  changes: &mut A,
+                                                  entity_store: &EntityStore,
+                                                  spatial_hash: &SpatialHashTable,
+                                                  allocator: &mut EntityIdAllocator,
+                                                  vision: &StaticGrid<u64>,
+                                                  vision_time: u64,
+                                                  turn: TurnInfo,
+                                                  rng: &mut R)
+    {
+        let tables = &self.tables;
+        let spawners = &mut self.spawners;
+        let candidates = &mut self.candidates;
+        let reachable = &mut self.reachable;
+
+        // forget spawners that have lost their spawner component
+        spawners.retain(|entity, _| {
+            allocator.is_current(*entity) && entity_store.spawner.get(&entity.id).is_some()
+        });
+
+        for (id, info) in entity_store.spawner.iter() {
+            let coord = if let Some(coord) = entity_store.coord.get(id) {
+                *coord
+            } else {
+                continue;
+            };
+
+            let state = spawners.entry(allocator.entity_ref(*id)).or_insert_with(|| SpawnerState {
+                next_spawn: turn.count,
+                spawned: Vec::new(),
+            });
+
+            if turn.count < state.next_spawn {
+                continue;
+            }
+
+            // forget anything this spawner made that has since died or been deleted
+            state.spawned.retain(|entity| {
+                allocator.is_current(*entity) && entity_store.npc.contains(&entity.id)
+            });
+            if state.spawned.len() >= info.population_cap {
+                continue;
+            }
+
+            let creature = if let Some(creature) = tables.get(&info.table)
+                .and_then(|table| table.choose(rng))
+            {
+                creature
+            } else {
+                continue;
+            };
+
+            // creatures only appear where they could have walked from the spawner, so never behind walls
+            // or closed doors. diagonals that don't cut corners reach nothing four-way steps can't.
+            reachable.compute_distance_to_coord(spatial_hash, coord, info.radius as u32 + 1,
+                                                MovementMode::FourWay.directions(), can_enter);
+
+            candidates.clear();
+            for y in (coord.y - info.radius)..(coord.y + info.radius + 1) {
+                for x in (coord.x - info.radius)..(coord.x + info.radius + 1) {
+                    let candidate = Vector2::new(x, y);
+                    if let Some(sh_cell) = spatial_hash.get_signed(candidate) {
+                        if sh_cell.solid_count > 0 || !sh_cell.door_set.is_empty() ||
+                            sh_cell.npc_count > 0 || sh_cell.player_count > 0
+                        {
+                            continue;
+                        }
+                    } else {
+                        continue;
+                    }
+                    if reachable.get_distance_signed(candidate).is_none() {
+                        continue;
+                    }
+                    // never spawn where the player can currently see
+                    if vision_time != 0 && vision.get_signed(candidate) == Some(&vision_time) {
+                        continue;
+                    }
+                    candidates.push(candidate);
+                }
+            }
+
+            if candidates.is_empty() {
+                continue;
+            }
+
+            let index = rng.gen_range(0, candidates.len());
+            let spawned_id = allocator.allocate();
+            prototype::creature(changes, spawned_id, candidates[index], creature);
+            state.spawned.push(allocator.entity_ref(spawned_id));
+            state.next_spawn = turn.count + info.period * NUM_TURN_STATES as u64;
+        }
+    }
+}
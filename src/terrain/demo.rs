@@ -3,6 +3,7 @@ use entity_store::EntityChange;
 use entity_id_allocator::EntityIdAllocator;
 use terrain::TerrainMetadata;
 use prototype;
+use content::SpawnerInfo;
 
 pub fn generate(changes: &mut Vec<EntityChange>,
                 allocator: &mut EntityIdAllocator) -> TerrainMetadata {
@@ -12,19 +13,19 @@ pub fn generate(changes: &mut Vec<EntityChange>,
     let strings = vec![
         "..................................................",
         "..................................................",
-        "..........######o###################..............",
+        "..........######b###################..............",
         ".........##,,,,,,%,,,,%m,,,,,,,,,,,#..............",
         "........##,,,l,,,%,,,,%,,,,,,,,,,,,#..............",
         "........#,,,,,,,,%,,,s%%%%%%%%,,,,,#..............",
         "........#,,,,,,,c,,,,@+,,,,,,,,,,,,####o####......",
         "........o,,,,,,,,,,,,s%,,,,,,,,,,,,%,,,,,,,#......",
-        "........#,,,,,,,,,,,~~%,,,,,,,,,,,,+,,,,,,,#......",
+        "........#,,,X,,,,,,,~~%,,,,,,,,,,,,+,,,,,,,#......",
         "........#,,,,,,,,,,~~~%n,,,,,,,,,,,%,,,,,,,#......",
         "........#######*o############o######%%%%+%%####...",
         "...................................#,,,,,,,,,,#...",
         "...................................o,,,,,,,,,,#...",
-        "...................................#,,,,,,,,,,#...",
-        "...................................*,,,,,,,,,,*...",
+        "...................................#,,,,,x,,,,#...",
+        "...................................*,,,,,,,,v,*...",
         "...................................#,,,,,,,,,,#...",
         "...................................####o#######...",
         "..................................................",
@@ -86,6 +87,18 @@ pub fn generate(changes: &mut Vec<EntityChange>,
                     prototype::light(changes, allocator.allocate(), coord, [0.0, 1.0, 0.0]);
                     prototype::inner_floor(changes, allocator.allocate(), coord);
                 }
+                'v' => {
+                    prototype::gas_vent(changes, allocator.allocate(), coord, 1.0);
+                    prototype::vent_floor(changes, allocator.allocate(), coord);
+                }
+                'X' => {
+                    prototype::spawner(changes, allocator.allocate(), coord, SpawnerInfo::new("shore", 8, 2, 4));
+                    prototype::inner_floor(changes, allocator.allocate(), coord);
+                }
+                'x' => {
+                    prototype::spawner(changes, allocator.allocate(), coord, SpawnerInfo::new("garden", 12, 3, 4));
+                    prototype::inner_floor(changes, allocator.allocate(), coord);
+                }
                 '%' => {
                     prototype::inner_wall(changes, allocator.allocate(), coord);
                     prototype::inner_floor(changes, allocator.allocate(), coord);
@@ -103,6 +116,11 @@ pub fn generate(changes: &mut Vec<EntityChange>,
                     prototype::inner_floor(changes, allocator.allocate(), coord);
                     prototype::window(changes, allocator.allocate(), coord);
                 }
+                'b' => {
+                    prototype::outer_wall(changes, allocator.allocate(), coord);
+                    prototype::inner_floor(changes, allocator.allocate(), coord);
+                    prototype::stained_window(changes, allocator.allocate(), coord, [0.3, 0.5, 1.0]);
+                }
                 '*' => {
                     prototype::outer_door(changes, allocator.allocate(), coord);
                     prototype::inner_floor(changes, allocator.allocate(), coord);
@@ -0,0 +1,204 @@
+use cgmath::Vector2;
+use direction::Direction;
+use dijkstra_map::DijkstraMap;
+use search::{SearchEnv, PathNode};
+use spatial_hash::{SpatialHashTable, SpatialHashCell};
+use static_grid::StaticGrid;
+use entity_store::{EntityStore, EntityChange, ComponentValue, ComponentType};
+use content::DoorState;
+use movement::{self, MovementMode};
+
+fn can_travel_through(cell: &SpatialHashCell) -> bool {
+    // closed doors are opened by walking into them
+    cell.solid_count == 0 || !cell.door_set.is_empty()
+}
+
+pub struct TravelEnv {
+    distance_map: DijkstraMap,
+    search_env: SearchEnv,
+    path: Vec<PathNode>,
+    exploring: bool,
+    // set when the last step explored into a closed door
+    opening_door: bool,
+    unseen: Vec<Vector2<i32>>,
+    // distances to the nearest unseen cell, kept until the map or what the player has seen changes
+    explore_map: DijkstraMap,
+    explore_map_stale: bool,
+    threshold: u32,
+    movement_mode: MovementMode,
+}
+
+impl TravelEnv {
+    pub fn new(width: u32, height: u32, movement_mode: MovementMode) -> Self {
+        Self {
+            distance_map: DijkstraMap::new(width, height),
+            search_env: SearchEnv::new(width, height),
+            path: Vec::new(),
+            exploring: false,
+            opening_door: false,
+            unseen: Vec::new(),
+            explore_map: DijkstraMap::new(width, height),
+            explore_map_stale: true,
+            threshold: width * height,
+            movement_mode,
+        }
+    }
+
+    pub fn is_travelling(&self) -> bool {
+        self.exploring || !self.path.is_empty()
+    }
+
+    pub fn cancel(&mut self) {
+        self.path.clear();
+        self.exploring = false;
+        self.opening_door = false;
+    }
+
+    pub fn start(&mut self, spatial_hash: &SpatialHashTable, start: Vector2<i32>, destination: Vector2<i32>) -> bool {
+        self.cancel();
+
+        let directions = self.movement_mode.directions();
+        self.distance_map.compute_distance_to_coord(spatial_hash, destination, self.threshold,
+                                                    directions, can_travel_through);
+
+        self.search_env.search(spatial_hash, start, destination, directions, |cell, _| {
+            if can_travel_through(cell) {
+                Some(1)
+            } else {
+                None
+            }
+        }, &self.distance_map, &mut self.path).is_ok()
+    }
+
+    // call when the player sees a cell for the first time
+    pub fn handle_discovery(&mut self) {
+        self.explore_map_stale = true;
+        if self.opening_door {
+            // stop so the player can see what was behind the door
+            self.cancel();
+        }
+    }
+
+    pub fn update(&mut self, change: &EntityChange, entity_store: &EntityStore) {
+        use self::EntityChange::*;
+        let changes_map = match change {
+            &Insert(_, ComponentValue::Solid) | &Remove(_, ComponentType::Solid) |
+                &Insert(_, ComponentValue::Door(_)) | &Remove(_, ComponentType::Door) => true,
+            &Insert(id, ComponentValue::Coord(_)) | &Remove(id, ComponentType::Coord) => {
+                entity_store.solid.contains(&id) || entity_store.door.get(&id).is_some()
+            }
+            _ => false,
+        };
+        if changes_map {
+            self.explore_map_stale = true;
+        }
+    }
+
+    pub fn start_explore(&mut self) {
+        self.cancel();
+        self.exploring = true;
+    }
+
+    pub fn next_direction(&mut self,
+                          coord: Vector2<i32>,
+                          entity_store: &EntityStore,
+                          spatial_hash: &SpatialHashTable,
+                          vision: &StaticGrid<u64>) -> Option<Direction> {
+        let direction = if self.exploring {
+            self.explore_direction(coord, entity_store, spatial_hash, vision)
+        } else {
+            self.path_direction(coord)
+        };
+
+        if direction.is_none() {
+            self.cancel();
+        }
+
+        direction
+    }
+
+    fn path_direction(&mut self, coord: Vector2<i32>) -> Option<Direction> {
+        // the path is stored in reverse, so the next step is at the end
+        let arrived = if let Some(node) = self.path.last() {
+            coord == node.origin + node.direction.vector()
+        } else {
+            return None;
+        };
+        if arrived {
+            self.path.pop();
+        }
+
+        if let Some(node) = self.path.last() {
+            if node.origin == coord {
+                // a step is repeated if it was spent opening a door
+                return Some(node.direction);
+            }
+        }
+
+        // something pushed us off the path
+        None
+    }
+
+    fn explore_direction(&mut self,
+                         coord: Vector2<i32>,
+                         entity_store: &EntityStore,
+                         spatial_hash: &SpatialHashTable,
+                         vision: &StaticGrid<u64>) -> Option<Direction> {
+        let directions = self.movement_mode.directions();
+
+        // the door opened without revealing anything, so keep going
+        self.opening_door = false;
+
+        if self.explore_map_stale {
+            self.unseen.clear();
+            for unseen_coord in vision.coord_iter() {
+                if *vision.get_checked(unseen_coord) != 0 {
+                    continue;
+                }
+                let unseen_coord = unseen_coord.cast();
+                if spatial_hash.get_signed(unseen_coord).map_or(false, can_travel_through) {
+                    self.unseen.push(unseen_coord);
+                }
+            }
+
+            self.explore_map.compute_distance_to_coords(spatial_hash, self.unseen.drain(..), self.threshold,
+                                                        directions, can_travel_through);
+            self.explore_map_stale = false;
+        }
+
+        let mut best = None;
+        let mut min_distance = if let Some(distance) = self.explore_map.get_distance_signed(coord) {
+            distance
+        } else {
+            // everything reachable has been seen
+            return None;
+        };
+
+        for direction in directions.iter() {
+            let next_coord = coord + direction.vector();
+            if movement::cuts_corner(spatial_hash, coord, next_coord) {
+                continue;
+            }
+            if let Some(distance) = self.explore_map.get_distance_signed(next_coord) {
+                if distance < min_distance {
+                    min_distance = distance;
+                    best = Some((*direction, next_coord));
+                }
+            }
+        }
+
+        let (direction, next_coord) = if let Some(best) = best {
+            best
+        } else {
+            return None;
+        };
+
+        self.opening_door = spatial_hash.get_signed(next_coord).map_or(false, |sh_cell| {
+            sh_cell.door_set.iter().any(|id| {
+                entity_store.door.get(id).map_or(false, |door| door.state == DoorState::Closed)
+            })
+        });
+
+        Some(direction)
+    }
+}
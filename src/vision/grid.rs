@@ -1,6 +1,90 @@
 use cgmath::Vector2;
 use direction::DirectionBitmap;
+use static_grid::StaticGrid;
+
+// the colour filter of light that hasn't passed through anything tinted
+pub const NO_FILTER: [f32; 3] = [1.0, 1.0, 1.0];
 
 pub trait VisionGrid {
-    fn see(&mut self, v: Vector2<u32>, bitmap: DirectionBitmap, time: u64);
+    fn see(&mut self, v: Vector2<u32>, bitmap: DirectionBitmap, filter: [f32; 3], time: u64);
+}
+
+// records the time each cell was last seen
+impl VisionGrid for StaticGrid<u64> {
+    fn see(&mut self, v: Vector2<u32>, _bitmap: DirectionBitmap, _filter: [f32; 3], time: u64) {
+        if let Some(cell) = self.get_mut(v) {
+            *cell = time;
+        }
+    }
+}
+
+// records the time each cell was last seen, noting whether any cell was seen for the first time
+pub struct DiscoveryGrid<'a> {
+    grid: &'a mut StaticGrid<u64>,
+    discovered: bool,
+}
+
+impl<'a> DiscoveryGrid<'a> {
+    pub fn new(grid: &'a mut StaticGrid<u64>) -> Self {
+        Self {
+            grid,
+            discovered: false,
+        }
+    }
+
+    pub fn discovered(&self) -> bool {
+        self.discovered
+    }
+}
+
+impl<'a> VisionGrid for DiscoveryGrid<'a> {
+    fn see(&mut self, v: Vector2<u32>, _bitmap: DirectionBitmap, _filter: [f32; 3], time: u64) {
+        if let Some(cell) = self.grid.get_mut(v) {
+            if *cell == 0 {
+                self.discovered = true;
+            }
+            *cell = time;
+        }
+    }
+}
+
+// records the time, visible sides and colour filter of each cell, for frontends that light on the cpu
+#[derive(Debug, Clone, Copy)]
+pub struct VisionCell {
+    pub time: u64,
+    pub bitmap: u8,
+    pub filter: [f32; 3],
+}
+
+impl Default for VisionCell {
+    fn default() -> Self {
+        Self {
+            time: 0,
+            bitmap: 0,
+            filter: NO_FILTER,
+        }
+    }
+}
+
+impl VisionGrid for StaticGrid<VisionCell> {
+    fn see(&mut self, v: Vector2<u32>, bitmap: DirectionBitmap, filter: [f32; 3], time: u64) {
+        if let Some(cell) = self.get_mut(v) {
+            cell.time = time;
+            cell.bitmap = bitmap.raw;
+            cell.filter = filter;
+        }
+    }
+}
+
+impl<'a, G: VisionGrid> VisionGrid for &'a mut G {
+    fn see(&mut self, v: Vector2<u32>, bitmap: DirectionBitmap, filter: [f32; 3], time: u64) {
+        (**self).see(v, bitmap, filter, time);
+    }
+}
+
+impl<A: VisionGrid, B: VisionGrid> VisionGrid for (A, B) {
+    fn see(&mut self, v: Vector2<u32>, bitmap: DirectionBitmap, filter: [f32; 3], time: u64) {
+        self.0.see(v, bitmap, filter, time);
+        self.1.see(v, bitmap, filter, time);
+    }
 }
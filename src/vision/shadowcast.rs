@@ -1,8 +1,9 @@
 use std::mem;
 use std::cmp;
 use cgmath::Vector2;
-use spatial_hash::SpatialHashTable;
-use vision::VisionGrid;
+use spatial_hash::{SpatialHashTable, SpatialHashCell};
+use entity_store::EntityStore;
+use vision::{VisionGrid, NO_FILTER};
 use direction::DirectionBitmap;
 
 use vision::shadowcast_octants::*;
@@ -24,6 +25,7 @@ struct StaticParams<'a> {
     vision_distance_squared: i32,
     time: u64,
     spatial_hash: &'a SpatialHashTable,
+    entity_store: &'a EntityStore,
 }
 
 struct ScanParams {
@@ -31,6 +33,7 @@ struct ScanParams {
     max_gradient: Gradient,
     depth: i32,
     visibility: f32,
+    filter: [f32; 3],
 }
 
 impl Default for ScanParams {
@@ -40,15 +43,37 @@ impl Default for ScanParams {
             max_gradient: Gradient::new(1, 1),
             depth: 1,
             visibility: 1.0,
+            filter: NO_FILTER,
         }
     }
 }
 
 struct CornerInfo {
     bitmap: DirectionBitmap,
+    filter: [f32; 3],
     coord: Vector2<i32>,
 }
 
+fn apply_tints(mut filter: [f32; 3], sh_cell: &SpatialHashCell, entity_store: &EntityStore) -> [f32; 3] {
+    for id in sh_cell.tint_set.iter() {
+        if let Some(tint) = entity_store.tint.get(id) {
+            for i in 0..3 {
+                filter[i] *= tint.filter[i];
+            }
+        }
+    }
+    filter
+}
+
+fn dim(filter: [f32; 3], visibility: f32) -> [f32; 3] {
+    let visibility = visibility.min(1.0);
+    [filter[0] * visibility, filter[1] * visibility, filter[2] * visibility]
+}
+
+fn filter_total(filter: [f32; 3]) -> f32 {
+    filter[0] + filter[1] + filter[2]
+}
+
 fn scan<G, O>(grid: &mut G,
               octant: &O,
               next: &mut Vec<ScanParams>,
@@ -57,7 +82,7 @@ fn scan<G, O>(grid: &mut G,
     where G: VisionGrid,
           O: Octant,
 {
-    let ScanParams { mut min_gradient, max_gradient, depth, visibility } = params;
+    let ScanParams { mut min_gradient, max_gradient, depth, visibility, filter } = params;
 
     let depth_index = if let Some(depth_index) = octant.depth_index(static_params.centre, depth) {
         depth_index
@@ -82,6 +107,7 @@ fn scan<G, O>(grid: &mut G,
     let lateral_max = cmp::min(lateral_max, octant.lateral_max(static_params.centre));
 
     let mut prev_visibility = 0.0;
+    let mut prev_filter = NO_FILTER;
     let mut prev_opaque = false;
 
     for lateral_index in lateral_min..=lateral_max {
@@ -108,6 +134,12 @@ fn scan<G, O>(grid: &mut G,
         let cur_visibility = (visibility - sh_cell.opacity_total).max(0.0);
         let cur_opaque = cur_visibility == 0.0;
 
+        // tinted cells are lit with the colour they let through
+        let cur_filter = apply_tints(filter, sh_cell, static_params.entity_store);
+
+        // partially opaque cells, such as gas, dim the light reaching the cell as well as hiding what's beyond
+        let lit_filter = dim(cur_filter, visibility);
+
         if cur_opaque {
             // check if we can actually see the facing side
             if max_gradient.lateral * front_gradient_depth > gradient_lateral * max_gradient.depth {
@@ -119,10 +151,12 @@ fn scan<G, O>(grid: &mut G,
             direction_bitmap |= DirectionBitmap::all();
         };
 
-        // handle changes in opacity
-        if lateral_index != lateral_min && cur_visibility != prev_visibility {
+        // handle changes in opacity or tint
+        if lateral_index != lateral_min && (cur_visibility != prev_visibility || cur_filter != prev_filter) {
             // use the back of the cell if necessary
-            let gradient_depth = if cur_visibility < prev_visibility {
+            let restricting = cur_visibility < prev_visibility ||
+                (cur_visibility == prev_visibility && filter_total(cur_filter) < filter_total(prev_filter));
+            let gradient_depth = if restricting {
                 back_gradient_depth
             } else {
                 front_gradient_depth
@@ -136,6 +170,7 @@ fn scan<G, O>(grid: &mut G,
                     max_gradient: gradient,
                     depth: depth + 1,
                     visibility: prev_visibility,
+                    filter: prev_filter,
                 });
             }
 
@@ -155,21 +190,24 @@ fn scan<G, O>(grid: &mut G,
                     max_gradient,
                     depth: depth + 1,
                     visibility: cur_visibility,
+                    filter: cur_filter,
                 });
             }
             if in_range && lateral_index == depth {
                 return Some(CornerInfo {
                     bitmap: direction_bitmap,
+                    filter: lit_filter,
                     coord,
                 });
             }
         }
 
         if in_range && octant.should_see(lateral_index) {
-            grid.see(coord_u32, direction_bitmap, static_params.time);
+            grid.see(coord_u32, direction_bitmap, lit_filter, static_params.time);
         }
 
         prev_visibility = cur_visibility;
+        prev_filter = cur_filter;
         prev_opaque = cur_opaque;
     }
 
@@ -194,6 +232,11 @@ impl ShadowcastEnv {
     }
 }
 
+// the diagonal between two octants is scanned by both, so takes the light from whichever lets through more
+fn brightest_filter(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0].max(b[0]), a[1].max(b[1]), a[2].max(b[2])]
+}
+
 fn observe_octant<G, A, B>(grid: &mut G,
                            env: &mut ShadowcastEnv,
                            octant_a: A,
@@ -208,11 +251,13 @@ fn observe_octant<G, A, B>(grid: &mut G,
 
     loop {
         let mut corner_bitmap = DirectionBitmap::empty();
+        let mut corner_filter = [0.0; 3];
         let mut corner_coord = None;
 
         while let Some(params) = env.queue_a.pop() {
             if let Some(corner) = scan(grid, &octant_a, &mut env.queue_a_swap, params, static_params) {
                 corner_bitmap |= corner.bitmap;
+                corner_filter = brightest_filter(corner_filter, corner.filter);
                 corner_coord = Some(corner.coord);
             }
         }
@@ -220,11 +265,12 @@ fn observe_octant<G, A, B>(grid: &mut G,
         while let Some(params) = env.queue_b.pop() {
             if let Some(corner) = scan(grid, &octant_b, &mut env.queue_b_swap, params, static_params) {
                 corner_bitmap |= corner.bitmap;
+                corner_filter = brightest_filter(corner_filter, corner.filter);
             }
         }
 
         if let Some(corner_coord) = corner_coord {
-            grid.see(corner_coord.cast(), corner_bitmap, static_params.time);
+            grid.see(corner_coord.cast(), corner_bitmap, corner_filter, static_params.time);
         }
 
         if env.queue_a_swap.is_empty() && env.queue_b_swap.is_empty() {
@@ -239,6 +285,7 @@ pub fn observe<G>(grid: &mut G,
                   env: &mut ShadowcastEnv,
                   position: Vector2<f32>,
                   spatial_hash: &SpatialHashTable,
+                  entity_store: &EntityStore,
                   distance: u32,
                   time: u64)
     where G: VisionGrid,
@@ -246,7 +293,7 @@ pub fn observe<G>(grid: &mut G,
     let coord = (position + Vector2::new(0.5, 0.5)).cast();
 
     if coord.x >= 0 && coord.y >= 0 {
-        grid.see(Vector2::new(coord.x as u32, coord.y as u32), DirectionBitmap::all(), time);
+        grid.see(Vector2::new(coord.x as u32, coord.y as u32), DirectionBitmap::all(), NO_FILTER, time);
     }
 
     let width = spatial_hash.width() as i32;
@@ -257,6 +304,7 @@ pub fn observe<G>(grid: &mut G,
         vision_distance_squared: (distance * distance) as i32,
         time,
         spatial_hash,
+        entity_store,
     };
 
     observe_octant(grid, env, TopLeft, LeftTop, &params);